@@ -1,12 +1,379 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 
-use ck_dodo::curve::field::Fp;
+use ck_dodo::curve::{
+    edwards::{
+        CompressedEdwardsY, EdwardsBasepointTable, EdwardsPoint, EdwardsPrecomputation,
+        VartimeEdwardsPrecomputation, ED25519_BASEPOINT_COMPRESSED, ED25519_BASEPOINT_POINT,
+        ED25519_BASEPOINT_TABLE,
+    },
+    field::{reference, Fp},
+    montgomery::X25519_BASEPOINT,
+    ristretto::{
+        RistrettoPoint, RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_POINT,
+        RISTRETTO_BASEPOINT_TABLE,
+    },
+    scalar::Scalar,
+    traits::{MultiscalarMul, VartimeMultiscalarMul, VartimePrecomputedMultiscalarMul},
+};
+
+/// The name of the backend used for field elements, as selected by this crate's features.
+///
+/// Backends are chosen at compile time, so comparing them means running these benchmarks
+/// once per backend, e.g. with `--features u51-backend`. Including the name in each
+/// benchmark's id keeps the results of different runs apart.
+fn backend() -> &'static str {
+    if cfg!(feature = "fiat-backend") {
+        "fiat"
+    } else if cfg!(feature = "u51-backend") {
+        "u51"
+    } else if cfg!(any(feature = "u32-backend", target_pointer_width = "32")) {
+        "u32"
+    } else {
+        "saturated"
+    }
+}
+
+/// Benchmark the operations shared by all of our field types.
+///
+/// The generic reference fields don't share a trait with `Fp`, so this is a macro.
+macro_rules! bench_common_ops {
+    ($c:expr, $name:expr, $Fp:ty) => {{
+        let a = <$Fp>::from_bytes_wide(&[0xAB; 64]);
+        let b = <$Fp>::from_bytes_wide(&[0xCD; 64]);
+        let mut group = $c.benchmark_group(format!("field/{}", $name));
+        group.throughput(Throughput::Elements(1));
+        group.bench_function("add", |bench| bench.iter(|| black_box(a) + black_box(b)));
+        group.bench_function("sub", |bench| bench.iter(|| black_box(a) - black_box(b)));
+        group.bench_function("neg", |bench| bench.iter(|| -black_box(a)));
+        group.bench_function("mul", |bench| bench.iter(|| black_box(a) * black_box(b)));
+        group.bench_function("square", |bench| bench.iter(|| black_box(a).square()));
+        group.bench_function("invert", |bench| bench.iter(|| black_box(a).invert()));
+        group.bench_function("vartime_invert", |bench| {
+            bench.iter(|| black_box(a).vartime_invert())
+        });
+        group.bench_function("to_bytes", |bench| bench.iter(|| black_box(a).to_bytes()));
+        group
+    }};
+}
 
 fn fp_benchmark(c: &mut Criterion) {
-    let a = Fp::constant();
-    c.bench_function("Fp *=", |b| b.iter(|| *(&mut black_box(a)) *= black_box(a)));
+    let a = Fp::from_bytes(&[0xAB; 32]);
+    let mut group = bench_common_ops!(c, backend(), Fp);
+    group.bench_function("mul_small", |b| {
+        b.iter(|| black_box(a).mul_small(black_box(121666)))
+    });
+    group.bench_function("double", |b| b.iter(|| black_box(a).double()));
+    group.bench_function("invert_safegcd", |b| {
+        b.iter(|| black_box(a).invert_safegcd())
+    });
+    group.bench_function("sqrt", |b| b.iter(|| black_box(a).sqrt()));
+    group.bench_function("from_bytes", |b| {
+        b.iter(|| Fp::from_bytes(black_box(&[0xAB; 32])))
+    });
+    group.finish();
+
+    // For batch inversion, the interesting number is the cost per element.
+    let mut group = c.benchmark_group(format!("field/{}/batch_invert", backend()));
+    for n in [1, 16, 256] {
+        let elements: Vec<Fp> = (1..=n).map(|i| a.mul_small(i)).collect();
+        group.throughput(Throughput::Elements(u64::from(n)));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &elements, |b, elements| {
+            b.iter(|| {
+                let mut elements = elements.clone();
+                Fp::batch_invert(black_box(&mut elements));
+                elements
+            })
+        });
+    }
+    group.finish();
+}
+
+/// The name of the reduction strategy used for scalars, as selected by this crate's features.
+fn scalar_backend() -> &'static str {
+    if cfg!(any(feature = "u32-backend", target_pointer_width = "32")) {
+        "montgomery32"
+    } else if cfg!(feature = "montgomery-scalars") {
+        "montgomery"
+    } else {
+        "barrett"
+    }
+}
+
+fn scalar_benchmark(c: &mut Criterion) {
+    let a = Scalar::from_bytes_mod_order_wide(&[0xAB; 64]);
+    let b = Scalar::from_bytes_mod_order_wide(&[0xCD; 64]);
+    let mut group = c.benchmark_group(format!("scalar/{}", scalar_backend()));
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("add", |bench| bench.iter(|| black_box(a) + black_box(b)));
+    group.bench_function("mul", |bench| bench.iter(|| black_box(a) * black_box(b)));
+    group.bench_function("mul_add", |bench| {
+        bench.iter(|| Scalar::mul_add(&black_box(a), &black_box(b), &black_box(a)))
+    });
+    group.bench_function("square", |bench| bench.iter(|| black_box(a).square()));
+    group.bench_function("invert", |bench| bench.iter(|| black_box(a).invert()));
+    group.bench_function("from_bytes_mod_order_wide", |bench| {
+        bench.iter(|| Scalar::from_bytes_mod_order_wide(black_box(&[0xAB; 64])))
+    });
+    group.bench_function("to_bytes", |bench| bench.iter(|| black_box(a).to_bytes()));
+    group.finish();
+}
+
+fn edwards_benchmark(c: &mut Criterion) {
+    let p = ED25519_BASEPOINT_POINT.double();
+    let q = ED25519_BASEPOINT_POINT;
+    let s = Scalar::from_bytes_mod_order_wide(&[0xAB; 64]);
+    let table = EdwardsBasepointTable::new(&ED25519_BASEPOINT_POINT);
+    let mut group = c.benchmark_group(format!("edwards/{}", backend()));
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("add", |b| b.iter(|| black_box(p) + black_box(q)));
+    group.bench_function("add_projective_niels", |b| {
+        let q = q.to_projective_niels();
+        b.iter(|| &black_box(p) + &black_box(q))
+    });
+    group.bench_function("add_affine_niels", |b| {
+        let q = q.to_affine_niels();
+        b.iter(|| &black_box(p) + &black_box(q))
+    });
+    group.bench_function("sub", |b| b.iter(|| black_box(p) - black_box(q)));
+    group.bench_function("neg", |b| b.iter(|| -black_box(p)));
+    group.bench_function("double", |b| b.iter(|| black_box(p).double()));
+    group.bench_function("mul_by_pow_2/8", |b| {
+        b.iter(|| black_box(p).mul_by_pow_2(black_box(8)))
+    });
+    group.bench_function("mul_by_cofactor", |b| {
+        b.iter(|| black_box(p).mul_by_cofactor())
+    });
+    group.bench_function("is_torsion_free", |b| {
+        b.iter(|| black_box(p).is_torsion_free())
+    });
+    group.bench_function("compress", |b| b.iter(|| black_box(p).compress()));
+    group.bench_function("decompress", |b| {
+        b.iter(|| black_box(ED25519_BASEPOINT_COMPRESSED).decompress())
+    });
+    group.bench_function("basepoint_table_new", |b| {
+        b.iter(|| EdwardsBasepointTable::new(&black_box(q)))
+    });
+    group.bench_function("basepoint_table_mul", |b| {
+        b.iter(|| black_box(&table) * &black_box(s))
+    });
+    group.bench_function("ed25519_basepoint_table_mul", |b| {
+        b.iter(|| &ED25519_BASEPOINT_TABLE * &black_box(s))
+    });
+    // There's no dedicated constant time multiplication for a variable base, so this
+    // goes through a multiscalar multiplication with a single point.
+    group.bench_function("mul", |b| {
+        b.iter(|| EdwardsPoint::multiscalar_mul([black_box(s)], [black_box(p)]))
+    });
+    group.bench_function("vartime_mul", |b| {
+        b.iter(|| black_box(p).vartime_mul(&black_box(s)))
+    });
+    group.bench_function("precomputation_new", |b| {
+        b.iter(|| EdwardsPrecomputation::new(&black_box(p)))
+    });
+    group.bench_function("precomputation_vartime_mul", |b| {
+        let precomputation = EdwardsPrecomputation::new(&p);
+        b.iter(|| black_box(&precomputation).vartime_mul(&black_box(s)))
+    });
+    group.bench_function("double_scalar_mul_basepoint", |b| {
+        b.iter(|| {
+            EdwardsPoint::multiscalar_mul(
+                [black_box(s), black_box(s)],
+                [black_box(p), ED25519_BASEPOINT_POINT],
+            )
+        })
+    });
+    group.bench_function("vartime_double_scalar_mul_basepoint", |b| {
+        b.iter(|| {
+            EdwardsPoint::vartime_double_scalar_mul_basepoint(
+                &black_box(s),
+                &black_box(p),
+                &black_box(s),
+            )
+        })
+    });
+    group.finish();
+
+    // For multiscalar multiplication, the interesting number is the cost per point.
+    for (name, vartime) in [
+        ("multiscalar_mul", false),
+        ("vartime_multiscalar_mul", true),
+    ] {
+        let mut group = c.benchmark_group(format!("edwards/{}/{}", backend(), name));
+        // Pippenger's method only kicks in for many points, and only in variable time.
+        let sizes: &[u64] = if vartime {
+            &[4, 16, 64, 256, 1024]
+        } else {
+            &[4, 16, 64, 256]
+        };
+        for &n in sizes {
+            let scalars: Vec<Scalar> = (1..=n).map(|i| s * Scalar::from(i)).collect();
+            let points: Vec<EdwardsPoint> = (1..=n).map(|i| &table * &Scalar::from(i)).collect();
+            group.throughput(Throughput::Elements(n));
+            group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+                if vartime {
+                    b.iter(|| EdwardsPoint::vartime_multiscalar_mul(&scalars, &points))
+                } else {
+                    b.iter(|| EdwardsPoint::multiscalar_mul(&scalars, &points))
+                }
+            });
+        }
+        group.finish();
+    }
+
+    // Half of the points are static, and half are dynamic, as when verifying a proof.
+    let mut group = c.benchmark_group(format!(
+        "edwards/{}/vartime_precomputed_multiscalar_mul",
+        backend()
+    ));
+    for n in [4u64, 16, 64, 256] {
+        let scalars: Vec<Scalar> = (1..=n).map(|i| s * Scalar::from(i)).collect();
+        let points: Vec<EdwardsPoint> = (1..=n).map(|i| &table * &Scalar::from(i)).collect();
+        let half = n as usize / 2;
+        let precomputation = VartimeEdwardsPrecomputation::new(&points[..half]);
+        group.throughput(Throughput::Elements(n));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| {
+                precomputation.vartime_mixed_multiscalar_mul(
+                    &scalars[..half],
+                    &scalars[half..],
+                    &points[half..],
+                )
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group(format!("edwards/{}/batch_decompress", backend()));
+    for n in [1u64, 4, 16, 64] {
+        let encoded: Vec<CompressedEdwardsY> = (1..=n)
+            .map(|i| (&table * &Scalar::from(i)).compress())
+            .collect();
+        group.throughput(Throughput::Elements(n));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| CompressedEdwardsY::batch_decompress(black_box(&encoded)))
+        });
+    }
+    group.finish();
+}
+
+fn ristretto_benchmark(c: &mut Criterion) {
+    let s = Scalar::from_bytes_mod_order_wide(&[0xAB; 64]);
+    let p = RISTRETTO_BASEPOINT_POINT * s;
+    let mut group = c.benchmark_group(format!("ristretto/{}", backend()));
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("compress", |b| b.iter(|| black_box(p).compress()));
+    group.bench_function("decompress", |b| {
+        b.iter(|| black_box(RISTRETTO_BASEPOINT_COMPRESSED).decompress())
+    });
+    group.bench_function("from_uniform_bytes", |b| {
+        b.iter(|| RistrettoPoint::from_uniform_bytes(black_box(&[0xCD; 64])))
+    });
+    group.bench_function("basepoint_table_mul", |b| {
+        b.iter(|| RISTRETTO_BASEPOINT_TABLE * &black_box(s))
+    });
+    group.finish();
+
+    // The cost per element, which should approach a few multiplications.
+    let mut group = c.benchmark_group(format!("ristretto/{}/double_and_compress_batch", backend()));
+    for n in [1u64, 16, 256] {
+        let points: Vec<RistrettoPoint> = (1..=n)
+            .map(|i| RISTRETTO_BASEPOINT_POINT * Scalar::from(i))
+            .collect();
+        group.throughput(Throughput::Elements(n));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| RistrettoPoint::double_and_compress_batch(black_box(&points)))
+        });
+    }
+    group.finish();
+}
+
+fn montgomery_benchmark(c: &mut Criterion) {
+    let p = X25519_BASEPOINT.mul_clamped([0xCD; 32]);
+    let s = Scalar::from_bytes_mod_order_wide(&[0xAB; 64]);
+    let mut group = c.benchmark_group(format!("montgomery/{}", backend()));
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("mul", |b| b.iter(|| &black_box(p) * &black_box(s)));
+    group.bench_function("edwards_mul_ladder", |b| {
+        b.iter(|| black_box(ED25519_BASEPOINT_POINT).mul_ladder(&black_box(s)))
+    });
+    group.bench_function("mul_clamped", |b| {
+        b.iter(|| black_box(p).mul_clamped(black_box([0xAB; 32])))
+    });
+    group.finish();
+}
+
+#[cfg(feature = "ed25519")]
+fn ed25519_benchmark(c: &mut Criterion) {
+    use ck_dodo::sign::{verify_batch, Signature, SigningKey, VerifyingKey};
+
+    let key = SigningKey::from_seed([0xAB; 32]);
+    let msg = [0xCD; 64];
+    let signature = key.sign(&msg);
+    let verifying_key = key.verifying_key();
+    let mut group = c.benchmark_group(format!("ed25519/{}", backend()));
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("sign", |b| b.iter(|| key.sign(black_box(&msg))));
+    group.bench_function("verify", |b| {
+        b.iter(|| verifying_key.verify(black_box(&msg), black_box(&signature)))
+    });
+    group.finish();
+
+    // Verifying the same signatures one at a time, and as a batch, with the cost per
+    // signature as the interesting number.
+    let sizes = [4u64, 16, 64, 256, 1024];
+    let keys: Vec<SigningKey> = (0..sizes[sizes.len() - 1])
+        .map(|i| SigningKey::from_seed(Scalar::from(i).to_bytes()))
+        .collect();
+    let messages: Vec<[u8; 64]> = (0..keys.len()).map(|i| [i as u8; 64]).collect();
+    let signatures: Vec<Signature> = keys.iter().zip(&messages).map(|(k, m)| k.sign(m)).collect();
+    let keys: Vec<VerifyingKey> = keys.iter().map(|k| k.verifying_key()).collect();
+    let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+    for (name, batch) in [("verify_each", false), ("verify_batch", true)] {
+        let mut group = c.benchmark_group(format!("ed25519/{}/{}", backend(), name));
+        for &n in &sizes {
+            let n_usize = n as usize;
+            let (messages, signatures, keys) = (
+                &messages[..n_usize],
+                &signatures[..n_usize],
+                &keys[..n_usize],
+            );
+            group.throughput(Throughput::Elements(n));
+            group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+                if batch {
+                    b.iter(|| verify_batch(messages, signatures, keys))
+                } else {
+                    b.iter(|| {
+                        messages
+                            .iter()
+                            .zip(signatures)
+                            .zip(keys)
+                            .try_for_each(|((m, s), k)| k.verify(m, s))
+                    })
+                }
+            });
+        }
+        group.finish();
+    }
+}
+
+#[cfg(not(feature = "ed25519"))]
+fn ed25519_benchmark(_: &mut Criterion) {}
+
+fn reference_benchmark(c: &mut Criterion) {
+    bench_common_ops!(c, "reference", reference::Fp).finish();
+    bench_common_ops!(c, "reference-montgomery", reference::MontgomeryFp).finish();
+    bench_common_ops!(c, "reference-barrett", reference::BarrettFp).finish();
 }
 
-criterion_group!(benches, fp_benchmark);
+criterion_group!(
+    benches,
+    fp_benchmark,
+    scalar_benchmark,
+    edwards_benchmark,
+    ristretto_benchmark,
+    montgomery_benchmark,
+    ed25519_benchmark,
+    reference_benchmark
+);
 
 criterion_main!(benches);
@@ -0,0 +1,194 @@
+//! Precompute the basepoint table, so that it can be embedded in the crate as a constant.
+//!
+//! This can't use the crate itself, so it has its own minimal field arithmetic. None of
+//! this needs to be fast, or constant time: everything here is public.
+use std::{env, fs, path::Path};
+
+/// An element of the field mod P = 2²⁵⁵ - 19, as little endian limbs, always reduced.
+type Fe = [u64; 4];
+
+const P: Fe = [
+    0xFFFF_FFFF_FFFF_FFED,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0x7FFF_FFFF_FFFF_FFFF,
+];
+
+fn from_u64(x: u64) -> Fe {
+    [x, 0, 0, 0]
+}
+
+fn from_bytes(bytes: &[u8; 32]) -> Fe {
+    std::array::from_fn(|i| u64::from_le_bytes(bytes[8 * i..8 * i + 8].try_into().unwrap()))
+}
+
+fn to_bytes(a: &Fe) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, limb) in a.iter().enumerate() {
+        out[8 * i..8 * i + 8].copy_from_slice(&limb.to_le_bytes());
+    }
+    out
+}
+
+/// Subtract P, if a ≥ P.
+fn reduce_once(a: Fe) -> Fe {
+    let mut out = [0u64; 4];
+    let mut borrow = 0u128;
+    for i in 0..4 {
+        let diff = (a[i] as u128).wrapping_sub(P[i] as u128 + borrow);
+        out[i] = diff as u64;
+        borrow = diff >> 127;
+    }
+    if borrow == 0 {
+        out
+    } else {
+        a
+    }
+}
+
+fn add(a: &Fe, b: &Fe) -> Fe {
+    // Both are < 2²⁵⁵, so the sum fits in 256 bits.
+    let mut out = [0u64; 4];
+    let mut carry = 0u128;
+    for i in 0..4 {
+        let sum = a[i] as u128 + b[i] as u128 + carry;
+        out[i] = sum as u64;
+        carry = sum >> 64;
+    }
+    reduce_once(out)
+}
+
+fn neg(a: &Fe) -> Fe {
+    // P - a, which is P for a = 0, and needs reducing.
+    let mut out = [0u64; 4];
+    let mut borrow = 0u128;
+    for i in 0..4 {
+        let diff = (P[i] as u128).wrapping_sub(a[i] as u128 + borrow);
+        out[i] = diff as u64;
+        borrow = diff >> 127;
+    }
+    reduce_once(out)
+}
+
+fn sub(a: &Fe, b: &Fe) -> Fe {
+    add(a, &neg(b))
+}
+
+fn mul(a: &Fe, b: &Fe) -> Fe {
+    let mut wide = [0u64; 8];
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 {
+            let x = wide[i + j] as u128 + a[i] as u128 * b[j] as u128 + carry;
+            wide[i + j] = x as u64;
+            carry = x >> 64;
+        }
+        wide[i + 4] = carry as u64;
+    }
+    // 2²⁵⁶ ≡ 38, so we fold the top half into the bottom, until nothing is left over.
+    let mut out = [0u64; 4];
+    let mut carry = 0u128;
+    for i in 0..4 {
+        let x = wide[i] as u128 + 38 * wide[i + 4] as u128 + carry;
+        out[i] = x as u64;
+        carry = x >> 64;
+    }
+    while carry != 0 {
+        let mut folded = 38 * carry;
+        for limb in out.iter_mut() {
+            let x = *limb as u128 + folded;
+            *limb = x as u64;
+            folded = x >> 64;
+        }
+        carry = folded;
+    }
+    // What's left is < 2²⁵⁶ = 2⋅P + 38, so at most two subtractions are needed.
+    reduce_once(reduce_once(out))
+}
+
+fn invert(a: &Fe) -> Fe {
+    // a^(P - 2), by square and multiply.
+    let mut e = P;
+    e[0] -= 2;
+    let mut out = from_u64(1);
+    for i in (0..255).rev() {
+        out = mul(&out, &out);
+        if (e[i / 64] >> (i % 64)) & 1 == 1 {
+            out = mul(&out, a);
+        }
+    }
+    out
+}
+
+/// An affine point on edwards25519.
+#[derive(Clone, Copy)]
+struct Point {
+    x: Fe,
+    y: Fe,
+}
+
+/// Add two points, with the affine formulas, using d = -121665 / 121666.
+fn point_add(d: &Fe, p: &Point, q: &Point) -> Point {
+    let dxy = mul(d, &mul(&mul(&p.x, &q.x), &mul(&p.y, &q.y)));
+    let one = from_u64(1);
+    let x = add(&mul(&p.x, &q.y), &mul(&p.y, &q.x));
+    let y = add(&mul(&p.y, &q.y), &mul(&p.x, &q.x));
+    Point {
+        x: mul(&x, &invert(&add(&one, &dxy))),
+        y: mul(&y, &invert(&sub(&one, &dxy))),
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let d = mul(&neg(&from_u64(121665)), &invert(&from_u64(121666)));
+    let d2 = add(&d, &d);
+    // The basepoint from RFC 8032.
+    let mut base = Point {
+        x: from_bytes(&[
+            0x1A, 0xD5, 0x25, 0x8F, 0x60, 0x2D, 0x56, 0xC9, 0xB2, 0xA7, 0x25, 0x95, 0x60, 0xC7,
+            0x2C, 0x69, 0x5C, 0xDC, 0xD6, 0xFD, 0x31, 0xE2, 0xA4, 0xC0, 0xFE, 0x53, 0x6E, 0xCD,
+            0xD3, 0x36, 0x69, 0x21,
+        ]),
+        y: from_bytes(&{
+            let mut y = [0x66; 32];
+            y[0] = 0x58;
+            y
+        }),
+    };
+
+    // The same layout as EdwardsBasepointTable::new: for 0 ≤ i < tables, and 1 ≤ j ≤ 8,
+    // entry [i][j - 1] holds j⋅16ˢⁱ⋅B, as (y + x, y - x, 2⋅d⋅x⋅y), with s = 64 / tables.
+    let tables = if env::var_os("CARGO_FEATURE_SMALL_TABLES").is_some() {
+        4
+    } else {
+        32
+    };
+    let mut out = String::from("[\n");
+    for _ in 0..tables {
+        out.push_str("    [\n");
+        let mut acc = base;
+        for _ in 0..8 {
+            let entry = [
+                add(&acc.y, &acc.x),
+                sub(&acc.y, &acc.x),
+                mul(&d2, &mul(&acc.x, &acc.y)),
+            ];
+            out.push_str("        [\n");
+            for fe in &entry {
+                out.push_str(&format!("            {:?},\n", to_bytes(fe)));
+            }
+            out.push_str("        ],\n");
+            acc = point_add(&d, &acc, &base);
+        }
+        out.push_str("    ],\n");
+        for _ in 0..4 * (64 / tables) {
+            base = point_add(&d, &base, &base);
+        }
+    }
+    out.push_str("]\n");
+
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("basepoint_table.rs");
+    fs::write(path, out).unwrap();
+}
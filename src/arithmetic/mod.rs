@@ -0,0 +1,519 @@
+//! Arithmetic on multi-limb integers, independent of any particular modulus.
+//!
+//! These are the building blocks of [`define_prime_field`], which produces a field
+//! for an arbitrary modulus from them. Everything here runs in constant time.
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64 as arch;
+
+use subtle::{Choice, ConditionallySelectable};
+
+pub(crate) mod safegcd;
+
+/// adc computes out <- a + b + carry, outputting a new carry.
+///
+/// `carry` must be 0, or 1. The return value will satisfy this constraint
+#[inline]
+pub fn adc(carry: u8, a: u64, b: u64, out: &mut u64) -> u8 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        arch::_addcarry_u64(carry, a, b, out)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        // The largest result is 2 * (2^64 - 1) + 1 = 2^65 - 1, which needs exactly 65 bits
+        // Hence, we use u128. Hopefully, Rust will realize that we don't really want to use
+        // 128 bit operations, but rather want to use an `adc` instruction, or whatever equivalent
+        // our ISA has, and insert that instead.
+        let full_res = u128::from(a) + u128::from(b) + u128::from(carry);
+        *out = full_res as u64;
+        (full_res >> 64) as u8
+    }
+}
+
+/// sbb computes out <- a - (b + borrow), outputting a new borrow.
+///
+/// `borrow` must be 0, or 1. The return value will satisfy this constraint
+#[inline]
+pub fn sbb(borrow: u8, a: u64, b: u64, out: &mut u64) -> u8 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        arch::_subborrow_u64(borrow, a, b, out)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        // If the subtraction underflows, the wrapped result will have its top bit set,
+        // since the magnitude of the true result is at most 2^64.
+        let full_res = u128::from(a).wrapping_sub(u128::from(b) + u128::from(borrow));
+        *out = full_res as u64;
+        (full_res >> 127) as u8
+    }
+}
+
+/// Calculate a + b, returning the result, along with the carry out of the top limb.
+#[inline]
+pub(crate) fn add<const N: usize>(a: &[u64; N], b: &[u64; N]) -> ([u64; N], u8) {
+    let mut out = [0u64; N];
+    let mut carry = 0;
+    for i in 0..N {
+        carry = adc(carry, a[i], b[i], &mut out[i]);
+    }
+    (out, carry)
+}
+
+/// Calculate a - b, returning the result, along with the borrow out of the top limb.
+#[inline]
+pub(crate) fn sub<const N: usize>(a: &[u64; N], b: &[u64; N]) -> ([u64; N], u8) {
+    let mut out = [0u64; N];
+    let mut borrow = 0;
+    for i in 0..N {
+        borrow = sbb(borrow, a[i], b[i], &mut out[i]);
+    }
+    (out, borrow)
+}
+
+/// Given a + carry⋅2⁶⁴ᴺ < 2m, reduce it to the range [0, m).
+#[inline]
+pub(crate) fn sub_if_ge<const N: usize>(a: &[u64; N], carry: u8, m: &[u64; N]) -> [u64; N] {
+    let (diff, borrow) = sub(a, m);
+    // If we carried, then a ≥ 2⁶⁴ᴺ > m, and the subtraction's borrow is cancelled out.
+    let ge = Choice::from(carry | (borrow ^ 1));
+    let mut out = [0u64; N];
+    for i in 0..N {
+        out[i] = u64::conditional_select(&a[i], &diff[i], ge);
+    }
+    out
+}
+
+/// Calculate (a + b) mod m, given a, b < m.
+#[inline]
+pub(crate) fn add_mod<const N: usize>(a: &[u64; N], b: &[u64; N], m: &[u64; N]) -> [u64; N] {
+    let (sum, carry) = add(a, b);
+    sub_if_ge(&sum, carry, m)
+}
+
+/// Calculate (a - b) mod m, given a, b < m.
+#[inline]
+pub(crate) fn sub_mod<const N: usize>(a: &[u64; N], b: &[u64; N], m: &[u64; N]) -> [u64; N] {
+    let (diff, borrow) = sub(a, b);
+    // If we borrowed, we've calculated a - b + 2⁶⁴ᴺ, and adding m back wraps around.
+    let mut masked = [0u64; N];
+    for i in 0..N {
+        masked[i] = m[i] & 0u64.wrapping_sub(u64::from(borrow));
+    }
+    add(&diff, &masked).0
+}
+
+/// Calculate the full product of a and b, writing it into out, which must have 2N limbs.
+#[inline]
+pub(crate) fn mul_wide<const N: usize>(a: &[u64; N], b: &[u64; N], out: &mut [u64]) {
+    debug_assert_eq!(out.len(), 2 * N);
+    out.fill(0);
+    for i in 0..N {
+        let mut carry = 0u64;
+        for j in 0..N {
+            // This can't overflow, since (2⁶⁴ - 1)² + 2⋅(2⁶⁴ - 1) = 2¹²⁸ - 1.
+            let full_res =
+                u128::from(a[i]) * u128::from(b[j]) + u128::from(out[i + j]) + u128::from(carry);
+            out[i + j] = full_res as u64;
+            carry = (full_res >> 64) as u64;
+        }
+        out[i + N] = carry;
+    }
+}
+
+/// Add c into a wider integer, in place, which must not overflow.
+#[inline]
+pub(crate) fn add_into<const N: usize>(wide: &mut [u64], c: &[u64; N]) {
+    debug_assert!(wide.len() >= N);
+    let mut carry = 0;
+    for (i, w) in wide.iter_mut().enumerate() {
+        let c_i = if i < N { c[i] } else { 0 };
+        carry = adc(carry, *w, c_i, w);
+    }
+    debug_assert_eq!(carry, 0);
+}
+
+/// Reduce an integer of any size mod m, which must be odd.
+///
+/// This works with one bit at a time, from the top down, calculating x ← 2x + bit mod m.
+/// That's slow, but doesn't depend on the form of the modulus at all.
+pub(crate) fn reduce_wide<const N: usize>(wide: &[u64], m: &[u64; N]) -> [u64; N] {
+    let mut out = [0u64; N];
+    for limb in wide.iter().rev() {
+        for j in (0..64).rev() {
+            let (mut doubled, carry) = add(&out, &out);
+            // doubled is even, so this can't carry.
+            doubled[0] |= (limb >> j) & 1;
+            out = sub_if_ge(&doubled, carry, m);
+        }
+    }
+    out
+}
+
+// The Montgomery routines below are const fns, so that fields using them can still
+// define constants. This means using plain u128 arithmetic instead of `adc`, and
+// masks instead of `subtle`, but these compile down to the same constant time code.
+
+/// Select a if mask is all zeros, and b if mask is all ones.
+#[inline(always)]
+const fn select_mask<const N: usize>(a: &[u64; N], b: &[u64; N], mask: u64) -> [u64; N] {
+    let mut out = [0u64; N];
+    let mut i = 0;
+    while i < N {
+        out[i] = a[i] ^ (mask & (a[i] ^ b[i]));
+        i += 1;
+    }
+    out
+}
+
+/// Given a + carry⋅2⁶⁴ᴺ < 2m, reduce it to the range [0, m), in a const context.
+#[inline(always)]
+const fn sub_if_ge_const<const N: usize>(a: &[u64; N], carry: u64, m: &[u64; N]) -> [u64; N] {
+    let mut diff = [0u64; N];
+    let mut borrow = 0u64;
+    let mut i = 0;
+    while i < N {
+        let full_res = (a[i] as u128).wrapping_sub(m[i] as u128 + borrow as u128);
+        diff[i] = full_res as u64;
+        borrow = (full_res >> 127) as u64;
+        i += 1;
+    }
+    // See `sub_if_ge` for why this is the right condition.
+    let ge = carry | (borrow ^ 1);
+    select_mask(a, &diff, 0u64.wrapping_sub(ge))
+}
+
+/// Calculate -m⁻¹ mod 2⁶⁴, for an odd m.
+pub(crate) const fn neg_inv64(m: u64) -> u64 {
+    // Since m⋅m ≡ 1 mod 8, m is its own inverse to 3 bits, and then each step of
+    // Newton's iteration doubles the number of correct bits: 3 → 6 → 12 → 24 → 48 → 96.
+    let mut inv = m;
+    let mut i = 0;
+    while i < 5 {
+        inv = inv.wrapping_mul(2u64.wrapping_sub(m.wrapping_mul(inv)));
+        i += 1;
+    }
+    inv.wrapping_neg()
+}
+
+/// Calculate 2ᵏ mod m, for an odd m > 1.
+pub(crate) const fn pow2_mod<const N: usize>(k: usize, m: &[u64; N]) -> [u64; N] {
+    let mut out = [0u64; N];
+    out[0] = 1;
+    let mut i = 0;
+    while i < k {
+        let mut doubled = [0u64; N];
+        let mut j = 0;
+        while j < N {
+            doubled[j] = (out[j] << 1) | if j > 0 { out[j - 1] >> 63 } else { 0 };
+            j += 1;
+        }
+        out = sub_if_ge_const(&doubled, out[N - 1] >> 63, m);
+        i += 1;
+    }
+    out
+}
+
+/// Calculate a⋅b⋅2⁻⁶⁴ᴺ mod m, given a, b < m, and m_inv = -m⁻¹ mod 2⁶⁴.
+///
+/// This interleaves the multiplication with Montgomery reduction, one limb at a
+/// time, which is sometimes called the CIOS method.
+pub(crate) const fn mont_mul<const N: usize>(
+    a: &[u64; N],
+    b: &[u64; N],
+    m: &[u64; N],
+    m_inv: u64,
+) -> [u64; N] {
+    // The accumulator is always < 2m, so it fits in N + 1 limbs, with t_hi holding
+    // the top one. During each step, we need one more bit, which goes into t_top.
+    let mut t = [0u64; N];
+    let mut t_hi = 0u64;
+    let mut i = 0;
+    while i < N {
+        // t ← t + a[i]⋅b
+        let mut carry = 0u64;
+        let mut j = 0;
+        while j < N {
+            let full_res = a[i] as u128 * b[j] as u128 + t[j] as u128 + carry as u128;
+            t[j] = full_res as u64;
+            carry = (full_res >> 64) as u64;
+            j += 1;
+        }
+        let full_res = t_hi as u128 + carry as u128;
+        t_hi = full_res as u64;
+        let t_top = (full_res >> 64) as u64;
+        // t ← (t + u⋅m) / 2⁶⁴, where u is chosen to make the low limb vanish.
+        let u = t[0].wrapping_mul(m_inv);
+        let full_res = u as u128 * m[0] as u128 + t[0] as u128;
+        carry = (full_res >> 64) as u64;
+        j = 1;
+        while j < N {
+            let full_res = u as u128 * m[j] as u128 + t[j] as u128 + carry as u128;
+            t[j - 1] = full_res as u64;
+            carry = (full_res >> 64) as u64;
+            j += 1;
+        }
+        let full_res = t_hi as u128 + carry as u128;
+        t[N - 1] = full_res as u64;
+        t_hi = t_top + (full_res >> 64) as u64;
+        i += 1;
+    }
+    sub_if_ge_const(&t, t_hi, m)
+}
+
+/// Read the ith 32 bit word of a, in little endian order.
+#[inline(always)]
+const fn word<const N: usize>(a: &[u64; N], i: usize) -> u32 {
+    (a[i / 2] >> (32 * (i % 2))) as u32
+}
+
+/// Calculate a⋅b⋅2⁻⁶⁴ᴺ mod m, given a, b < m, and m_inv = -m⁻¹ mod 2³².
+///
+/// This is the same as [`mont_mul`], but works with 32 bit words, so that it only
+/// needs 32 × 32 → 64 bit multiplication, which is much cheaper than 64 × 64 → 128 bit
+/// multiplication on 32 bit targets. Since 2N words make up 64N bits, both functions
+/// use the same Montgomery form, and give the same results.
+pub(crate) const fn mont_mul32<const N: usize>(
+    a: &[u64; N],
+    b: &[u64; N],
+    m: &[u64; N],
+    m_inv: u32,
+) -> [u64; N] {
+    // We can't write [u32; 2 * N] with a generic N, so we store the accumulator as
+    // pairs of words instead. Otherwise, this follows `mont_mul` exactly.
+    let w = 2 * N;
+    let mut t = [[0u32; 2]; N];
+    let mut t_hi = 0u32;
+    let mut i = 0;
+    while i < w {
+        // t ← t + a[i]⋅b
+        let a_i = word(a, i) as u64;
+        let mut carry = 0u64;
+        let mut j = 0;
+        while j < w {
+            let full_res = a_i * word(b, j) as u64 + t[j / 2][j % 2] as u64 + carry;
+            t[j / 2][j % 2] = full_res as u32;
+            carry = full_res >> 32;
+            j += 1;
+        }
+        let full_res = t_hi as u64 + carry;
+        t_hi = full_res as u32;
+        let t_top = (full_res >> 32) as u32;
+        // t ← (t + u⋅m) / 2³², where u is chosen to make the low word vanish.
+        let u = t[0][0].wrapping_mul(m_inv) as u64;
+        let full_res = u * word(m, 0) as u64 + t[0][0] as u64;
+        carry = full_res >> 32;
+        j = 1;
+        while j < w {
+            let full_res = u * word(m, j) as u64 + t[j / 2][j % 2] as u64 + carry;
+            t[(j - 1) / 2][(j - 1) % 2] = full_res as u32;
+            carry = full_res >> 32;
+            j += 1;
+        }
+        let full_res = t_hi as u64 + carry;
+        t[N - 1][1] = full_res as u32;
+        t_hi = t_top + (full_res >> 32) as u32;
+        i += 1;
+    }
+    let mut out = [0u64; N];
+    i = 0;
+    while i < N {
+        out[i] = t[i][0] as u64 | (t[i][1] as u64) << 32;
+        i += 1;
+    }
+    sub_if_ge_const(&out, t_hi as u64, m)
+}
+
+/// Calculate ⌊2¹²⁸ᴺ / m⌋, the constant Barrett reduction multiplies by.
+///
+/// The top limb of m must be non-zero, which makes the result fit in N + 1 limbs.
+/// We return its low N limbs, along with its top limb, separately.
+pub(crate) const fn barrett_mu<const N: usize>(m: &[u64; N]) -> ([u64; N], u64) {
+    // This is schoolbook long division, one bit at a time, from the top down.
+    // The remainder stays < m, so after doubling it, we only need one extra bit.
+    let mut r = [0u64; N];
+    let mut q = [0u64; N];
+    let mut q_hi = 0u64;
+    let mut bit = 128 * N + 1;
+    while bit > 0 {
+        bit -= 1;
+        let mut doubled = [0u64; N];
+        let mut j = 0;
+        while j < N {
+            doubled[j] = (r[j] << 1) | if j > 0 { r[j - 1] >> 63 } else { 0 };
+            j += 1;
+        }
+        // The dividend is 2¹²⁸ᴺ, which has only its top bit set.
+        if bit == 128 * N {
+            doubled[0] |= 1;
+        }
+        r = sub_if_ge_const(&doubled, r[N - 1] >> 63, m);
+        // We subtracted m exactly when the remainder changed.
+        let mut changed = 0;
+        j = 0;
+        while j < N {
+            changed |= r[j] ^ doubled[j];
+            j += 1;
+        }
+        if changed != 0 {
+            if bit < 64 * N {
+                q[bit / 64] |= 1 << (bit % 64);
+            } else {
+                q_hi |= 1 << (bit - 64 * N);
+            }
+        }
+    }
+    (q, q_hi)
+}
+
+/// A 192 bit accumulator, for summing up the products in a column of a multiplication.
+#[derive(Clone, Copy)]
+struct Column {
+    lo: u128,
+    hi: u64,
+}
+
+impl Column {
+    /// Add in the product a⋅b.
+    #[inline(always)]
+    fn mul_add(&mut self, a: u64, b: u64) {
+        let (sum, carry) = self.lo.overflowing_add(u128::from(a) * u128::from(b));
+        self.lo = sum;
+        self.hi += u64::from(carry);
+    }
+
+    /// Return the low limb, shifting the rest down, to carry into the next column.
+    #[inline(always)]
+    fn shift(&mut self) -> u64 {
+        let out = self.lo as u64;
+        self.lo = (self.lo >> 64) | (u128::from(self.hi) << 64);
+        self.hi = 0;
+        out
+    }
+}
+
+/// Reduce x < 2¹²⁸ᴺ mod m, given as 2N limbs, using Barrett reduction.
+///
+/// This needs mu = ⌊2¹²⁸ᴺ / m⌋, as returned by [`barrett_mu`], and the top limb of
+/// m to be non-zero. Multiplying by mu gives us an estimate of ⌊x / m⌋ which is off
+/// by at most 2, so only two conditional subtractions are needed at the end.
+/// This is Algorithm 14.42 of the Handbook of Applied Cryptography.
+pub(crate) fn barrett_reduce<const N: usize>(
+    x: &[u64],
+    m: &[u64; N],
+    mu: &([u64; N], u64),
+) -> [u64; N] {
+    debug_assert_eq!(x.len(), 2 * N);
+    // Many of the numbers here have N + 1 limbs, so we store their top limb separately.
+    let mu = |i: usize| if i < N { mu.0[i] } else { mu.1 };
+    // q1 = ⌊x / 2⁶⁴⁽ᴺ⁻¹⁾⌋, which has N + 1 limbs.
+    let q1 = |i: usize| x[N - 1 + i];
+
+    // q3 = ⌊q1⋅mu / 2⁶⁴⁽ᴺ⁺¹⁾⌋, which we calculate one column at a time, keeping
+    // only the top N + 1 limbs.
+    let mut q3 = [0u64; N];
+    let mut q3_hi = 0u64;
+    let mut acc = Column { lo: 0, hi: 0 };
+    for k in 0..2 * N + 2 {
+        for i in k.saturating_sub(N)..=k.min(N) {
+            acc.mul_add(q1(i), mu(k - i));
+        }
+        let limb = acc.shift();
+        if k > 2 * N {
+            q3_hi = limb;
+        } else if k > N {
+            q3[k - N - 1] = limb;
+        }
+    }
+    let q3 = |i: usize| if i < N { q3[i] } else { q3_hi };
+
+    // r = x - q3⋅m mod 2⁶⁴⁽ᴺ⁺¹⁾, which is < 3m, so we only need the low N + 1 limbs.
+    let mut r = [0u64; N];
+    let mut r_hi = 0u64;
+    let mut borrow = 0;
+    for k in 0..=N {
+        for i in k.saturating_sub(N - 1)..=k {
+            acc.mul_add(q3(i), m[k - i]);
+        }
+        let limb = acc.shift();
+        if k < N {
+            borrow = sbb(borrow, x[k], limb, &mut r[k]);
+        } else {
+            sbb(borrow, x[k], limb, &mut r_hi);
+        }
+    }
+
+    // Finally, we subtract m until r < m, which takes at most two tries.
+    for _ in 0..2 {
+        let (diff, borrow) = sub(&r, m);
+        let mut diff_hi = 0;
+        let underflow = sbb(borrow, r_hi, 0, &mut diff_hi);
+        let ge = Choice::from(underflow ^ 1);
+        for i in 0..N {
+            r[i] = u64::conditional_select(&r[i], &diff[i], ge);
+        }
+        r_hi = u64::conditional_select(&r_hi, &diff_hi, ge);
+    }
+    debug_assert_eq!(r_hi, 0);
+    r
+}
+
+/// Shift a right by one bit, with top becoming the new top bit.
+#[inline]
+fn shr1<const N: usize>(a: &mut [u64; N], top: u8) {
+    for i in 0..N - 1 {
+        a[i] = (a[i] >> 1) | (a[i + 1] << 63);
+    }
+    a[N - 1] = (a[N - 1] >> 1) | (u64::from(top) << 63);
+}
+
+/// Divide a by 2 mod m, for an odd m, and a < m, in variable time.
+fn half_mod_vartime<const N: usize>(a: &mut [u64; N], m: &[u64; N]) {
+    // If a is odd, a + m is even, and has the same value mod m.
+    let mut carry = 0;
+    if a[0] & 1 == 1 {
+        (*a, carry) = add(a, m);
+    }
+    shr1(a, carry);
+}
+
+/// Calculate x⁻¹ mod m, given x < m, and an odd modulus m.
+///
+/// This uses the binary extended Euclidean algorithm, which is fast, but takes
+/// a variable amount of time, depending on x. This should only be used on public values.
+/// The inverse of zero is defined to be zero.
+pub(crate) fn invert_vartime<const N: usize>(x: &[u64; N], m: &[u64; N]) -> [u64; N] {
+    let is_one = |a: &[u64; N]| a[0] == 1 && a[1..].iter().all(|&l| l == 0);
+    if x.iter().all(|&l| l == 0) {
+        return [0; N];
+    }
+    // We maintain x1⋅x ≡ u, and x2⋅x ≡ v mod m, while shrinking u and v towards
+    // gcd(x, m) = 1.
+    let (mut u, mut v) = (*x, *m);
+    let mut x1 = [0u64; N];
+    x1[0] = 1;
+    let mut x2 = [0u64; N];
+    while !is_one(&u) && !is_one(&v) {
+        while u[0] & 1 == 0 {
+            shr1(&mut u, 0);
+            half_mod_vartime(&mut x1, m);
+        }
+        while v[0] & 1 == 0 {
+            shr1(&mut v, 0);
+            half_mod_vartime(&mut x2, m);
+        }
+        let (diff, borrow) = sub(&u, &v);
+        if borrow == 0 {
+            u = diff;
+            x1 = sub_mod(&x1, &x2, m);
+        } else {
+            v = sub(&v, &u).0;
+            x2 = sub_mod(&x2, &x1, m);
+        }
+    }
+    if is_one(&u) {
+        x1
+    } else {
+        x2
+    }
+}
@@ -0,0 +1,243 @@
+//! Constant time modular inversion, using Bernstein and Yang's safegcd algorithm.
+//!
+//! This follows the variant used in libsecp256k1, with integers stored as five signed
+//! 62 bit limbs, and batches of 59 "divsteps" applied at a time through a 2 × 2
+//! transition matrix. See <https://gcd.cr.yp.to/safegcd-20190413.pdf>, as well as
+//! libsecp256k1's `modinv64` for a detailed explanation.
+//!
+//! This works for any odd modulus < 2²⁵⁶.
+
+/// A mask for the low 62 bits of a limb.
+const M62: u64 = u64::MAX >> 2;
+
+/// The number of batches of divsteps we do.
+///
+/// 10 ⋅ 59 = 590 divsteps are always enough to reach a gcd for 256 bit inputs.
+const BATCHES: usize = 10;
+
+/// An integer, stored as Σ vᵢ⋅2⁶²ⁱ, where each vᵢ is signed.
+///
+/// In normalized form, every limb but the top one is in [0, 2⁶²), and the top limb
+/// holds the sign.
+type Signed62 = [i64; 5];
+
+/// Convert a 256 bit integer into signed limbs.
+const fn to_signed62(a: &[u64; 4]) -> Signed62 {
+    [
+        (a[0] & M62) as i64,
+        ((a[0] >> 62 | a[1] << 2) & M62) as i64,
+        ((a[1] >> 60 | a[2] << 4) & M62) as i64,
+        ((a[2] >> 58 | a[3] << 6) & M62) as i64,
+        (a[3] >> 56) as i64,
+    ]
+}
+
+/// Convert normalized, non-negative, signed limbs back into a 256 bit integer.
+fn from_signed62(v: &Signed62) -> [u64; 4] {
+    let v = v.map(|x| x as u64);
+    [
+        v[0] | v[1] << 62,
+        v[1] >> 2 | v[2] << 60,
+        v[2] >> 4 | v[3] << 58,
+        v[3] >> 6 | v[4] << 56,
+    ]
+}
+
+/// A modulus, along with the precomputed values the inversion needs.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Modulus {
+    /// The modulus itself, in signed limbs.
+    m: Signed62,
+    /// m⁻¹ mod 2⁶².
+    m_inv62: u64,
+}
+
+/// Pad an integer with up to 4 limbs out to exactly 4 limbs.
+const fn pad<const N: usize>(a: &[u64; N]) -> [u64; 4] {
+    assert!(N <= 4, "safegcd only supports integers with up to 4 limbs");
+    let mut out = [0u64; 4];
+    let mut i = 0;
+    while i < N {
+        out[i] = a[i];
+        i += 1;
+    }
+    out
+}
+
+impl Modulus {
+    /// Prepare an odd modulus < 2²⁵⁶, with up to 4 limbs, for inversion.
+    pub(crate) const fn new<const N: usize>(m: &[u64; N]) -> Self {
+        Self {
+            m: to_signed62(&pad(m)),
+            m_inv62: super::neg_inv64(m[0]).wrapping_neg() & M62,
+        }
+    }
+}
+
+/// A 2 × 2 matrix, describing the effect of a batch of divsteps, scaled by 2⁶².
+struct Transition {
+    u: i64,
+    v: i64,
+    q: i64,
+    r: i64,
+}
+
+/// Apply 59 divsteps to the low limbs of f and g, returning the new value of zeta.
+///
+/// zeta is -(δ + 1/2), using the δ of the paper. Rather than updating f and g
+/// directly, we record the transition matrix, which we apply to the full values afterwards.
+fn divsteps_59(mut zeta: i64, f0: u64, g0: u64, t: &mut Transition) -> i64 {
+    // The matrix starts as the identity, scaled by 2³, since we only do 59 steps,
+    // but the caller expects it to be scaled by 2⁶². We use u64 for shifting,
+    // but the values are always in [-2⁶², 2⁶²].
+    let (mut u, mut v, mut q, mut r) = (8u64, 0u64, 0u64, 8u64);
+    let (mut f, mut g) = (f0, g0);
+    for _ in 3..62 {
+        debug_assert_eq!(f & 1, 1);
+        // Masks for zeta < 0, and g being odd.
+        let mut c1 = (zeta >> 63) as u64;
+        let c2 = (g & 1).wrapping_neg();
+        // Conditionally negate f, u, v, and add them to g, q, r.
+        let x = (f ^ c1).wrapping_sub(c1);
+        let y = (u ^ c1).wrapping_sub(c1);
+        let z = (v ^ c1).wrapping_sub(c1);
+        g = g.wrapping_add(x & c2);
+        q = q.wrapping_add(y & c2);
+        r = r.wrapping_add(z & c2);
+        // If both conditions hold, we swap roles: zeta becomes -zeta - 2, and
+        // the updated g, q, r get added into f, u, v. Otherwise, zeta becomes zeta - 1.
+        c1 &= c2;
+        zeta = (zeta ^ c1 as i64) - 1;
+        f = f.wrapping_add(g & c1);
+        u = u.wrapping_add(q & c1);
+        v = v.wrapping_add(r & c1);
+        g >>= 1;
+        u <<= 1;
+        v <<= 1;
+    }
+    t.u = u as i64;
+    t.v = v as i64;
+    t.q = q as i64;
+    t.r = r as i64;
+    zeta
+}
+
+/// Compute (t ⋅ [d, e]) / 2⁶² mod m.
+///
+/// The inputs must be in (-2m, m), and the outputs will be too.
+fn update_de(d: &mut Signed62, e: &mut Signed62, t: &Transition, m: &Modulus) {
+    let (u, v, q, r) = (t.u, t.v, t.q, t.r);
+    // We'll add md⋅m and me⋅m to make the results divisible by 2⁶². We start them off
+    // so that negative inputs get brought into range as well.
+    let sd = d[4] >> 63;
+    let se = e[4] >> 63;
+    let mut md = (u & sd) + (v & se);
+    let mut me = (q & sd) + (r & se);
+    let mut cd = i128::from(u) * i128::from(d[0]) + i128::from(v) * i128::from(e[0]);
+    let mut ce = i128::from(q) * i128::from(d[0]) + i128::from(r) * i128::from(e[0]);
+    // Choose md and me so that the low 62 bits of the results vanish.
+    md -= ((m.m_inv62.wrapping_mul(cd as u64)).wrapping_add(md as u64) & M62) as i64;
+    me -= ((m.m_inv62.wrapping_mul(ce as u64)).wrapping_add(me as u64) & M62) as i64;
+    cd += i128::from(m.m[0]) * i128::from(md);
+    ce += i128::from(m.m[0]) * i128::from(me);
+    debug_assert_eq!(cd as u64 & M62, 0);
+    debug_assert_eq!(ce as u64 & M62, 0);
+    cd >>= 62;
+    ce >>= 62;
+    // The remaining limbs get shifted down by one, dividing by 2⁶².
+    for i in 1..5 {
+        cd += i128::from(u) * i128::from(d[i])
+            + i128::from(v) * i128::from(e[i])
+            + i128::from(m.m[i]) * i128::from(md);
+        ce += i128::from(q) * i128::from(d[i])
+            + i128::from(r) * i128::from(e[i])
+            + i128::from(m.m[i]) * i128::from(me);
+        d[i - 1] = (cd as u64 & M62) as i64;
+        e[i - 1] = (ce as u64 & M62) as i64;
+        cd >>= 62;
+        ce >>= 62;
+    }
+    d[4] = cd as i64;
+    e[4] = ce as i64;
+}
+
+/// Compute (t ⋅ [f, g]) / 2⁶², which is exact.
+fn update_fg(f: &mut Signed62, g: &mut Signed62, t: &Transition) {
+    let (u, v, q, r) = (t.u, t.v, t.q, t.r);
+    let mut cf = i128::from(u) * i128::from(f[0]) + i128::from(v) * i128::from(g[0]);
+    let mut cg = i128::from(q) * i128::from(f[0]) + i128::from(r) * i128::from(g[0]);
+    debug_assert_eq!(cf as u64 & M62, 0);
+    debug_assert_eq!(cg as u64 & M62, 0);
+    cf >>= 62;
+    cg >>= 62;
+    for i in 1..5 {
+        cf += i128::from(u) * i128::from(f[i]) + i128::from(v) * i128::from(g[i]);
+        cg += i128::from(q) * i128::from(f[i]) + i128::from(r) * i128::from(g[i]);
+        f[i - 1] = (cf as u64 & M62) as i64;
+        g[i - 1] = (cg as u64 & M62) as i64;
+        cf >>= 62;
+        cg >>= 62;
+    }
+    f[4] = cf as i64;
+    g[4] = cg as i64;
+}
+
+/// Add the modulus to r if it's negative.
+fn add_modulus_if_negative(r: &mut Signed62, m: &Modulus) {
+    let cond_add = r[4] >> 63;
+    for (x, y) in r.iter_mut().zip(m.m) {
+        *x += y & cond_add;
+    }
+}
+
+/// Propagate carries between limbs, so that all but the top one are in [0, 2⁶²).
+fn propagate(r: &mut Signed62) {
+    for i in 0..4 {
+        r[i + 1] += r[i] >> 62;
+        r[i] &= M62 as i64;
+    }
+}
+
+/// Bring r from (-2m, m) into [0, m), negating it if sign is negative.
+fn normalize(r: &mut Signed62, sign: i64, m: &Modulus) {
+    // Adding m once brings us into (-m, m), and then we can negate freely.
+    add_modulus_if_negative(r, m);
+    let cond_negate = sign >> 63;
+    for x in r.iter_mut() {
+        *x = (*x ^ cond_negate) - cond_negate;
+    }
+    propagate(r);
+    // If we're still negative, one more addition brings us into [0, m).
+    add_modulus_if_negative(r, m);
+    propagate(r);
+}
+
+/// Calculate x⁻¹ mod m, given x < m, in constant time.
+///
+/// x must have the same number of limbs as the modulus did.
+/// The inverse of zero is defined to be zero.
+pub(crate) fn invert<const N: usize>(x: &[u64; N], m: &Modulus) -> [u64; N] {
+    // We maintain the invariants d⋅x ≡ f⋅2^(-steps), e⋅x ≡ g⋅2^(-steps) mod m.
+    // Once g reaches 0, f is ±gcd(m, x) = ±1, so ±d is the inverse we want.
+    let mut d = [0i64; 5];
+    let mut e = [1, 0, 0, 0, 0];
+    let mut f = m.m;
+    let mut g = to_signed62(&pad(x));
+    let mut zeta = -1;
+    let mut t = Transition {
+        u: 0,
+        v: 0,
+        q: 0,
+        r: 0,
+    };
+    for _ in 0..BATCHES {
+        zeta = divsteps_59(zeta, f[0] as u64, g[0] as u64, &mut t);
+        update_de(&mut d, &mut e, &t, m);
+        update_fg(&mut f, &mut g, &t);
+    }
+    debug_assert!(g.iter().all(|&x| x == 0));
+    normalize(&mut d, f[4], m);
+    let mut out = [0u64; N];
+    out.copy_from_slice(&from_signed62(&d)[..N]);
+    out
+}
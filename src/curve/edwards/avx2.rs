@@ -0,0 +1,171 @@
+//! Point arithmetic with AVX2, computing four field operations at once.
+//!
+//! This uses the parallel formulas of Hisil, Wong, Carter, and Dawson, from
+//! ["Twisted Edwards Curves Revisited"](https://eprint.iacr.org/2008/522), as
+//! curve25519-dalek does. We keep the four coordinates (X : Y : Z : T) of a point in
+//! the four lanes of an [`FpVec4`], so that adding points takes two vector
+//! multiplications, instead of eight or nine scalar ones, and doubling takes one vector
+//! squaring, and one vector multiplication.
+//!
+//! Doubling, which dominates scalar multiplication, gains the most from this. Compared to
+//! the 51 bit, and 25.5 bit backends, this is several times faster, and compared to the
+//! saturated backend, which has fast multiplication with BMI2 and ADX, still about a
+//! third faster.
+//!
+//! AVX2 usually isn't enabled at compile time, so we check for it at runtime, with
+//! [`is_supported`], and then call into whole algorithms with AVX2 enabled, like
+//! [`straus_vartime`], into which all of the arithmetic gets inlined.
+
+// Only the operators on references are marked to always be inlined, so we stick to those.
+#![allow(clippy::op_ref)]
+
+use super::{EdwardsPoint, EDWARDS_D};
+use crate::curve::field::{avx2::FpVec4, Fp};
+
+/// Check whether the CPU we're running on supports AVX2.
+#[inline(always)]
+pub(crate) fn is_supported() -> bool {
+    // We can skip the runtime check if AVX2 is enabled at compile time.
+    cfg!(target_feature = "avx2") || is_x86_feature_detected!("avx2")
+}
+
+/// A point, with its coordinates (X : Y : Z : T) in the lanes of a single vector.
+#[derive(Clone, Copy)]
+struct ExtendedPoint(FpVec4);
+
+/// A point, cached as (Y - X, Y + X, 2⋅Z, 2⋅d⋅T), ready to be added to another.
+///
+/// This is the vectorized counterpart to [`ProjectiveNiels`](super::ProjectiveNiels),
+/// with Z doubled, so that both factors of 2 in the addition formulas come for free.
+#[derive(Clone, Copy)]
+struct CachedPoint(FpVec4);
+
+impl ExtendedPoint {
+    #[inline(always)]
+    fn new(point: &EdwardsPoint) -> Self {
+        Self(FpVec4::new(&point.x, &point.y, &point.z, &point.t))
+    }
+
+    #[inline(always)]
+    fn to_edwards(self) -> EdwardsPoint {
+        let [x, y, z, t] = self.0.split();
+        EdwardsPoint { x, y, z, t }
+    }
+
+    #[inline(always)]
+    fn to_cached(self) -> CachedPoint {
+        // (Y, Y, Z, T) ± (X, X, Z, T) gives us Y - X, and Y + X, along with 2⋅Z, and 2⋅T.
+        let a = self.0.shuffle([1, 1, 2, 3]);
+        let b = self.0.shuffle([0, 0, 2, 3]);
+        let v = (&a + &b).blend(&(&a - &b), [true, false, false, false]);
+        CachedPoint(&v * &FpVec4::new(&Fp::ONE, &Fp::ONE, &Fp::ONE, &EDWARDS_D))
+    }
+
+    /// Add a cached point, with add-2008-hwcd-3.
+    #[inline(always)]
+    fn add(&self, other: &CachedPoint) -> Self {
+        // (Y₁ - X₁, Y₁ + X₁, Z₁, T₁), times the cached point, gives (A, B, D, C).
+        let a = self.0.shuffle([1, 1, 2, 3]);
+        let b = self.0.shuffle([0, 0, 2, 3]);
+        let v = a.blend(&(&a - &b), [true, false, false, false]);
+        let v = v.blend(&(&a + &b), [false, true, false, false]);
+        finish(&(&v * &other.0))
+    }
+
+    /// Double this point, with dbl-2008-hwcd.
+    #[inline(always)]
+    fn double(&self) -> Self {
+        // (X, Y, Z, X + Y)² gives (A, B, Z², S), with S = (X + Y)².
+        let s = &self.0.shuffle([0, 1, 2, 0]) + &self.0.shuffle([0, 1, 2, 1]);
+        let v = self.0.blend(&s, [false, false, false, true]).square();
+
+        // With a = -1, D = -A, and then (E, F, G, H) is (S, B, B, 0) - (A + B, A + 2⋅Z², A, A + B):
+        //     E = S - (A + B), F = G - 2⋅Z², G = B - A, H = 0 - (A + B).
+        let zero = FpVec4::zero();
+        let lhs = v
+            .shuffle([3, 1, 1, 1])
+            .blend(&zero, [false, false, false, true]);
+        let q = v
+            .shuffle([1, 2, 2, 1])
+            .blend(&zero, [false, false, true, false]);
+        let rhs = &(&q + &q.blend(&zero, [true, false, true, true])) + &v.shuffle([0, 0, 0, 0]);
+        finish_efgh(&(&lhs - &rhs))
+    }
+}
+
+/// Finish an addition, given (A, B, D, C).
+#[inline(always)]
+fn finish(abdc: &FpVec4) -> ExtendedPoint {
+    // (B, D, D, B) ± (A, C, C, A) gives (E, F, G, H) = (B - A, D - C, D + C, B + A).
+    let u = abdc.shuffle([1, 2, 2, 1]);
+    let w = abdc.shuffle([0, 3, 3, 0]);
+    finish_efgh(&(&u - &w).blend(&(&u + &w), [false, false, true, true]))
+}
+
+/// Finish an addition, or a doubling, given (E, F, G, H).
+#[inline(always)]
+fn finish_efgh(efgh: &FpVec4) -> ExtendedPoint {
+    // (E, G, F, E) times (F, H, G, H) gives (X₃, Y₃, Z₃, T₃).
+    ExtendedPoint(&efgh.shuffle([0, 2, 1, 0]) * &efgh.shuffle([1, 3, 2, 3]))
+}
+
+impl CachedPoint {
+    /// Negate this point, giving (Y + X, Y - X, 2⋅Z, -2⋅d⋅T).
+    #[inline(always)]
+    fn neg(&self) -> Self {
+        let swapped = self.0.shuffle([1, 0, 2, 3]);
+        let negated = &FpVec4::zero() - &swapped;
+        Self(swapped.blend(&negated, [false, false, false, true]))
+    }
+}
+
+/// Calculate Σ aᵢ⋅Pᵢ, given the width 5 non-adjacent form of each aᵢ.
+///
+/// This is the same algorithm as [`super::vartime::straus_vartime`], with the same
+/// tables of odd multiples, only with vectorized point arithmetic.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[target_feature(enable = "avx2")]
+pub(crate) unsafe fn straus_vartime(nafs: &[[i8; 256]], points: &[EdwardsPoint]) -> EdwardsPoint {
+    // We avoid closures in here, since they wouldn't have AVX2 enabled.
+    let mut tables: Vec<[CachedPoint; 8]> = Vec::with_capacity(points.len());
+    for point in points {
+        let point = ExtendedPoint::new(point);
+        let point2 = point.double().to_cached();
+        let mut acc = point;
+        let mut table = [point.to_cached(); 8];
+        for entry in table.iter_mut().skip(1) {
+            acc = acc.add(&point2);
+            *entry = acc.to_cached();
+        }
+        tables.push(table);
+    }
+
+    // Skip the leading zero digits, common to all of the scalars.
+    let mut top = None;
+    for i in (0..256).rev() {
+        if nafs.iter().any(|naf| naf[i] != 0) {
+            top = Some(i);
+            break;
+        }
+    }
+    let Some(top) = top else {
+        return EdwardsPoint::IDENTITY;
+    };
+
+    let mut out = ExtendedPoint::new(&EdwardsPoint::IDENTITY);
+    for i in (0..=top).rev() {
+        out = out.double();
+        for (naf, table) in nafs.iter().zip(tables.iter()) {
+            let x = naf[i];
+            if x > 0 {
+                out = out.add(&table[usize::from(x.unsigned_abs()) / 2]);
+            } else if x < 0 {
+                out = out.add(&table[usize::from(x.unsigned_abs()) / 2].neg());
+            }
+        }
+    }
+    out.to_edwards()
+}
@@ -0,0 +1,250 @@
+//! The 32 byte encoding of points, as used by Ed25519.
+use std::fmt::{Debug, Display, LowerHex};
+
+use subtle::{Choice, ConditionallyNegatable, ConstantTimeEq, CtOption};
+
+#[cfg(target_arch = "x86_64")]
+use super::avx2;
+use super::{EdwardsPoint, EDWARDS_D};
+#[cfg(target_arch = "x86_64")]
+use crate::curve::field;
+#[cfg(feature = "serde")]
+use crate::util::{deserialize_bytes, serialize_bytes};
+use crate::{curve::field::Fp, util::write_hex};
+
+/// A point on edwards25519, compressed to 32 bytes.
+///
+/// This is the little endian encoding of the y coordinate, with the top bit holding
+/// the sign of the x coordinate, as specified in RFC 8032. This type doesn't guarantee
+/// that its bytes encode a valid point; that only gets checked by [`Self::decompress`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompressedEdwardsY(pub [u8; 32]);
+
+impl CompressedEdwardsY {
+    /// View this encoding as an array of bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Copy this encoding into an array of bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Attempt to recover the point this encodes.
+    ///
+    /// This fails if the encoding is not canonical, i.e. if y ≥ P, or if the sign
+    /// bit is set when x = 0, or if no point has this y coordinate.
+    /// This runs in constant time.
+    pub fn decompress(&self) -> CtOption<EdwardsPoint> {
+        let y = self.canonical_y();
+        // The value is only used if the encoding is canonical.
+        let (u, v) = x2_ratio(&y.unwrap_or(Fp::ZERO));
+        let (is_square, x) = Fp::sqrt_ratio_i(&u, &v);
+        self.finish_decompress(&y, is_square, x)
+    }
+
+    /// Attempt to recover the point this encodes, accepting non-canonical encodings.
+    ///
+    /// Unlike [`Self::decompress`], y gets reduced mod P, and the sign bit gets ignored
+    /// when x = 0, so this only fails if no point has this y coordinate. Some points
+    /// thus have more than one encoding, which is what ZIP-215 requires, but makes
+    /// encodings malleable. This runs in constant time.
+    pub fn decompress_non_canonical(&self) -> CtOption<EdwardsPoint> {
+        let mut y_bytes = self.0;
+        y_bytes[31] &= 0x7F;
+        let y = Fp::from_bytes(&y_bytes);
+        let (u, v) = x2_ratio(&y);
+        let (is_square, x) = Fp::sqrt_ratio_i(&u, &v);
+        let (point, _) = self.with_root(&y, x);
+        CtOption::new(point, is_square)
+    }
+
+    /// The y coordinate in this encoding, if it's canonical.
+    fn canonical_y(&self) -> CtOption<Fp> {
+        let mut y_bytes = self.0;
+        y_bytes[31] &= 0x7F;
+        Fp::from_canonical_bytes(&y_bytes)
+    }
+
+    /// Finish [`Self::decompress`], given the result of [`Fp::sqrt_ratio_i`].
+    fn finish_decompress(
+        &self,
+        y: &CtOption<Fp>,
+        is_square: Choice,
+        x: Fp,
+    ) -> CtOption<EdwardsPoint> {
+        let (point, bad_sign) = self.with_root(&y.unwrap_or(Fp::ZERO), x);
+        CtOption::new(point, y.is_some() & is_square & !bad_sign)
+    }
+
+    /// Recover the point with a given y coordinate, and the non-negative root x of
+    /// [`x2_ratio`], using the sign of x in this encoding.
+    ///
+    /// This also returns whether or not the sign bit is set when x = 0.
+    fn with_root(&self, y: &Fp, mut x: Fp) -> (EdwardsPoint, Choice) {
+        let sign = Choice::from(self.0[31] >> 7);
+        // The root is non-negative, so this gives it the right sign.
+        x.conditional_negate(sign);
+        let bad_sign = sign & x.is_zero();
+
+        let point = EdwardsPoint {
+            x,
+            y: *y,
+            z: Fp::ONE,
+            t: x * y,
+        };
+        (point, bad_sign)
+    }
+
+    /// Attempt to recover the points encoded by a batch of encodings.
+    ///
+    /// This returns the points, in order, if all of them decompress, and otherwise the
+    /// indices of the encodings that failed, in increasing order. Each encoding is
+    /// checked exactly like [`Self::decompress`] does.
+    ///
+    /// Decompression needs no inversion, since [`Fp::sqrt_ratio_i`] folds the division
+    /// into its exponentiation, which is nearly all of the work. On x86_64, if the CPU
+    /// supports AVX2, we run four of these exponentiations at once, with `FpVec4`,
+    /// which makes decompressing a batch about twice as fast as decompressing each point
+    /// on its own.
+    ///
+    /// The work done for each point runs in constant time, but which points failed is
+    /// revealed, so this is meant for public points, like those in signatures.
+    pub fn batch_decompress(points: &[Self]) -> Result<Vec<EdwardsPoint>, Vec<usize>> {
+        let mut out = Vec::with_capacity(points.len());
+        let mut failed = Vec::new();
+        for (i, decompressed) in decompress_all(points).into_iter().enumerate() {
+            if bool::from(decompressed.is_some()) {
+                out.push(decompressed.unwrap());
+            } else {
+                failed.push(i);
+            }
+        }
+        if failed.is_empty() {
+            Ok(out)
+        } else {
+            Err(failed)
+        }
+    }
+}
+
+/// Calculate (u, v), with x² = u / v for the points with a given y coordinate.
+///
+/// From the curve equation, x² = (y² - 1) / (d⋅y² + 1), and the denominator is
+/// never 0, since -1 / d isn't a square.
+fn x2_ratio(y: &Fp) -> (Fp, Fp) {
+    let y2 = y.square();
+    (y2 - Fp::ONE, EDWARDS_D * y2 + Fp::ONE)
+}
+
+/// Decompress each encoding, like [`CompressedEdwardsY::decompress`].
+fn decompress_all(points: &[CompressedEdwardsY]) -> Vec<CtOption<EdwardsPoint>> {
+    #[cfg(target_arch = "x86_64")]
+    if avx2::is_supported() {
+        let mut out = Vec::with_capacity(points.len());
+        for chunk in points.chunks(4) {
+            // A single point is faster to decompress on its own, and otherwise, a short
+            // chunk gets padded with 0 / 1, whose root we ignore.
+            if let [point] = chunk {
+                out.push(point.decompress());
+                continue;
+            }
+            let mut ys = [CtOption::new(Fp::ZERO, Choice::from(0)); 4];
+            let mut u = [Fp::ZERO; 4];
+            let mut v = [Fp::ONE; 4];
+            for (i, point) in chunk.iter().enumerate() {
+                ys[i] = point.canonical_y();
+                (u[i], v[i]) = x2_ratio(&ys[i].unwrap_or(Fp::ZERO));
+            }
+            // Safe, since we just checked that the CPU supports AVX2.
+            let roots = unsafe { field::avx2::sqrt_ratio_i(&u, &v) };
+            for (i, point) in chunk.iter().enumerate() {
+                let (is_square, x) = roots[i];
+                out.push(point.finish_decompress(&ys[i], is_square, x));
+            }
+        }
+        return out;
+    }
+    points.iter().map(|p| p.decompress()).collect()
+}
+
+impl ConstantTimeEq for CompressedEdwardsY {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
+/// This prints the 64 hex characters of the encoding, in order.
+impl LowerHex for CompressedEdwardsY {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+impl Display for CompressedEdwardsY {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+impl Debug for CompressedEdwardsY {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CompressedEdwardsY({})", self)
+    }
+}
+
+/// Points are printed out through their compressed encoding, which is unique, unlike
+/// their coordinates.
+impl Debug for EdwardsPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EdwardsPoint({})", self.compress())
+    }
+}
+
+impl EdwardsPoint {
+    /// Compress this point to its canonical 32 byte encoding.
+    ///
+    /// This requires an inversion, so it's relatively expensive. This runs in constant time.
+    pub fn compress(&self) -> CompressedEdwardsY {
+        let (x, y) = self.to_affine();
+        let mut out = y.to_bytes();
+        out[31] |= x.is_negative().unwrap_u8() << 7;
+        CompressedEdwardsY(out)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CompressedEdwardsY {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(&self.0, serializer)
+    }
+}
+
+/// Like the type itself, this doesn't check that the bytes encode a valid point.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CompressedEdwardsY {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_bytes(deserializer, "a 32 byte compressed point").map(Self)
+    }
+}
+
+/// Points are serialized in compressed form.
+#[cfg(feature = "serde")]
+impl serde::Serialize for EdwardsPoint {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(&self.compress().0, serializer)
+    }
+}
+
+/// This rejects any encoding that [`CompressedEdwardsY::decompress`] rejects.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for EdwardsPoint {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let bytes = deserialize_bytes(deserializer, "the canonical 32 byte encoding of a point")?;
+        Option::from(CompressedEdwardsY(bytes).decompress())
+            .ok_or_else(|| D::Error::custom("invalid point encoding"))
+    }
+}
@@ -0,0 +1,113 @@
+//! Constant points on edwards25519.
+use super::{CompressedEdwardsY, EdwardsBasepointTable, EdwardsPoint};
+use crate::curve::field::Fp;
+
+/// The x coordinate of [`EIGHT_TORSION`]`[1]`.
+const X1: Fp = Fp::from_bytes(&[
+    0x4A, 0xD1, 0x45, 0xC5, 0x46, 0x46, 0xA1, 0xDE, 0x38, 0xE2, 0xE5, 0x13, 0x70, 0x3C, 0x19, 0x5C,
+    0xBB, 0x4A, 0xDE, 0x38, 0x32, 0x99, 0x33, 0xE9, 0x28, 0x4A, 0x39, 0x06, 0xA0, 0xB9, 0xD5, 0x1F,
+]);
+
+/// -X1.
+const NEG_X1: Fp = Fp::from_bytes(&[
+    0xA3, 0x2E, 0xBA, 0x3A, 0xB9, 0xB9, 0x5E, 0x21, 0xC7, 0x1D, 0x1A, 0xEC, 0x8F, 0xC3, 0xE6, 0xA3,
+    0x44, 0xB5, 0x21, 0xC7, 0xCD, 0x66, 0xCC, 0x16, 0xD7, 0xB5, 0xC6, 0xF9, 0x5F, 0x46, 0x2A, 0x60,
+]);
+
+/// The y coordinate of [`EIGHT_TORSION`]`[1]`.
+const Y1: Fp = Fp::from_bytes(&[
+    0x26, 0xE8, 0x95, 0x8F, 0xC2, 0xB2, 0x27, 0xB0, 0x45, 0xC3, 0xF4, 0x89, 0xF2, 0xEF, 0x98, 0xF0,
+    0xD5, 0xDF, 0xAC, 0x05, 0xD3, 0xC6, 0x33, 0x39, 0xB1, 0x38, 0x02, 0x88, 0x6D, 0x53, 0xFC, 0x05,
+]);
+
+/// -Y1.
+const NEG_Y1: Fp = Fp::from_bytes(&[
+    0xC7, 0x17, 0x6A, 0x70, 0x3D, 0x4D, 0xD8, 0x4F, 0xBA, 0x3C, 0x0B, 0x76, 0x0D, 0x10, 0x67, 0x0F,
+    0x2A, 0x20, 0x53, 0xFA, 0x2C, 0x39, 0xCC, 0xC6, 0x4E, 0xC7, 0xFD, 0x77, 0x92, 0xAC, 0x03, 0x7A,
+]);
+
+/// X1⋅Y1.
+const T1: Fp = Fp::from_bytes(&[
+    0x6C, 0x78, 0x81, 0x21, 0xC1, 0xDA, 0xF2, 0xD2, 0x69, 0x7A, 0x05, 0x32, 0x8B, 0xC2, 0x66, 0x45,
+    0xF5, 0x50, 0x07, 0x6D, 0x2E, 0x89, 0xCD, 0xCF, 0x14, 0x94, 0x5D, 0x9F, 0x3C, 0xBB, 0x1D, 0x13,
+]);
+
+/// -T1.
+const NEG_T1: Fp = Fp::from_bytes(&[
+    0x81, 0x87, 0x7E, 0xDE, 0x3E, 0x25, 0x0D, 0x2D, 0x96, 0x85, 0xFA, 0xCD, 0x74, 0x3D, 0x99, 0xBA,
+    0x0A, 0xAF, 0xF8, 0x92, 0xD1, 0x76, 0x32, 0x30, 0xEB, 0x6B, 0xA2, 0x60, 0xC3, 0x44, 0xE2, 0x6C,
+]);
+
+/// -sqrt(-1).
+const NEG_SQRT_M1: Fp = Fp::from_bytes(&[
+    0x3D, 0x5F, 0xF1, 0xB5, 0xD8, 0xE4, 0x11, 0x3B, 0x87, 0x1B, 0xD0, 0x52, 0xF9, 0xE7, 0xBC, 0xD0,
+    0x58, 0x28, 0x04, 0xC2, 0x66, 0xFF, 0xB2, 0xD4, 0xF4, 0x20, 0x3E, 0xB0, 0x7F, 0xDB, 0x7C, 0x54,
+]);
+
+/// -1.
+const NEG_ONE: Fp = Fp::from_bytes(&[
+    0xEC, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F,
+]);
+
+/// Create a point from affine coordinates, along with their product.
+const fn affine(x: Fp, y: Fp, t: Fp) -> EdwardsPoint {
+    EdwardsPoint {
+        x,
+        y,
+        z: Fp::ONE,
+        t,
+    }
+}
+
+/// The 8-torsion subgroup, i.e. the points of small order.
+///
+/// [`EIGHT_TORSION`]`[i]` is i⋅T, where T = [`EIGHT_TORSION`]`[1]` has order 8.
+/// The points at even indices are the 4-torsion subgroup, and [`EIGHT_TORSION`]`[4]`
+/// is the only point of order 2, namely (0, -1).
+pub const EIGHT_TORSION: [EdwardsPoint; 8] = [
+    EdwardsPoint::IDENTITY,
+    affine(X1, Y1, T1),
+    affine(Fp::SQRT_M1, Fp::ZERO, Fp::ZERO),
+    affine(X1, NEG_Y1, NEG_T1),
+    affine(Fp::ZERO, NEG_ONE, Fp::ZERO),
+    affine(NEG_X1, NEG_Y1, T1),
+    affine(NEG_SQRT_M1, Fp::ZERO, Fp::ZERO),
+    affine(NEG_X1, Y1, NEG_T1),
+];
+
+/// The Ed25519 basepoint, B, as specified in RFC 8032.
+///
+/// This has y = 4 / 5, and a positive x, and generates the prime order subgroup.
+pub const ED25519_BASEPOINT_POINT: EdwardsPoint = EdwardsPoint {
+    x: Fp::from_bytes(&[
+        0x1A, 0xD5, 0x25, 0x8F, 0x60, 0x2D, 0x56, 0xC9, 0xB2, 0xA7, 0x25, 0x95, 0x60, 0xC7, 0x2C,
+        0x69, 0x5C, 0xDC, 0xD6, 0xFD, 0x31, 0xE2, 0xA4, 0xC0, 0xFE, 0x53, 0x6E, 0xCD, 0xD3, 0x36,
+        0x69, 0x21,
+    ]),
+    y: Fp::from_bytes(&[
+        0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+        0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+        0x66, 0x66,
+    ]),
+    z: Fp::ONE,
+    t: Fp::from_bytes(&[
+        0xA3, 0xDD, 0xB7, 0xA5, 0xB3, 0x8A, 0xDE, 0x6D, 0xF5, 0x52, 0x51, 0x77, 0x80, 0x9F, 0xF0,
+        0x20, 0x7D, 0xE3, 0xAB, 0x64, 0x8E, 0x4E, 0xEA, 0x66, 0x65, 0x76, 0x8B, 0xD7, 0x0F, 0x5F,
+        0x87, 0x67,
+    ]),
+};
+
+/// The encoding of [`ED25519_BASEPOINT_POINT`].
+pub const ED25519_BASEPOINT_COMPRESSED: CompressedEdwardsY = CompressedEdwardsY([
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+]);
+
+/// A table of multiples of [`ED25519_BASEPOINT_POINT`], for fast multiplication.
+///
+/// This is the same as `EdwardsBasepointTable::new(&ED25519_BASEPOINT_POINT)`, but it's
+/// computed by the build script, and embedded in the crate, so there's no cost at
+/// startup, and the table lives in read-only memory.
+pub static ED25519_BASEPOINT_TABLE: EdwardsBasepointTable =
+    EdwardsBasepointTable::from_bytes(&include!(concat!(env!("OUT_DIR"), "/basepoint_table.rs")));
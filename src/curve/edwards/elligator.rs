@@ -0,0 +1,144 @@
+//! The Elligator 2 map, from field elements to points.
+//!
+//! This follows the straight-line description in
+//! [RFC 9380, Appendix G.2](https://www.rfc-editor.org/rfc/rfc9380#appendix-G.2),
+//! mapping to Curve25519, in Montgomery form, and then over to edwards25519, without
+//! any inversions, or branches.
+//!
+//! Hashing to the curve is built on top of this map. Going the other way, the inverse
+//! map encodes about half of the points as strings indistinguishable from random bytes.
+use subtle::{ConditionallyNegatable, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+use super::EdwardsPoint;
+use crate::curve::{
+    field::Fp,
+    montgomery::{MONTGOMERY_A, SQRT_NEG_A_PLUS_2},
+};
+
+/// -A.
+const NEG_MONTGOMERY_A: Fp = Fp::from_bytes(&[
+    0xE7, 0x92, 0xF8, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F,
+]);
+
+/// 2^((P + 3) / 8), which fixes up a candidate root of 2⋅u²⋅g(x₁).
+const POW_2_P38: Fp = Fp::from_bytes(&[
+    0xB1, 0xA0, 0x0E, 0x4A, 0x27, 0x1B, 0xEE, 0xC4, 0x78, 0xE4, 0x2F, 0xAD, 0x06, 0x18, 0x43, 0x2F,
+    0xA7, 0xD7, 0xFB, 0x3D, 0x99, 0x00, 0x4D, 0x2B, 0x0B, 0xDF, 0xC1, 0x4F, 0x80, 0x24, 0x83, 0x2B,
+]);
+
+/// Map a field element to a point on Curve25519, returning (xn, xd, y), with x = xn / xd.
+///
+/// With the non-square Z = 2, x₁ = -A / (1 + 2⋅u²) and x₂ = -x₁ - A, one of g(x₁) and
+/// g(x₂) is a square, and we pick the matching x, along with a root y, which is
+/// negative for x₁, and non-negative for x₂.
+fn map_to_montgomery(u: &Fp) -> (Fp, Fp, Fp) {
+    let tv1 = u.square().double();
+    let xd = tv1 + Fp::ONE;
+    let x1n = NEG_MONTGOMERY_A;
+    let tv2 = xd.square();
+    // g(x₁) = gx1 / gxd, with x₁ = x1n / xd.
+    let gxd = tv2 * xd;
+    let gx1 = ((MONTGOMERY_A * tv1) * x1n + tv2) * x1n;
+
+    // A candidate root of g(x₁), as in sqrt_ratio_i, with v = gxd.
+    let tv3 = gxd.square();
+    let tv2 = tv3.square();
+    let tv3 = tv3 * gxd * gx1;
+    let y11 = (tv2 * tv3).pow_p58() * tv3;
+    let y12 = y11 * Fp::SQRT_M1;
+    let e1 = (y11.square() * gxd).ct_eq(&gx1);
+    let y1 = Fp::conditional_select(&y12, &y11, e1);
+
+    // g(x₂) = 2⋅u²⋅g(x₁), so its root follows from the candidate above.
+    let x2n = x1n * tv1;
+    let y21 = y11 * u * POW_2_P38;
+    let y22 = y21 * Fp::SQRT_M1;
+    let gx2 = gx1 * tv1;
+    let e2 = (y21.square() * gxd).ct_eq(&gx2);
+    let y2 = Fp::conditional_select(&y22, &y21, e2);
+
+    let e3 = (y1.square() * gxd).ct_eq(&gx1);
+    let xn = Fp::conditional_select(&x2n, &x1n, e3);
+    let mut y = Fp::conditional_select(&y2, &y1, e3);
+    y.conditional_negate(e3 ^ y.is_negative());
+    (xn, xd, y)
+}
+
+/// Map a field element to a point on edwards25519, using Elligator 2.
+///
+/// The map isn't surjective, and the result may have a component of small order,
+/// so this should be followed by clearing the cofactor, in most uses.
+/// This runs in constant time.
+pub(crate) fn map_to_curve(u: &Fp) -> EdwardsPoint {
+    let (x_mn, x_md, y_m) = map_to_montgomery(u);
+    // The birational map (x, y) = (sqrt(-(A + 2))⋅xₘ / yₘ, (xₘ - 1) / (xₘ + 1)).
+    let mut xn = x_mn * SQRT_NEG_A_PLUS_2;
+    let mut xd = x_md * y_m;
+    let mut yn = x_mn - x_md;
+    let mut yd = x_mn + x_md;
+    // The map sends the points with yₘ = 0, or xₘ = -1, to the identity.
+    let exceptional = (xd * yd).is_zero();
+    xn.conditional_assign(&Fp::ZERO, exceptional);
+    xd.conditional_assign(&Fp::ONE, exceptional);
+    yn.conditional_assign(&Fp::ONE, exceptional);
+    yd.conditional_assign(&Fp::ONE, exceptional);
+    EdwardsPoint {
+        x: xn * yd,
+        y: yn * xd,
+        z: xd * yd,
+        t: xn * yn,
+    }
+}
+
+impl EdwardsPoint {
+    /// Decode an Elligator 2 representative, mapping it to a point.
+    ///
+    /// Only the low 254 bits are used, so that every 32 byte string, including
+    /// random ones, is a valid representative. The point returned is the one
+    /// [`Self::to_elligator_representative`] started from, including any small order
+    /// component. This runs in constant time.
+    pub fn from_elligator_representative(representative: &[u8; 32]) -> Self {
+        let mut bytes = *representative;
+        bytes[31] &= 0x3F;
+        map_to_curve(&Fp::from_bytes(&bytes))
+    }
+
+    /// Encode this point as an Elligator 2 representative, if it has one.
+    ///
+    /// About half of the points have a representative. For a random such point, the
+    /// representative is indistinguishable from 32 random bytes, which is what
+    /// censorship resistant protocols need, to hide public keys in a handshake.
+    /// The top two bits of the encoding aren't used by the map, and get filled in
+    /// with the top two bits of `tweak`, which should be random.
+    ///
+    /// Points in the prime order subgroup only reach some of the representatives,
+    /// so for the output to look random, the point should have a random small order
+    /// component, like a random multiple of [`EIGHT_TORSION[1]`](super::EIGHT_TORSION),
+    /// added to it.
+    ///
+    /// This runs in constant time, with respect to the point, and to whether or not it
+    /// has a representative.
+    pub fn to_elligator_representative(&self, tweak: u8) -> CtOption<[u8; 32]> {
+        let (x_m, y_m) = self.montgomery_coordinates();
+
+        // The forward map picks x₁ = -A / (1 + 2⋅u²) with a negative y, and
+        // x₂ = -x₁ - A with a non-negative y, so we solve for u² accordingly:
+        //     u² = -(xₘ + A) / (2⋅xₘ), or u² = -xₘ / (2⋅(xₘ + A)).
+        let x_plus_a = x_m + MONTGOMERY_A;
+        let negative = y_m.is_negative();
+        let num = -Fp::conditional_select(&x_m, &x_plus_a, negative);
+        let den = Fp::conditional_select(&x_plus_a, &x_m, negative).double();
+        let (is_square, mut u) = Fp::sqrt_ratio_i(&num, &den);
+        // Of ±u, we pick the one ≤ (P - 1) / 2, which fits in 254 bits. For larger u,
+        // 2⋅u wraps around P, becoming odd.
+        u.conditional_negate(u.double().is_negative());
+
+        // The exceptional points, like those with xₘ = 0, or yₘ = 0, don't survive
+        // the round trip, and checking that catches them.
+        let round_trip = map_to_curve(&u).ct_eq(self);
+        let mut bytes = u.to_bytes();
+        bytes[31] |= tweak & 0xC0;
+        CtOption::new(bytes, is_square & round_trip)
+    }
+}
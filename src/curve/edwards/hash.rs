@@ -0,0 +1,168 @@
+//! Hashing to the curve, following [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380).
+//!
+//! With SHA-512, this implements the edwards25519_XMD:SHA-512_ELL2_RO_ and
+//! edwards25519_XMD:SHA-512_ELL2_NU_ suites, for [`EdwardsPoint::hash_to_curve`] and
+//! [`EdwardsPoint::encode_to_curve`] respectively.
+use digest::{core_api::BlockSizeUser, Digest, ExtendableOutput, Update};
+
+use super::{elligator::map_to_curve, EdwardsPoint};
+use crate::{curve::field::Fp, util::wipe};
+
+/// The prefix used to hash domain separation tags longer than 255 bytes.
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+/// Expand a message into a uniformly random byte string, filling `out`.
+///
+/// This is expand_message_xmd, from RFC 9380, Section 5.3.1. Following Section 5.3.3,
+/// a domain separation tag longer than 255 bytes gets hashed down first.
+///
+/// # Panics
+///
+/// This panics if `out` is longer than 255 hash outputs, or 65535 bytes.
+pub(crate) fn expand_message_xmd<D: Digest + BlockSizeUser>(
+    msg: &[u8],
+    dst: &[u8],
+    out: &mut [u8],
+) {
+    let b_in_bytes = <D as Digest>::output_size();
+    let ell = out.len().div_ceil(b_in_bytes);
+    assert!(
+        ell <= 255 && out.len() <= 0xFFFF,
+        "requested too many bytes from expand_message_xmd"
+    );
+
+    let oversize;
+    let dst = if dst.len() > 255 {
+        oversize = D::new()
+            .chain_update(OVERSIZE_DST_PREFIX)
+            .chain_update(dst)
+            .finalize();
+        &oversize[..]
+    } else {
+        dst
+    };
+    let dst_len = [dst.len() as u8];
+
+    // b₀ = H(Z_pad || msg || l_i_b_str || 0 || DST_prime).
+    let b_0 = D::new()
+        .chain_update(vec![0u8; D::block_size()])
+        .chain_update(msg)
+        .chain_update((out.len() as u16).to_be_bytes())
+        .chain_update([0u8])
+        .chain_update(dst)
+        .chain_update(dst_len)
+        .finalize();
+    // b₁ = H(b₀ || 1 || DST_prime), and bᵢ = H((b₀ ⊕ bᵢ₋₁) || i || DST_prime).
+    let mut b_i = D::new()
+        .chain_update(&b_0)
+        .chain_update([1u8])
+        .chain_update(dst)
+        .chain_update(dst_len)
+        .finalize();
+    for (i, chunk) in (1u8..).zip(out.chunks_mut(b_in_bytes)) {
+        if i > 1 {
+            let mut xored = b_0.clone();
+            for (x, y) in xored.iter_mut().zip(b_i.iter()) {
+                *x ^= y;
+            }
+            b_i = D::new()
+                .chain_update(&xored)
+                .chain_update([i])
+                .chain_update(dst)
+                .chain_update(dst_len)
+                .finalize();
+        }
+        chunk.copy_from_slice(&b_i[..chunk.len()]);
+    }
+}
+
+/// Expand a message into a uniformly random byte string, filling `out`, with an XOF.
+///
+/// This is expand_message_xof, from RFC 9380, Section 5.3.2. Following Section 5.3.3,
+/// a domain separation tag longer than 255 bytes gets hashed down to 32 bytes first,
+/// matching the 128 bit security level of our curves.
+///
+/// # Panics
+///
+/// This panics if `out` is longer than 65535 bytes.
+pub(crate) fn expand_message_xof<X: Default + Update + ExtendableOutput>(
+    msg: &[u8],
+    dst: &[u8],
+    out: &mut [u8],
+) {
+    assert!(
+        out.len() <= 0xFFFF,
+        "requested too many bytes from expand_message_xof"
+    );
+
+    let mut oversize = [0u8; 32];
+    let dst = if dst.len() > 255 {
+        X::default()
+            .chain(OVERSIZE_DST_PREFIX)
+            .chain(dst)
+            .finalize_xof_into(&mut oversize);
+        &oversize[..]
+    } else {
+        dst
+    };
+
+    // uniform_bytes = H(msg || l_i_b_str || DST_prime, len_in_bytes).
+    X::default()
+        .chain(msg)
+        .chain((out.len() as u16).to_be_bytes())
+        .chain(dst)
+        .chain([dst.len() as u8])
+        .finalize_xof_into(out);
+}
+
+/// Hash a message to N uniformly random field elements.
+///
+/// This is hash_to_field, from RFC 9380, Section 5.2, with L = 48 bytes per element,
+/// which leaves a bias of at most 2⁻¹²⁸ after reducing.
+fn hash_to_field<D: Digest + BlockSizeUser, const N: usize>(msg: &[u8], dst: &[u8]) -> [Fp; N] {
+    let mut bytes = vec![0u8; 48 * N];
+    expand_message_xmd::<D>(msg, dst, &mut bytes);
+    let out = std::array::from_fn(|i| {
+        // Each chunk is a big endian integer, which we reverse into a little endian one.
+        let mut wide = [0u8; 64];
+        wide[..48].copy_from_slice(&bytes[48 * i..48 * (i + 1)]);
+        wide[..48].reverse();
+        let out = Fp::from_bytes_wide(&wide);
+        wipe(&mut wide);
+        out
+    });
+    wipe(&mut bytes[..]);
+    out
+}
+
+impl EdwardsPoint {
+    /// Hash a message to a point, using a domain separation tag.
+    ///
+    /// This is hash_to_curve, from RFC 9380, which hashes the message to two field
+    /// elements, maps each of them to the curve with Elligator 2, and adds the results.
+    /// The output is indistinguishable from a random point in the prime order subgroup,
+    /// so this is the variant protocols should use, unless they specifically call
+    /// for [`Self::encode_to_curve`].
+    ///
+    /// With `D` set to SHA-512, this is the edwards25519_XMD:SHA-512_ELL2_RO_ suite.
+    /// Different protocols, or different uses within a protocol, should use different
+    /// tags. This runs in constant time, with respect to the message.
+    pub fn hash_to_curve<D: Digest + BlockSizeUser>(msg: &[u8], dst: &[u8]) -> Self {
+        let [u0, u1] = hash_to_field::<D, 2>(msg, dst);
+        (map_to_curve(&u0) + map_to_curve(&u1)).mul_by_cofactor()
+    }
+
+    /// Encode a message as a point, using a domain separation tag.
+    ///
+    /// This is encode_to_curve, from RFC 9380, which maps a single field element to
+    /// the curve. This is about twice as fast as [`Self::hash_to_curve`], but only
+    /// reaches about half of the points in the prime order subgroup, so the output is
+    /// distinguishable from random.
+    ///
+    /// With `D` set to SHA-512, this is the edwards25519_XMD:SHA-512_ELL2_NU_ suite.
+    /// This runs in constant time, with respect to the message.
+    pub fn encode_to_curve<D: Digest + BlockSizeUser>(msg: &[u8], dst: &[u8]) -> Self {
+        let [u] = hash_to_field::<D, 1>(msg, dst);
+        map_to_curve(&u).mul_by_cofactor()
+    }
+}
@@ -0,0 +1,343 @@
+//! Points on the twisted Edwards form of Curve25519, i.e. edwards25519.
+//!
+//! The curve is given by -x² + y² = 1 + d⋅x²⋅y², with d = -121665 / 121666.
+//!
+//! # Completeness
+//!
+//! The formulas we use for addition and doubling are complete: they work for any pair
+//! of points on the curve, with no exceptions for the identity, for doubling through
+//! addition, for adding a point to its inverse, or for points of small order.
+//!
+//! This follows from -1 being a square in our field, and d not being one. The formulas
+//! only divide, implicitly, by 1 ± d⋅x₁⋅x₂⋅y₁⋅y₂. If that were 0, then, as shown in
+//! [Bernstein-Lange 2007, Theorem 3.3](https://eprint.iacr.org/2007/286), d would have to
+//! be a square. Thus, the Z coordinate of a result is never 0, and no special cases,
+//! or branches, are needed.
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::curve::{field::Fp, scalar::Scalar};
+
+#[cfg(target_arch = "x86_64")]
+mod avx2;
+mod compressed;
+mod constants;
+mod elligator;
+#[cfg(feature = "digest")]
+pub(crate) mod hash;
+mod msm;
+mod niels;
+mod table;
+mod vartime;
+
+pub use compressed::CompressedEdwardsY;
+pub use constants::{
+    ED25519_BASEPOINT_COMPRESSED, ED25519_BASEPOINT_POINT, ED25519_BASEPOINT_TABLE, EIGHT_TORSION,
+};
+pub use msm::VartimeEdwardsPrecomputation;
+pub use niels::{AffineNiels, ProjectiveNiels};
+pub use table::EdwardsBasepointTable;
+pub use vartime::EdwardsPrecomputation;
+
+/// The constant d = -121665 / 121666 in the curve equation.
+pub(crate) const EDWARDS_D: Fp = Fp::from_bytes(&[
+    0xA3, 0x78, 0x59, 0x13, 0xCA, 0x4D, 0xEB, 0x75, 0xAB, 0xD8, 0x41, 0x41, 0x4D, 0x0A, 0x70, 0x00,
+    0x98, 0xE8, 0x79, 0x77, 0x79, 0x40, 0xC7, 0x8C, 0x73, 0xFE, 0x6F, 0x2B, 0xEE, 0x6C, 0x03, 0x52,
+]);
+
+/// 2⋅d, which the addition formulas use.
+pub(crate) const EDWARDS_D2: Fp = Fp::from_bytes(&[
+    0x59, 0xF1, 0xB2, 0x26, 0x94, 0x9B, 0xD6, 0xEB, 0x56, 0xB1, 0x83, 0x82, 0x9A, 0x14, 0xE0, 0x00,
+    0x30, 0xD1, 0xF3, 0xEE, 0xF2, 0x80, 0x8E, 0x19, 0xE7, 0xFC, 0xDF, 0x56, 0xDC, 0xD9, 0x06, 0x24,
+]);
+
+/// A point on edwards25519, in extended coordinates.
+///
+/// The point (X : Y : Z : T) represents the affine point (X / Z, Y / Z), with
+/// T / Z = (X / Z)⋅(Y / Z). Keeping T around is what makes addition cheap. This
+/// representation isn't unique, so to compare points, use [`ConstantTimeEq`].
+#[derive(Clone, Copy)]
+pub struct EdwardsPoint {
+    pub(crate) x: Fp,
+    pub(crate) y: Fp,
+    pub(crate) z: Fp,
+    pub(crate) t: Fp,
+}
+
+impl EdwardsPoint {
+    /// The identity element of the group, i.e. the affine point (0, 1).
+    pub const IDENTITY: Self = Self {
+        x: Fp::ZERO,
+        y: Fp::ONE,
+        z: Fp::ONE,
+        t: Fp::ZERO,
+    };
+
+    /// Create a point from its affine coordinates, if they lie on the curve.
+    ///
+    /// This runs in constant time.
+    pub fn from_affine(x: &Fp, y: &Fp) -> CtOption<Self> {
+        let (x2, y2) = (x.square(), y.square());
+        // -x² + y² = 1 + d⋅x²⋅y²
+        let on_curve = (y2 - x2).ct_eq(&(Fp::ONE + EDWARDS_D * x2 * y2));
+        let point = Self {
+            x: *x,
+            y: *y,
+            z: Fp::ONE,
+            t: x * y,
+        };
+        CtOption::new(point, on_curve)
+    }
+
+    /// Convert this point to affine coordinates, (x, y).
+    ///
+    /// This requires an inversion, so it's relatively expensive. This runs in constant time.
+    pub fn to_affine(&self) -> (Fp, Fp) {
+        let z_inv = self.z.invert();
+        (self.x * z_inv, self.y * z_inv)
+    }
+
+    /// Calculate 2⋅self.
+    ///
+    /// This is cheaper than adding a point to itself, and is complete, like addition.
+    /// This runs in constant time.
+    pub fn double(&self) -> Self {
+        let (e, f, g, h) = Self::double_parts(&self.x, &self.y, &self.z);
+        Self {
+            x: e * f,
+            y: g * h,
+            z: f * g,
+            t: e * h,
+        }
+    }
+
+    /// The bulk of doubling, which returns E, F, G, and H, with the result being
+    /// (E⋅F : G⋅H : F⋅G : E⋅H).
+    ///
+    /// This doesn't read T, so chains of doublings only need to compute it at the end.
+    fn double_parts(x: &Fp, y: &Fp, z: &Fp) -> (Fp, Fp, Fp, Fp) {
+        // This is the "dbl-2008-hwcd" formula, with a = -1, from the Explicit-Formulas Database.
+        // For a point on the curve, F ∝ d⋅x²⋅y² - 1 and G ∝ 1 + d⋅x²⋅y², neither of which is 0.
+        let a = x.square();
+        let b = y.square();
+        let c = z.square().double();
+        let e = (x + y).square() - a - b;
+        let g = b - a;
+        let f = g - c;
+        let h = -a - b;
+        (e, f, g, h)
+    }
+
+    /// Calculate 2ᵏ⋅self, by doubling k times.
+    ///
+    /// This is faster than calling [`Self::double`] k times, since the intermediate
+    /// results skip computing T, which doubling doesn't need. This runs in constant
+    /// time, with respect to self.
+    pub fn mul_by_pow_2(&self, k: u32) -> Self {
+        if k == 0 {
+            return *self;
+        }
+        let (mut x, mut y, mut z) = (self.x, self.y, self.z);
+        for _ in 1..k {
+            let (e, f, g, h) = Self::double_parts(&x, &y, &z);
+            (x, y, z) = (e * f, g * h, f * g);
+        }
+        let (e, f, g, h) = Self::double_parts(&x, &y, &z);
+        Self {
+            x: e * f,
+            y: g * h,
+            z: f * g,
+            t: e * h,
+        }
+    }
+
+    /// Calculate 8⋅self, clearing any component in the 8-torsion subgroup.
+    ///
+    /// The result lies in the prime order subgroup, which is what cofactored protocols,
+    /// like cofactored Ed25519 verification, rely on. This runs in constant time.
+    pub fn mul_by_cofactor(&self) -> Self {
+        self.mul_by_pow_2(3)
+    }
+
+    /// Check that this point satisfies the curve equation, and that T is consistent.
+    ///
+    /// Every point created by this crate satisfies this, so this is mainly useful
+    /// for assertions. This runs in constant time.
+    pub fn is_on_curve(&self) -> Choice {
+        // The curve equation, multiplied by Z⁴: (-X² + Y²)⋅Z² = Z⁴ + d⋅X²⋅Y².
+        let (x2, y2, z2) = (self.x.square(), self.y.square(), self.z.square());
+        let on_curve = ((y2 - x2) * z2).ct_eq(&(z2.square() + EDWARDS_D * x2 * y2));
+        // T / Z = (X / Z)⋅(Y / Z), multiplied by Z².
+        let t_matches = (self.x * self.y).ct_eq(&(self.z * self.t));
+        on_curve & t_matches & !self.z.is_zero()
+    }
+
+    /// Check whether or not this point is the identity, in constant time.
+    pub fn is_identity(&self) -> Choice {
+        self.ct_eq(&Self::IDENTITY)
+    }
+
+    /// Check whether or not this point has small order, i.e. lies in the 8-torsion subgroup.
+    ///
+    /// Small order points carry no information, so protocols often need to reject them.
+    /// This runs in constant time.
+    pub fn is_small_order(&self) -> Choice {
+        self.mul_by_cofactor().is_identity()
+    }
+
+    /// Check whether or not this point lies in the prime order subgroup, i.e. ℓ⋅self = 0.
+    ///
+    /// Points decoded from untrusted bytes can have a small order component, which this
+    /// detects. This is much more expensive than [`Self::is_small_order`].
+    /// This runs in constant time.
+    pub fn is_torsion_free(&self) -> Choice {
+        // ℓ is public, so we can use plain double and add, going through its bits.
+        let mut out = Self::IDENTITY;
+        for i in (0..253).rev() {
+            out = out.double();
+            if (Scalar::MODULUS[i / 64] >> (i % 64)) & 1 == 1 {
+                out += self;
+            }
+        }
+        out.is_identity()
+    }
+
+    /// Check whether or not 8⋅self = 8⋅other, i.e. if the points are equal up to a
+    /// component of small order.
+    ///
+    /// Protocols which clear the cofactor, like cofactored Ed25519 verification, need
+    /// to treat points that only differ by a small order point as equal. For exact
+    /// equality, use [`ConstantTimeEq`] instead. This runs in constant time.
+    pub fn torsion_safe_eq(&self, other: &Self) -> Choice {
+        // 8⋅self = 8⋅other exactly when self - other has small order.
+        (self - other).is_small_order()
+    }
+
+    /// The end of the addition formula, shared with the cached representations.
+    ///
+    /// This takes A = (Y₁ - X₁)⋅(Y₂ - X₂), B = (Y₁ + X₁)⋅(Y₂ + X₂), C = 2⋅d⋅T₁⋅T₂,
+    /// and D = 2⋅Z₁⋅Z₂.
+    fn finish_add(a: Fp, b: Fp, c: Fp, d: Fp) -> Self {
+        let e = b - a;
+        let f = d - c;
+        let g = d + c;
+        let h = b + a;
+        Self {
+            x: e * f,
+            y: g * h,
+            z: f * g,
+            t: e * h,
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for EdwardsPoint {
+    fn zeroize(&mut self) {
+        self.x.zeroize();
+        self.y.zeroize();
+        self.z.zeroize();
+        self.t.zeroize();
+    }
+}
+
+impl ConstantTimeEq for EdwardsPoint {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // (X₁ / Z₁, Y₁ / Z₁) = (X₂ / Z₂, Y₂ / Z₂), without dividing.
+        (self.x * other.z).ct_eq(&(other.x * self.z))
+            & (self.y * other.z).ct_eq(&(other.y * self.z))
+    }
+}
+
+// Along with negation, this gives us ConditionallyNegatable too.
+impl ConditionallySelectable for EdwardsPoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            x: Fp::conditional_select(&a.x, &b.x, choice),
+            y: Fp::conditional_select(&a.y, &b.y, choice),
+            z: Fp::conditional_select(&a.z, &b.z, choice),
+            t: Fp::conditional_select(&a.t, &b.t, choice),
+        }
+    }
+}
+
+impl AddAssign<&EdwardsPoint> for EdwardsPoint {
+    fn add_assign(&mut self, other: &EdwardsPoint) {
+        // This is the "add-2008-hwcd-3" formula, with a = -1, from the Explicit-Formulas
+        // Database, which needs 8 multiplications, along with 1 by the constant 2⋅d.
+        // Unlike "add-2008-hwcd-4", this formula is complete, since F and G are, up to a
+        // non-zero factor, 1 ∓ d⋅x₁⋅x₂⋅y₁⋅y₂.
+        let a = (self.y - self.x) * (other.y - other.x);
+        let b = (self.y + self.x) * (other.y + other.x);
+        let c = self.t * EDWARDS_D2 * other.t;
+        let d = (self.z * other.z).double();
+        *self = Self::finish_add(a, b, c, d);
+    }
+}
+
+impl Add<&EdwardsPoint> for &EdwardsPoint {
+    type Output = EdwardsPoint;
+
+    fn add(self, other: &EdwardsPoint) -> Self::Output {
+        let mut out = *self;
+        out += other;
+        out
+    }
+}
+
+define_op_variants!(impl Add, add, AddAssign, add_assign for EdwardsPoint);
+
+impl SubAssign<&EdwardsPoint> for EdwardsPoint {
+    fn sub_assign(&mut self, other: &EdwardsPoint) {
+        *self += -other;
+    }
+}
+
+impl Sub<&EdwardsPoint> for &EdwardsPoint {
+    type Output = EdwardsPoint;
+
+    fn sub(self, other: &EdwardsPoint) -> Self::Output {
+        let mut out = *self;
+        out -= other;
+        out
+    }
+}
+
+define_op_variants!(impl Sub, sub, SubAssign, sub_assign for EdwardsPoint);
+
+impl Neg for EdwardsPoint {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        // -(x, y) = (-x, y), so we negate X, and T along with it.
+        Self {
+            x: -self.x,
+            y: self.y,
+            z: self.z,
+            t: -self.t,
+        }
+    }
+}
+
+impl Neg for &EdwardsPoint {
+    type Output = EdwardsPoint;
+
+    fn neg(self) -> Self::Output {
+        -*self
+    }
+}
+
+impl std::iter::Sum for EdwardsPoint {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::IDENTITY, |acc, x| acc + x)
+    }
+}
+
+impl<'a> std::iter::Sum<&'a EdwardsPoint> for EdwardsPoint {
+    fn sum<I: Iterator<Item = &'a EdwardsPoint>>(iter: I) -> Self {
+        iter.fold(Self::IDENTITY, |acc, x| acc + x)
+    }
+}
@@ -0,0 +1,220 @@
+//! Multiscalar multiplication, computing Σ aᵢ⋅Pᵢ all at once.
+use std::borrow::Borrow;
+
+use super::{
+    table::{LookupTable, NafLookupTable, NafTable},
+    vartime::{naf_sum, straus_vartime, PRECOMPUTATION_ENTRIES, PRECOMPUTATION_WIDTH},
+    AffineNiels, EdwardsPoint, ProjectiveNiels,
+};
+use crate::{
+    curve::{
+        scalar::Scalar,
+        traits::{MultiscalarMul, VartimeMultiscalarMul, VartimePrecomputedMultiscalarMul},
+    },
+    util::wipe,
+};
+
+/// Collect the scalars and points passed to a multiscalar multiplication.
+///
+/// This panics if their number differs.
+fn collect<I, J>(scalars: I, points: J) -> (Vec<Scalar>, Vec<EdwardsPoint>)
+where
+    I: IntoIterator,
+    I::Item: Borrow<Scalar>,
+    J: IntoIterator,
+    J::Item: Borrow<EdwardsPoint>,
+{
+    let scalars: Vec<Scalar> = scalars.into_iter().map(|s| *s.borrow()).collect();
+    let points: Vec<EdwardsPoint> = points.into_iter().map(|p| *p.borrow()).collect();
+    assert_eq!(
+        scalars.len(),
+        points.len(),
+        "multiscalar multiplication needs as many scalars as points"
+    );
+    (scalars, points)
+}
+
+/// Calculate Σ aᵢ⋅Pᵢ, with Straus' method, in constant time.
+///
+/// This interleaves fixed window multiplications in radix 16, so that all of the points
+/// share the same doublings. Each point gets a table of its first 8 multiples.
+///
+/// Unlike the variable time version, every digit leads to an addition, even when
+/// it's zero, and every lookup reads the whole table, so the sequence of operations
+/// and memory accesses only depends on the number of points.
+fn straus(scalars: &[Scalar], points: &[EdwardsPoint]) -> EdwardsPoint {
+    let mut tables: Vec<LookupTable<ProjectiveNiels, 8>> =
+        points.iter().map(LookupTable::from).collect();
+    let mut digits: Vec<[i8; 64]> = scalars.iter().map(Scalar::as_radix_16).collect();
+
+    let mut out = EdwardsPoint::IDENTITY;
+    for j in (0..64).rev() {
+        out = out.mul_by_pow_2(4);
+        for (table, digits) in tables.iter().zip(digits.iter()) {
+            out = &out + &table.select(digits[j]);
+        }
+    }
+    wipe(&mut digits);
+    wipe(&mut tables);
+    out
+}
+
+/// Above this many points, Pippenger's method beats Straus' method, in variable time.
+const PIPPENGER_THRESHOLD: usize = 190;
+
+/// Calculate Σ aᵢ⋅Pᵢ, with Pippenger's bucket method, in variable time.
+///
+/// For each digit position of the scalars, in radix 2ʷ, we sort the points into buckets
+/// by their digit, sum each bucket, and then add up the buckets, weighted by their digit.
+/// This takes about one addition per point for each position, independently of w,
+/// and a number of additions for the buckets that doesn't depend on the number of points.
+/// The cost per point thus keeps shrinking as the number of points grows, unlike
+/// with Straus' method.
+fn pippenger_vartime(scalars: &[Scalar], points: &[EdwardsPoint]) -> EdwardsPoint {
+    // These widths roughly minimize the number of additions, given the number of points.
+    let w = match points.len() {
+        0..500 => 6,
+        500..800 => 7,
+        _ => 8,
+    };
+    let digits: Vec<[i8; 64]> = scalars.iter().map(|s| s.as_radix_2w(w)).collect();
+    let points: Vec<ProjectiveNiels> = points.iter().map(|p| p.to_projective_niels()).collect();
+
+    // With digits in [-2ʷ⁻¹, 2ʷ⁻¹), bucket k holds the points with digit ±(k + 1).
+    let mut buckets = vec![EdwardsPoint::IDENTITY; 1 << (w - 1)];
+    let mut out = EdwardsPoint::IDENTITY;
+    for i in (0..256usize.div_ceil(w)).rev() {
+        buckets.fill(EdwardsPoint::IDENTITY);
+        for (digits, point) in digits.iter().zip(points.iter()) {
+            let digit = digits[i];
+            if digit > 0 {
+                let k = (digit - 1) as usize;
+                buckets[k] = &buckets[k] + point;
+            } else if digit < 0 {
+                let k = (-(digit as i16) - 1) as usize;
+                buckets[k] = &buckets[k] - point;
+            }
+        }
+        // Σ (k + 1)⋅buckets[k], as a sum of running sums, from the top bucket down.
+        let mut running = EdwardsPoint::IDENTITY;
+        let mut column = EdwardsPoint::IDENTITY;
+        for bucket in buckets.iter().rev() {
+            running += bucket;
+            column += running;
+        }
+        out = out.mul_by_pow_2(w as u32) + column;
+    }
+    out
+}
+
+impl MultiscalarMul for EdwardsPoint {
+    type Point = EdwardsPoint;
+
+    fn multiscalar_mul<I, J>(scalars: I, points: J) -> EdwardsPoint
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<EdwardsPoint>,
+    {
+        let (mut scalars, mut points) = collect(scalars, points);
+        let out = straus(&scalars, &points);
+        // The caller might be multiplying secret points, as well as secret scalars.
+        wipe(&mut scalars);
+        wipe(&mut points);
+        out
+    }
+}
+
+impl VartimeMultiscalarMul for EdwardsPoint {
+    type Point = EdwardsPoint;
+
+    fn vartime_multiscalar_mul<I, J>(scalars: I, points: J) -> EdwardsPoint
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<EdwardsPoint>,
+    {
+        let (scalars, points) = collect(scalars, points);
+        if points.len() < PIPPENGER_THRESHOLD {
+            straus_vartime(&scalars, &points)
+        } else {
+            pippenger_vartime(&scalars, &points)
+        }
+    }
+}
+
+/// Precomputed tables for a fixed set of points, for repeated variable time
+/// multiscalar multiplication.
+///
+/// Each static point gets a table of its odd multiples, up to 127 times the point, for
+/// the width 8 non-adjacent form of its scalar, as with an
+/// [`EdwardsPrecomputation`](super::EdwardsPrecomputation). The dynamic points get the
+/// same width 5 tables as in [`EdwardsPoint::vartime_multiscalar_mul`], built on
+/// every call. All of the points then share the same doublings, with Straus' method.
+///
+/// With the `small-tables` feature, the static points use width 5 tables as well.
+#[derive(Clone, Debug)]
+pub struct VartimeEdwardsPrecomputation(Vec<NafLookupTable<AffineNiels, PRECOMPUTATION_ENTRIES>>);
+
+impl VartimePrecomputedMultiscalarMul for VartimeEdwardsPrecomputation {
+    type Point = EdwardsPoint;
+
+    fn new<I>(static_points: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<EdwardsPoint>,
+    {
+        Self(
+            static_points
+                .into_iter()
+                .map(|p| NafLookupTable::from(p.borrow()))
+                .collect(),
+        )
+    }
+
+    fn vartime_mixed_multiscalar_mul<I, J, K>(
+        &self,
+        static_scalars: I,
+        dynamic_scalars: J,
+        dynamic_points: K,
+    ) -> EdwardsPoint
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Scalar>,
+        K: IntoIterator,
+        K::Item: Borrow<EdwardsPoint>,
+    {
+        let static_nafs: Vec<[i8; 256]> = static_scalars
+            .into_iter()
+            .map(|s| s.borrow().non_adjacent_form(PRECOMPUTATION_WIDTH))
+            .collect();
+        assert_eq!(
+            static_nafs.len(),
+            self.0.len(),
+            "multiscalar multiplication needs as many static scalars as static points"
+        );
+        let (dynamic_scalars, dynamic_points) = collect(dynamic_scalars, dynamic_points);
+        let dynamic_nafs: Vec<[i8; 256]> = dynamic_scalars
+            .iter()
+            .map(|s| s.non_adjacent_form(5))
+            .collect();
+        let dynamic_tables: Vec<NafLookupTable<ProjectiveNiels, 8>> =
+            dynamic_points.iter().map(NafLookupTable::from).collect();
+
+        // The two kinds of tables differ, so we go through dynamic dispatch, which costs
+        // little next to the point additions.
+        let mut terms: Vec<(&[i8; 256], &dyn NafTable)> =
+            Vec::with_capacity(static_nafs.len() + dynamic_nafs.len());
+        for (naf, table) in static_nafs.iter().zip(self.0.iter()) {
+            terms.push((naf, table));
+        }
+        for (naf, table) in dynamic_nafs.iter().zip(dynamic_tables.iter()) {
+            terms.push((naf, table));
+        }
+        naf_sum(&terms)
+    }
+}
@@ -0,0 +1,222 @@
+//! Cached representations of points, which make repeated additions cheaper.
+//!
+//! These are named after Niels Duif, who came up with them for the Ed25519 paper.
+use std::ops::{Add, Neg, Sub};
+
+use subtle::{Choice, ConditionallySelectable};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use super::{EdwardsPoint, EDWARDS_D2};
+use crate::curve::field::Fp;
+
+/// A point, cached in a form that's ready to be added to an [`EdwardsPoint`].
+///
+/// For a point (X : Y : Z : T), this stores (Y + X, Y - X, Z, 2⋅d⋅T). This makes
+/// adding it to a point take 8 multiplications, instead of 9.
+#[derive(Clone, Copy, Debug)]
+pub struct ProjectiveNiels {
+    y_plus_x: Fp,
+    y_minus_x: Fp,
+    z: Fp,
+    t2d: Fp,
+}
+
+impl ProjectiveNiels {
+    /// The identity element of the group, in this representation.
+    pub const IDENTITY: Self = Self {
+        y_plus_x: Fp::ONE,
+        y_minus_x: Fp::ONE,
+        z: Fp::ONE,
+        t2d: Fp::ZERO,
+    };
+}
+
+/// A point, cached in affine form, ready to be added to an [`EdwardsPoint`].
+///
+/// For the affine point (x, y), this stores (y + x, y - x, 2⋅d⋅x⋅y). Since Z = 1,
+/// adding it to a point takes only 7 multiplications. Getting there requires an
+/// inversion, so this is best used for tables of points which are computed once.
+#[derive(Clone, Copy, Debug)]
+pub struct AffineNiels {
+    y_plus_x: Fp,
+    y_minus_x: Fp,
+    xy2d: Fp,
+}
+
+impl AffineNiels {
+    /// The identity element of the group, in this representation.
+    pub const IDENTITY: Self = Self {
+        y_plus_x: Fp::ONE,
+        y_minus_x: Fp::ONE,
+        xy2d: Fp::ZERO,
+    };
+
+    /// Create this representation directly from the encodings of its fields.
+    ///
+    /// This is for tables computed ahead of time, so it doesn't check anything.
+    pub(crate) const fn from_bytes(
+        y_plus_x: &[u8; 32],
+        y_minus_x: &[u8; 32],
+        xy2d: &[u8; 32],
+    ) -> Self {
+        Self {
+            y_plus_x: Fp::from_bytes(y_plus_x),
+            y_minus_x: Fp::from_bytes(y_minus_x),
+            xy2d: Fp::from_bytes(xy2d),
+        }
+    }
+
+    /// Create this representation from the affine coordinates of a point.
+    pub(crate) fn from_affine(x: &Fp, y: &Fp) -> Self {
+        Self {
+            y_plus_x: y + x,
+            y_minus_x: y - x,
+            xy2d: x * y * EDWARDS_D2,
+        }
+    }
+}
+
+impl EdwardsPoint {
+    /// Convert this point into a form that's cheaper to add to other points.
+    ///
+    /// This runs in constant time.
+    pub fn to_projective_niels(&self) -> ProjectiveNiels {
+        ProjectiveNiels {
+            y_plus_x: self.y + self.x,
+            y_minus_x: self.y - self.x,
+            z: self.z,
+            t2d: self.t * EDWARDS_D2,
+        }
+    }
+
+    /// Convert this point into an affine form that's even cheaper to add to other points.
+    ///
+    /// This requires an inversion, so it's relatively expensive. This runs in constant time.
+    pub fn to_affine_niels(&self) -> AffineNiels {
+        let (x, y) = self.to_affine();
+        AffineNiels::from_affine(&x, &y)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for ProjectiveNiels {
+    fn zeroize(&mut self) {
+        self.y_plus_x.zeroize();
+        self.y_minus_x.zeroize();
+        self.z.zeroize();
+        self.t2d.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for AffineNiels {
+    fn zeroize(&mut self) {
+        self.y_plus_x.zeroize();
+        self.y_minus_x.zeroize();
+        self.xy2d.zeroize();
+    }
+}
+
+impl ConditionallySelectable for ProjectiveNiels {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            y_plus_x: Fp::conditional_select(&a.y_plus_x, &b.y_plus_x, choice),
+            y_minus_x: Fp::conditional_select(&a.y_minus_x, &b.y_minus_x, choice),
+            z: Fp::conditional_select(&a.z, &b.z, choice),
+            t2d: Fp::conditional_select(&a.t2d, &b.t2d, choice),
+        }
+    }
+}
+
+impl ConditionallySelectable for AffineNiels {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            y_plus_x: Fp::conditional_select(&a.y_plus_x, &b.y_plus_x, choice),
+            y_minus_x: Fp::conditional_select(&a.y_minus_x, &b.y_minus_x, choice),
+            xy2d: Fp::conditional_select(&a.xy2d, &b.xy2d, choice),
+        }
+    }
+}
+
+impl Neg for &ProjectiveNiels {
+    type Output = ProjectiveNiels;
+
+    fn neg(self) -> Self::Output {
+        // -(X : Y : Z : T) = (-X : Y : Z : -T), which swaps Y + X and Y - X.
+        ProjectiveNiels {
+            y_plus_x: self.y_minus_x,
+            y_minus_x: self.y_plus_x,
+            z: self.z,
+            t2d: -self.t2d,
+        }
+    }
+}
+
+impl Neg for ProjectiveNiels {
+    type Output = ProjectiveNiels;
+
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
+impl Neg for &AffineNiels {
+    type Output = AffineNiels;
+
+    fn neg(self) -> Self::Output {
+        AffineNiels {
+            y_plus_x: self.y_minus_x,
+            y_minus_x: self.y_plus_x,
+            xy2d: -self.xy2d,
+        }
+    }
+}
+
+impl Neg for AffineNiels {
+    type Output = AffineNiels;
+
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
+impl Add<&ProjectiveNiels> for &EdwardsPoint {
+    type Output = EdwardsPoint;
+
+    fn add(self, other: &ProjectiveNiels) -> Self::Output {
+        let a = (self.y - self.x) * other.y_minus_x;
+        let b = (self.y + self.x) * other.y_plus_x;
+        let c = self.t * other.t2d;
+        let d = (self.z * other.z).double();
+        EdwardsPoint::finish_add(a, b, c, d)
+    }
+}
+
+impl Sub<&ProjectiveNiels> for &EdwardsPoint {
+    type Output = EdwardsPoint;
+
+    fn sub(self, other: &ProjectiveNiels) -> Self::Output {
+        self + &(-other)
+    }
+}
+
+impl Add<&AffineNiels> for &EdwardsPoint {
+    type Output = EdwardsPoint;
+
+    fn add(self, other: &AffineNiels) -> Self::Output {
+        let a = (self.y - self.x) * other.y_minus_x;
+        let b = (self.y + self.x) * other.y_plus_x;
+        let c = self.t * other.xy2d;
+        let d = self.z.double();
+        EdwardsPoint::finish_add(a, b, c, d)
+    }
+}
+
+impl Sub<&AffineNiels> for &EdwardsPoint {
+    type Output = EdwardsPoint;
+
+    fn sub(self, other: &AffineNiels) -> Self::Output {
+        self + &(-other)
+    }
+}
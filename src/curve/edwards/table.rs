@@ -0,0 +1,265 @@
+//! Tables of precomputed multiples of points, for faster scalar multiplication.
+use std::ops::Mul;
+
+use subtle::{Choice, ConditionallyNegatable, ConditionallySelectable, ConstantTimeEq};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use super::{AffineNiels, EdwardsPoint, ProjectiveNiels};
+use crate::{
+    curve::{field::Fp, scalar::Scalar},
+    util::wipe,
+};
+
+/// Types with an identity element, which a table lookup returns for a digit of 0.
+pub(crate) trait Identity {
+    const IDENTITY: Self;
+}
+
+impl Identity for ProjectiveNiels {
+    const IDENTITY: Self = ProjectiveNiels::IDENTITY;
+}
+
+impl Identity for AffineNiels {
+    const IDENTITY: Self = AffineNiels::IDENTITY;
+}
+
+/// The multiples 1⋅P, 2⋅P, ..., N⋅P of some point P.
+///
+/// Along with cheap negation, this lets us look up x⋅P for any -N ≤ x ≤ N, which,
+/// with N = 8, is what we need for the signed digits of [`Scalar::as_radix_16`].
+/// Every constant time scalar multiplication goes through this table, so that secret
+/// digits only ever pick out an entry through [`Self::select`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LookupTable<T, const N: usize>([T; N]);
+
+impl<T, const N: usize> LookupTable<T, N>
+where
+    T: Identity + ConditionallySelectable + ConditionallyNegatable,
+{
+    /// Look up x⋅P, for -N ≤ x ≤ N.
+    ///
+    /// This runs in constant time, with respect to x: every entry gets read.
+    pub(crate) fn select(&self, x: i8) -> T {
+        debug_assert!(usize::from(x.unsigned_abs()) <= N);
+        // Branchless absolute value: the mask is -1 if x is negative, and 0 otherwise.
+        let mask = x >> 7;
+        let abs = ((x + mask) ^ mask) as u8;
+        let mut out = T::IDENTITY;
+        for (j, entry) in (1u8..).zip(self.0.iter()) {
+            out.conditional_assign(entry, abs.ct_eq(&j));
+        }
+        out.conditional_negate(Choice::from((mask & 1) as u8));
+        out
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: Zeroize, const N: usize> Zeroize for LookupTable<T, N> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const N: usize> From<&EdwardsPoint> for LookupTable<ProjectiveNiels, N> {
+    fn from(point: &EdwardsPoint) -> Self {
+        let mut acc = *point;
+        Self(std::array::from_fn(|_| {
+            let out = acc.to_projective_niels();
+            acc += point;
+            out
+        }))
+    }
+}
+
+/// The odd multiples 1⋅P, 3⋅P, ..., (2⋅N - 1)⋅P of some point P.
+///
+/// With N = 2ʷ⁻², these are the multiples needed for the width w non-adjacent form of
+/// a scalar, as returned by [`Scalar::non_adjacent_form`]. We use width 5, with
+/// N = 8, for one-off multiplications, and width 8, with N = 64, for points we
+/// multiply many times, where the larger table pays off.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct NafLookupTable<T, const N: usize>([T; N]);
+
+impl<T: Copy, const N: usize> NafLookupTable<T, N> {
+    /// Look up x⋅P, for an odd 0 < x < 2⋅N.
+    ///
+    /// This runs in variable time, and should only be used with public digits.
+    pub(crate) fn select(&self, x: usize) -> T {
+        debug_assert!(x & 1 == 1 && x < 2 * N);
+        self.0[x / 2]
+    }
+}
+
+impl<const N: usize> From<&EdwardsPoint> for NafLookupTable<ProjectiveNiels, N> {
+    fn from(point: &EdwardsPoint) -> Self {
+        let point2 = point.double();
+        let mut acc = *point;
+        Self(std::array::from_fn(|_| {
+            let out = acc.to_projective_niels();
+            acc += point2;
+            out
+        }))
+    }
+}
+
+impl<const N: usize> From<&EdwardsPoint> for NafLookupTable<AffineNiels, N> {
+    fn from(point: &EdwardsPoint) -> Self {
+        let point2 = point.double();
+        let mut points = Vec::with_capacity(N);
+        let mut acc = *point;
+        for _ in 0..N {
+            points.push(acc);
+            acc += point2;
+        }
+        let mut niels = batch_to_affine_niels(&points).into_iter();
+        Self(std::array::from_fn(|_| niels.next().unwrap()))
+    }
+}
+
+/// A table of odd multiples of a point, for scalar multiplication with a non-adjacent form.
+pub(crate) trait NafTable {
+    /// Calculate point + x⋅P, for an odd digit x, within the range of the table.
+    ///
+    /// This runs in variable time, and should only be used with public digits.
+    fn add_multiple(&self, point: &EdwardsPoint, x: i8) -> EdwardsPoint;
+}
+
+impl<const N: usize> NafTable for NafLookupTable<ProjectiveNiels, N> {
+    fn add_multiple(&self, point: &EdwardsPoint, x: i8) -> EdwardsPoint {
+        let entry = self.select(usize::from(x.unsigned_abs()));
+        if x > 0 {
+            point + &entry
+        } else {
+            point - &entry
+        }
+    }
+}
+
+impl<const N: usize> NafTable for NafLookupTable<AffineNiels, N> {
+    fn add_multiple(&self, point: &EdwardsPoint, x: i8) -> EdwardsPoint {
+        let entry = self.select(usize::from(x.unsigned_abs()));
+        if x > 0 {
+            point + &entry
+        } else {
+            point - &entry
+        }
+    }
+}
+
+/// Convert points to affine form, with a single inversion for all of them.
+///
+/// This runs in constant time, with respect to the values of the points.
+fn batch_to_affine_niels(points: &[EdwardsPoint]) -> Vec<AffineNiels> {
+    let mut z_invs: Vec<Fp> = points.iter().map(|p| p.z).collect();
+    Fp::batch_invert(&mut z_invs);
+    points
+        .iter()
+        .zip(z_invs.iter())
+        .map(|(p, z_inv)| AffineNiels::from_affine(&(p.x * z_inv), &(p.y * z_inv)))
+        .collect()
+}
+
+/// The number of lookup tables in an [`EdwardsBasepointTable`].
+#[cfg(not(feature = "small-tables"))]
+const BASEPOINT_TABLES: usize = 32;
+#[cfg(feature = "small-tables")]
+const BASEPOINT_TABLES: usize = 4;
+
+/// The number of radix 16 digits of a scalar sharing each of the tables.
+const BASEPOINT_STRIDE: usize = 64 / BASEPOINT_TABLES;
+
+/// Precomputed multiples of a fixed point, usually the basepoint, for fast scalar
+/// multiplication.
+///
+/// For a point B, this stores j⋅16²ⁱ⋅B, for 1 ≤ j ≤ 8, and 0 ≤ i < 32, in affine form.
+/// Multiplying by a scalar then only takes 64 mixed additions, and 4 doublings, instead
+/// of a doubling and an addition for each of the 64 digits of the scalar.
+///
+/// With the `small-tables` feature, this only stores j⋅16¹⁶ⁱ⋅B, for 0 ≤ i < 4, shrinking
+/// the table from 24 KiB down to 3 KiB, at the cost of 60 doublings per multiplication.
+///
+/// Building the table is much more expensive than a single multiplication,
+/// so this only pays off when the point is reused, as is the case for keygen and
+/// signing, with the basepoint.
+#[derive(Clone, Debug)]
+pub struct EdwardsBasepointTable([LookupTable<AffineNiels, 8>; BASEPOINT_TABLES]);
+
+impl EdwardsBasepointTable {
+    /// Precompute the table for a given point.
+    ///
+    /// This runs in constant time.
+    pub fn new(basepoint: &EdwardsPoint) -> Self {
+        // All of the multiples, in extended coordinates, with table i at [8 * i..8 * (i + 1)].
+        let mut points = Vec::with_capacity(BASEPOINT_TABLES * 8);
+        let mut base = *basepoint;
+        for _ in 0..BASEPOINT_TABLES {
+            let mut acc = base;
+            for _ in 0..8 {
+                points.push(acc);
+                acc += base;
+            }
+            base = base.mul_by_pow_2(4 * BASEPOINT_STRIDE as u32);
+        }
+        let mut niels = batch_to_affine_niels(&points).into_iter();
+        Self(std::array::from_fn(|_| {
+            LookupTable(std::array::from_fn(|_| niels.next().unwrap()))
+        }))
+    }
+}
+
+impl EdwardsBasepointTable {
+    /// Build a table from the encodings of its entries, computed ahead of time.
+    ///
+    /// Entry [i][j] holds the fields of (j + 1)⋅16ˢⁱ⋅B, with s = 64 / [`BASEPOINT_TABLES`],
+    /// as for [`AffineNiels::from_bytes`].
+    pub(crate) const fn from_bytes(bytes: &[[[[u8; 32]; 3]; 8]; BASEPOINT_TABLES]) -> Self {
+        let mut tables = [LookupTable([AffineNiels::IDENTITY; 8]); BASEPOINT_TABLES];
+        let mut i = 0;
+        while i < BASEPOINT_TABLES {
+            let mut j = 0;
+            while j < 8 {
+                let [y_plus_x, y_minus_x, xy2d] = &bytes[i][j];
+                tables[i].0[j] = AffineNiels::from_bytes(y_plus_x, y_minus_x, xy2d);
+                j += 1;
+            }
+            i += 1;
+        }
+        Self(tables)
+    }
+}
+
+impl Mul<&Scalar> for &EdwardsBasepointTable {
+    type Output = EdwardsPoint;
+
+    /// Multiply the point this table was built for by a scalar.
+    ///
+    /// This runs in constant time.
+    fn mul(self, scalar: &Scalar) -> Self::Output {
+        // With s = Σ dᵢ⋅16ⁱ, and a stride of k digits per table, we group the digits
+        // by i mod k, so that:
+        //     s⋅B = Σⱼ 16ʲ⋅(Σᵢ dₖᵢ₊ⱼ⋅16ᵏⁱ⋅B),
+        // and each inner term is an entry in one of our tables. By default, k = 2,
+        // and we only need to multiply by 16 once.
+        let mut digits = scalar.as_radix_16();
+        let mut out = EdwardsPoint::IDENTITY;
+        for j in (0..BASEPOINT_STRIDE).rev() {
+            for (i, table) in self.0.iter().enumerate() {
+                out = &out + &table.select(digits[BASEPOINT_STRIDE * i + j]);
+            }
+            if j > 0 {
+                out = out.mul_by_pow_2(4);
+            }
+        }
+        wipe(&mut digits);
+        out
+    }
+}
+
+impl Mul<&EdwardsBasepointTable> for &Scalar {
+    type Output = EdwardsPoint;
+
+    fn mul(self, table: &EdwardsBasepointTable) -> Self::Output {
+        table * self
+    }
+}
@@ -0,0 +1,143 @@
+//! Variable time scalar multiplication, for public scalars.
+//!
+//! These functions branch on, and index tables with, the digits of their scalars, so
+//! they leak them through timing. This is fine when the scalars are public, like when
+//! verifying signatures, where being faster matters more.
+#[cfg(target_arch = "x86_64")]
+use super::avx2;
+use super::{
+    table::{NafLookupTable, NafTable},
+    AffineNiels, EdwardsPoint, ProjectiveNiels, ED25519_BASEPOINT_POINT,
+};
+use crate::curve::scalar::Scalar;
+
+/// Calculate Σ aᵢ⋅Pᵢ, given the non-adjacent form of each aᵢ, and a matching table
+/// of odd multiples of each Pᵢ.
+///
+/// This interleaves the digits of all of the scalars, so that the points share the same
+/// doublings, and skips the additions for zero digits, which are most of them.
+pub(crate) fn naf_sum<T: NafTable + ?Sized>(terms: &[(&[i8; 256], &T)]) -> EdwardsPoint {
+    // Skip the leading zero digits, common to all of the scalars.
+    let Some(top) = (0..256)
+        .rev()
+        .find(|&i| terms.iter().any(|(naf, _)| naf[i] != 0))
+    else {
+        return EdwardsPoint::IDENTITY;
+    };
+
+    let mut out = EdwardsPoint::IDENTITY;
+    for i in (0..=top).rev() {
+        out = out.double();
+        for (naf, table) in terms {
+            if naf[i] != 0 {
+                out = table.add_multiple(&out, naf[i]);
+            }
+        }
+    }
+    out
+}
+
+/// Calculate Σ aᵢ⋅Pᵢ, with Straus' method, in variable time.
+///
+/// This interleaves the width 5 non-adjacent forms of the scalars, so that all of the
+/// points share the same doublings, and most digits require no addition.
+/// On x86_64, this uses the vectorized point arithmetic in [`super::avx2`], if the CPU
+/// supports AVX2.
+pub(crate) fn straus_vartime(scalars: &[Scalar], points: &[EdwardsPoint]) -> EdwardsPoint {
+    let nafs: Vec<[i8; 256]> = scalars.iter().map(|s| s.non_adjacent_form(5)).collect();
+    #[cfg(target_arch = "x86_64")]
+    if avx2::is_supported() {
+        // Safe, since we just checked that the CPU supports AVX2.
+        return unsafe { avx2::straus_vartime(&nafs, points) };
+    }
+    let tables: Vec<NafLookupTable<ProjectiveNiels, 8>> =
+        points.iter().map(NafLookupTable::from).collect();
+    let terms: Vec<_> = nafs.iter().zip(tables.iter()).collect();
+    naf_sum(&terms)
+}
+
+impl EdwardsPoint {
+    /// Calculate scalar⋅self, for a public scalar.
+    ///
+    /// This uses the width 5 non-adjacent form of the scalar, along with a table of the
+    /// odd multiples 1⋅self, 3⋅self, ..., 15⋅self. On average, only one in six digits
+    /// needs an addition, whereas constant time multiplication can't skip any.
+    ///
+    /// This runs in variable time, and should only be used with public inputs.
+    pub fn vartime_mul(&self, scalar: &Scalar) -> EdwardsPoint {
+        straus_vartime(&[*scalar], &[*self])
+    }
+
+    /// Calculate a⋅A + b⋅B, where B is the Ed25519 basepoint.
+    ///
+    /// This interleaves the width 5 non-adjacent forms of both scalars, sharing the
+    /// doublings between the two multiplications. This is what verifying an Ed25519
+    /// signature needs.
+    ///
+    /// This runs in variable time, and should only be used with public inputs.
+    pub fn vartime_double_scalar_mul_basepoint(
+        a: &Scalar,
+        point: &EdwardsPoint,
+        b: &Scalar,
+    ) -> EdwardsPoint {
+        straus_vartime(&[*a, *b], &[*point, ED25519_BASEPOINT_POINT])
+    }
+}
+
+/// The width of the non-adjacent form used with an [`EdwardsPrecomputation`].
+#[cfg(not(feature = "small-tables"))]
+pub(crate) const PRECOMPUTATION_WIDTH: usize = 8;
+#[cfg(feature = "small-tables")]
+pub(crate) const PRECOMPUTATION_WIDTH: usize = 5;
+
+/// The number of odd multiples needed for digits of [`PRECOMPUTATION_WIDTH`].
+pub(crate) const PRECOMPUTATION_ENTRIES: usize = 1 << (PRECOMPUTATION_WIDTH - 2);
+
+/// Precomputed odd multiples of a fixed point, for repeated variable time multiplication.
+///
+/// For a point P, this stores the odd multiples 1⋅Q, 3⋅Q, ..., 127⋅Q of Q = 2⁶⁴ʲ⋅P,
+/// for 0 ≤ j < 4, in affine form. Multiplying by a scalar then splits its width 8
+/// non-adjacent form into 4 chunks of 64 digits, one for each table, so that only
+/// 64 doublings are needed, instead of about 253 for [`EdwardsPoint::vartime_mul`],
+/// along with fewer, and cheaper, additions.
+///
+/// With the `small-tables` feature, this uses width 5 instead, storing only
+/// 1⋅Q, 3⋅Q, ..., 15⋅Q, in 3 KiB instead of 24 KiB, at the cost of more additions.
+///
+/// Building the tables costs about as much as a few multiplications, so this pays off
+/// when the same point gets multiplied many times, like a public key verifying many
+/// signatures. For those, [`EdwardsBasepointTable`](super::EdwardsBasepointTable) takes
+/// care of the multiplication by the basepoint.
+#[derive(Clone, Debug)]
+pub struct EdwardsPrecomputation([NafLookupTable<AffineNiels, PRECOMPUTATION_ENTRIES>; 4]);
+
+impl EdwardsPrecomputation {
+    /// Precompute the multiples of a point.
+    pub fn new(point: &EdwardsPoint) -> Self {
+        let mut base = *point;
+        Self(std::array::from_fn(|_| {
+            let table = NafLookupTable::from(&base);
+            base = base.mul_by_pow_2(64);
+            table
+        }))
+    }
+
+    /// Calculate scalar⋅P, for the point P this was built from.
+    ///
+    /// This runs in variable time, and should only be used with public inputs.
+    pub fn vartime_mul(&self, scalar: &Scalar) -> EdwardsPoint {
+        let naf = scalar.non_adjacent_form(PRECOMPUTATION_WIDTH);
+        // Chunk j holds the digits of 2⁶⁴ʲ, ..., 2⁶⁴ʲ⁺⁶³, which multiply 2⁶⁴ʲ⋅P.
+        let chunks: [[i8; 256]; 4] = std::array::from_fn(|j| {
+            let mut chunk = [0i8; 256];
+            chunk[..64].copy_from_slice(&naf[64 * j..64 * (j + 1)]);
+            chunk
+        });
+        naf_sum(&[
+            (&chunks[0], &self.0[0]),
+            (&chunks[1], &self.0[1]),
+            (&chunks[2], &self.0[2]),
+            (&chunks[3], &self.0[3]),
+        ])
+    }
+}
@@ -0,0 +1,502 @@
+//! Vectorized arithmetic on four field elements at once, using AVX2.
+//!
+//! Each element is stored with ten limbs of alternately 26 and 25 bits, like the
+//! portable 32 bit backend. We keep limb i of all four elements in the four 64 bit
+//! lanes of a single vector, so that `vpmuludq` computes four limb products at once.
+use core::arch::x86_64::*;
+use std::{
+    fmt::Debug,
+    ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign},
+};
+
+use subtle::Choice;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use super::{
+    radix25::{bytes_to_limbs, carry_limbs, limb_mask, limbs_to_bytes, mul19, N},
+    Fp,
+};
+use crate::util::wipe;
+
+// All of the intrinsics we use are wrapped in these helpers, which are only safe to
+// call when the CPU supports AVX2. Everything here is inlined into the functions using
+// it, so that the intrinsics are too.
+
+/// The vector with every lane set to zero.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn zero() -> __m256i {
+    _mm256_setzero_si256()
+}
+
+/// The vector with every lane set to x.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn splat(x: u64) -> __m256i {
+    _mm256_set1_epi64x(x as i64)
+}
+
+/// Add each pair of 64 bit lanes.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn add(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_add_epi64(a, b)
+}
+
+/// Subtract each pair of 64 bit lanes.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn sub(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_sub_epi64(a, b)
+}
+
+/// Calculate the bitwise and of each pair of lanes.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn and(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_and_si256(a, b)
+}
+
+/// Multiply the low 32 bits of each pair of 64 bit lanes, producing 64 bit results.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn mul32(a: __m256i, b: __m256i) -> __m256i {
+    _mm256_mul_epu32(a, b)
+}
+
+/// Shift each lane right by the number of bits in limb i.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn shift_limb(x: __m256i, i: usize) -> __m256i {
+    if i & 1 == 0 {
+        _mm256_srli_epi64::<26>(x)
+    } else {
+        _mm256_srli_epi64::<25>(x)
+    }
+}
+
+/// Keep the bits of limb i in each lane.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn mask_limb(x: __m256i, i: usize) -> __m256i {
+    and(x, splat(limb_mask(i)))
+}
+
+/// Shift each lane left by K bits.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn shl<const K: i32>(x: __m256i) -> __m256i {
+    _mm256_slli_epi64::<K>(x)
+}
+
+/// Carry wide lanes back into limbs, like the portable backend does.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn carry(mut z: [__m256i; N]) -> [__m256i; N] {
+    carry_limbs!(z, add, shift_limb, mask_limb, shl);
+    z
+}
+
+/// Carry each limb into the next one, wrapping around with a factor of 19, all at once.
+///
+/// Unlike with [`carry`], the carries don't depend on each other, which makes this
+/// faster, but it only works with smaller lanes. Each lane must be < 2³², and then every
+/// limb of the output exceeds its nominal size by less than 2¹².
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn carry_parallel(z: [__m256i; N]) -> [__m256i; N] {
+    let mut out = [zero(); N];
+    for i in 0..N {
+        out[i] = mask_limb(z[i], i);
+    }
+    for i in 0..N - 1 {
+        out[i + 1] = add(out[i + 1], shift_limb(z[i], i));
+    }
+    out[0] = add(out[0], mul19!(shift_limb(z[9], 9), add, shl));
+    out
+}
+
+/// Four field elements, processed in parallel using AVX2.
+///
+/// This is useful whenever a computation naturally contains four independent
+/// multiplications, like in the formulas for adding points.
+///
+/// Every operation uses AVX2, so a vector must only ever be created on a CPU supporting
+/// it. Outside of the crate, this type is only available when AVX2 is enabled at compile
+/// time, which guarantees that. Within the crate, it's only created by the vectorized
+/// point arithmetic, after checking for AVX2 at runtime.
+#[derive(Clone, Copy)]
+pub struct FpVec4 {
+    limbs: [__m256i; N],
+}
+
+impl Debug for FpVec4 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // This goes through the Debug implementation of Fp, which redacts secrets.
+        f.debug_tuple("FpVec4").field(&self.split()).finish()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for FpVec4 {
+    fn zeroize(&mut self) {
+        self.limbs.zeroize();
+    }
+}
+
+impl FpVec4 {
+    /// Pack four elements into a vector.
+    #[inline(always)]
+    pub fn new(x0: &Fp, x1: &Fp, x2: &Fp, x3: &Fp) -> Self {
+        let l0 = bytes_to_limbs(&x0.to_bytes());
+        let l1 = bytes_to_limbs(&x1.to_bytes());
+        let l2 = bytes_to_limbs(&x2.to_bytes());
+        let l3 = bytes_to_limbs(&x3.to_bytes());
+        // Safe, since this is only called when the CPU supports AVX2, as explained above.
+        unsafe {
+            let mut limbs = [zero(); N];
+            for i in 0..N {
+                limbs[i] =
+                    _mm256_set_epi64x(l3[i] as i64, l2[i] as i64, l1[i] as i64, l0[i] as i64);
+            }
+            Self { limbs }
+        }
+    }
+
+    /// Unpack the four elements in this vector.
+    #[inline(always)]
+    pub fn split(&self) -> [Fp; 4] {
+        let mut lanes = [[0u64; N]; 4];
+        for i in 0..N {
+            let mut tmp = [0u64; 4];
+            // Safe, since tmp has exactly 32 bytes, and we don't need any alignment, and
+            // this vector only exists if the CPU supports AVX2.
+            unsafe { _mm256_storeu_si256(tmp.as_mut_ptr() as *mut __m256i, self.limbs[i]) };
+            for (lane, x) in lanes.iter_mut().zip(tmp) {
+                lane[i] = x;
+            }
+        }
+        lanes.map(|lane| Fp::from_bytes(&limbs_to_bytes(&lane)))
+    }
+
+    /// The vector with all four elements set to zero.
+    #[inline(always)]
+    pub(crate) fn zero() -> Self {
+        // Safe, since this is only called when the CPU supports AVX2, as explained above.
+        Self {
+            limbs: unsafe { [zero(); N] },
+        }
+    }
+
+    /// Rearrange the four elements, so that element i of the output is element
+    /// `lanes[i]` of this vector.
+    #[inline(always)]
+    pub(crate) fn shuffle(&self, lanes: [usize; 4]) -> Self {
+        // Each 64 bit lane is made of two 32 bit ones, which we move together.
+        let [l0, l1, l2, l3] = lanes.map(|l| 2 * l as i32);
+        let mut out = *self;
+        // Safe, since this vector only exists if the CPU supports AVX2.
+        unsafe {
+            let idx = _mm256_set_epi32(l3 + 1, l3, l2 + 1, l2, l1 + 1, l1, l0 + 1, l0);
+            for x in out.limbs.iter_mut() {
+                *x = _mm256_permutevar8x32_epi32(*x, idx);
+            }
+        }
+        out
+    }
+
+    /// Pick element i from `other` when `lanes[i]` is set, and from this vector otherwise.
+    #[inline(always)]
+    pub(crate) fn blend(&self, other: &Self, lanes: [bool; 4]) -> Self {
+        let [m0, m1, m2, m3] = lanes.map(|l| -i64::from(l));
+        let mut out = *self;
+        // Safe, since this vector only exists if the CPU supports AVX2.
+        unsafe {
+            let mask = _mm256_set_epi64x(m3, m2, m1, m0);
+            for (x, y) in out.limbs.iter_mut().zip(other.limbs.iter()) {
+                *x = _mm256_blendv_epi8(*x, *y, mask);
+            }
+        }
+        out
+    }
+
+    /// Calculate the square of all four elements.
+    #[inline(always)]
+    pub fn square(&self) -> Self {
+        let a = &self.limbs;
+        // Safe, since this vector only exists if the CPU supports AVX2.
+        unsafe {
+            // As in `mul_assign`, this is a loop, and not a closure, so that it gets inlined.
+            let mut a19 = [zero(); N];
+            for (x19, x) in a19.iter_mut().zip(a.iter()) {
+                *x19 = mul32(*x, splat(19));
+            }
+            let mut z = [zero(); N];
+            square_row::<0>(&mut z, a, &a19);
+            square_row::<1>(&mut z, a, &a19);
+            square_row::<2>(&mut z, a, &a19);
+            square_row::<3>(&mut z, a, &a19);
+            square_row::<4>(&mut z, a, &a19);
+            square_row::<5>(&mut z, a, &a19);
+            square_row::<6>(&mut z, a, &a19);
+            square_row::<7>(&mut z, a, &a19);
+            square_row::<8>(&mut z, a, &a19);
+            square_row::<9>(&mut z, a, &a19);
+            let out = Self { limbs: carry(z) };
+            wipe(&mut z);
+            out
+        }
+    }
+
+    /// Square all four elements k times in place, like [`Fp::pow2k`].
+    #[inline(always)]
+    pub fn pow2k(&mut self, k: u32) {
+        // Safe, since this vector only exists if the CPU supports AVX2.
+        *self = unsafe { pow2k(*self, k) };
+    }
+
+    /// Calculate x^((P - 5) / 8) for all four elements, like [`Fp::pow_p58`].
+    #[inline(always)]
+    pub fn pow_p58(&self) -> Self {
+        let (mut out, _) = pow22501!(self);
+        out.pow2k(2); // 2²⁵² - 2²
+        out *= self; // 2²⁵² - 3
+        out
+    }
+}
+
+/// Square all four elements of x k times.
+///
+/// Unlike the rest of the arithmetic, this isn't inlined: the exponentiations call it
+/// about ten times, and inlining every copy of the loop makes them over twice as slow.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[target_feature(enable = "avx2")]
+#[inline(never)]
+unsafe fn pow2k(mut x: FpVec4, k: u32) -> FpVec4 {
+    for _ in 0..k {
+        x = x.square();
+    }
+    x
+}
+
+/// Calculate [`Fp::sqrt_ratio_i`] for four ratios at once.
+///
+/// Nearly all of the work is in the exponentiation, which we run on all four candidate
+/// roots together, before fixing each of them up separately.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[target_feature(enable = "avx2")]
+// Only the operators on references are marked to always be inlined, so we stick to those.
+#[allow(clippy::op_ref)]
+pub(crate) unsafe fn sqrt_ratio_i(u: &[Fp; 4], v: &[Fp; 4]) -> [(Choice, Fp); 4] {
+    let uv = FpVec4::new(&u[0], &u[1], &u[2], &u[3]);
+    let vv = FpVec4::new(&v[0], &v[1], &v[2], &v[3]);
+    let v3 = &vv.square() * &vv;
+    let v7 = &v3.square() * &vv;
+    let r = &(&uv * &v3) * &(&uv * &v7).pow_p58();
+    let r = r.split();
+    // We avoid closures in here, since they wouldn't have AVX2 enabled.
+    let mut out = [(Choice::from(0), Fp::ZERO); 4];
+    for i in 0..4 {
+        out[i] = Fp::sqrt_ratio_i_finish(&u[i], &v[i], r[i]);
+    }
+    out
+}
+
+/// Add the products of limb I with limbs I, ..., 9 into z, for squaring.
+///
+/// We write out the rows one by one, with a constant I, so that the branches in here
+/// disappear, and the compiler unrolls the loop, which it doesn't do for all 100 products.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn square_row<const I: usize>(z: &mut [__m256i; N], a: &[__m256i; N], a19: &[__m256i; N]) {
+    // Cross products appear twice, so we double one side of them up front, along with
+    // the factor of 2 explained in `mul_row`, for when both limbs are odd.
+    let a_i2 = add(a[I], a[I]);
+    let a_i4 = add(a_i2, a_i2);
+    for j in I..N {
+        let lhs = match (I == j, I & j & 1 == 1) {
+            (true, false) => a[I],
+            (true, true) | (false, false) => a_i2,
+            (false, true) => a_i4,
+        };
+        // Since 2²⁵⁵ ≡ 19 mod P, products which land past the top limb wrap around.
+        let p = if I + j >= N {
+            mul32(lhs, a19[j])
+        } else {
+            mul32(lhs, a[j])
+        };
+        z[(I + j) % N] = add(z[(I + j) % N], p);
+    }
+}
+
+/// Add the products of limb I of a with every limb of b into z, for multiplication.
+///
+/// As with [`square_row`], taking I as a constant lets the compiler unroll this.
+///
+/// # Safety
+///
+/// The CPU running this function must support AVX2.
+#[inline(always)]
+unsafe fn mul_row<const I: usize>(
+    z: &mut [__m256i; N],
+    a: &[__m256i; N],
+    b: &[__m256i; N],
+    b19: &[__m256i; N],
+) {
+    // When both limbs are odd, their offsets are each half a bit short,
+    // so the product lands one bit above the offset of limb i + j.
+    let a_i2 = add(a[I], a[I]);
+    for j in 0..N {
+        let lhs = if I & j & 1 == 1 { a_i2 } else { a[I] };
+        // Since 2²⁵⁵ ≡ 19 mod P, products which land past the top limb wrap around.
+        let p = if I + j >= N {
+            mul32(lhs, b19[j])
+        } else {
+            mul32(lhs, b[j])
+        };
+        z[(I + j) % N] = add(z[(I + j) % N], p);
+    }
+}
+
+impl AddAssign<&FpVec4> for FpVec4 {
+    #[inline(always)]
+    fn add_assign(&mut self, other: &FpVec4) {
+        // Safe, since this vector only exists if the CPU supports AVX2.
+        unsafe {
+            for (x, y) in self.limbs.iter_mut().zip(other.limbs.iter()) {
+                *x = add(*x, *y);
+            }
+            self.limbs = carry_parallel(self.limbs);
+        }
+    }
+}
+
+impl Add<&FpVec4> for &FpVec4 {
+    type Output = FpVec4;
+
+    #[inline(always)]
+    fn add(self, other: &FpVec4) -> Self::Output {
+        let mut out = *self;
+        out += other;
+        out
+    }
+}
+
+define_op_variants!(impl Add, add, AddAssign, add_assign for FpVec4);
+
+impl SubAssign<&FpVec4> for FpVec4 {
+    #[inline(always)]
+    fn sub_assign(&mut self, other: &FpVec4) {
+        // Safe, since this vector only exists if the CPU supports AVX2.
+        unsafe {
+            // To avoid underflow, we add 16P first, which is larger than any limb of other.
+            for (i, (x, y)) in self.limbs.iter_mut().zip(other.limbs.iter()).enumerate() {
+                let mut p16 = 16 * limb_mask(i);
+                if i == 0 {
+                    p16 -= 16 * 18;
+                }
+                *x = sub(add(*x, splat(p16)), *y);
+            }
+            self.limbs = carry_parallel(self.limbs);
+        }
+    }
+}
+
+impl Sub<&FpVec4> for &FpVec4 {
+    type Output = FpVec4;
+
+    #[inline(always)]
+    fn sub(self, other: &FpVec4) -> Self::Output {
+        let mut out = *self;
+        out -= other;
+        out
+    }
+}
+
+define_op_variants!(impl Sub, sub, SubAssign, sub_assign for FpVec4);
+
+impl MulAssign<&FpVec4> for FpVec4 {
+    #[inline(always)]
+    fn mul_assign(&mut self, other: &FpVec4) {
+        let a = &self.limbs;
+        let b = &other.limbs;
+        // Safe, since this vector only exists if the CPU supports AVX2.
+        unsafe {
+            // Limbs are < 2²⁷, so 19 times a limb still fits in the 32 bits `vpmuludq` uses.
+            // This avoids a closure, which wouldn't get inlined along with everything else.
+            let mut b19 = [zero(); N];
+            for (x19, x) in b19.iter_mut().zip(b.iter()) {
+                *x19 = mul32(*x, splat(19));
+            }
+            let mut z = [zero(); N];
+            mul_row::<0>(&mut z, a, b, &b19);
+            mul_row::<1>(&mut z, a, b, &b19);
+            mul_row::<2>(&mut z, a, b, &b19);
+            mul_row::<3>(&mut z, a, b, &b19);
+            mul_row::<4>(&mut z, a, b, &b19);
+            mul_row::<5>(&mut z, a, b, &b19);
+            mul_row::<6>(&mut z, a, b, &b19);
+            mul_row::<7>(&mut z, a, b, &b19);
+            mul_row::<8>(&mut z, a, b, &b19);
+            mul_row::<9>(&mut z, a, b, &b19);
+            self.limbs = carry(z);
+            wipe(&mut z);
+        }
+    }
+}
+
+impl Mul<&FpVec4> for &FpVec4 {
+    type Output = FpVec4;
+
+    #[inline(always)]
+    fn mul(self, other: &FpVec4) -> Self::Output {
+        let mut out = *self;
+        out *= other;
+        out
+    }
+}
+
+define_op_variants!(impl Mul, mul, MulAssign, mul_assign for FpVec4);
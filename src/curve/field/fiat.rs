@@ -0,0 +1,176 @@
+//! A backend using formally verified arithmetic, generated by fiat-crypto.
+//!
+//! This is slower than the hand written backends, but every operation on limbs
+//! comes with a machine checked proof of correctness.
+use std::ops::{AddAssign, MulAssign, SubAssign};
+
+#[cfg(target_pointer_width = "32")]
+use fiat_crypto::curve25519_32::*;
+#[cfg(target_pointer_width = "64")]
+use fiat_crypto::curve25519_64::*;
+use subtle::{Choice, ConditionallySelectable};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::util::wipe;
+
+/// The type of each limb, and N, the number of limbs in our representation.
+///
+/// fiat-crypto uses five 51 bit limbs on 64 bit targets, and ten 25.5 bit limbs otherwise.
+#[cfg(target_pointer_width = "32")]
+type Limb = u32;
+#[cfg(target_pointer_width = "32")]
+const N: usize = 10;
+#[cfg(target_pointer_width = "64")]
+type Limb = u64;
+#[cfg(target_pointer_width = "64")]
+const N: usize = 5;
+
+/// An element of the field of integers mod P = 2²⁵⁵ - 19.
+///
+/// Elements are stored in fiat-crypto's "tight" form, whose representation isn't unique.
+/// To compare elements, use [`ConstantTimeEq`](subtle::ConstantTimeEq), which compares
+/// canonical representatives.
+#[derive(Clone, Copy)]
+pub struct Fp(fiat_25519_tight_field_element);
+
+impl Fp {
+    /// The additive identity.
+    pub const ZERO: Self = Self::from_limbs([0; N]);
+    /// The multiplicative identity.
+    pub const ONE: Self = {
+        let mut limbs = [0; N];
+        limbs[0] = 1;
+        Self::from_limbs(limbs)
+    };
+    /// Create an element directly from its limbs, in little endian order.
+    ///
+    /// This is intended for defining constants.
+    pub(crate) const fn from_limbs(limbs: [Limb; N]) -> Self {
+        Self(fiat_25519_tight_field_element(limbs))
+    }
+
+    /// View this element with loose bounds, which is what most operations take as input.
+    #[inline]
+    fn relax(&self) -> fiat_25519_loose_field_element {
+        let mut out = fiat_25519_loose_field_element([0; N]);
+        fiat_25519_relax(&mut out, &self.0);
+        out
+    }
+
+    /// Reduce this element to its canonical representative, in the range [0, P), in place.
+    ///
+    /// This runs in constant time.
+    pub(super) fn reduce_assign(&mut self) {
+        // Encoding always produces the canonical representative, and decoding preserves it.
+        *self = Self::from_bytes(&self.pack());
+    }
+
+    /// Create an element from its 32 byte little endian encoding.
+    ///
+    /// All 256 bits are used, and the value is implicitly reduced mod P. This means
+    /// that non-canonical encodings are accepted: see [`Fp::from_canonical_bytes`]
+    /// for a stricter variant.
+    pub const fn from_bytes(bytes: &[u8; 32]) -> Self {
+        // fiat-crypto requires the top bit to be cleared, so we add it back ourselves.
+        let mut masked = *bytes;
+        masked[31] &= 0x7F;
+        let mut out = Self::ZERO;
+        fiat_25519_from_bytes(&mut out.0, &masked);
+        // The top bit is worth 2²⁵⁵ ≡ 19 mod P.
+        let mut top = [0; N];
+        top[0] = 19 * (bytes[31] >> 7) as Limb;
+        out.const_add(&Self::from_limbs(top))
+    }
+
+    /// Add two elements together, in a const context.
+    ///
+    /// This is intended for defining constants: at runtime, use `+` instead.
+    pub const fn const_add(&self, other: &Self) -> Self {
+        let mut loose = fiat_25519_loose_field_element([0; N]);
+        fiat_25519_add(&mut loose, &self.0, &other.0);
+        let mut out = Self::ZERO;
+        fiat_25519_carry(&mut out.0, &loose);
+        out
+    }
+
+    /// Convert this element to its canonical 32 byte little endian encoding.
+    ///
+    /// Unlike the other backends, this works even if the element isn't reduced.
+    pub(super) fn pack(&self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        fiat_25519_to_bytes(&mut out, &self.0);
+        out
+    }
+
+    /// Multiply this element by a small constant.
+    ///
+    /// fiat-crypto doesn't provide a general version of this, so this is just
+    /// a full multiplication.
+    pub fn mul_small(&self, k: u32) -> Self {
+        self * Self::from(u64::from(k))
+    }
+
+    /// The largest k that [`Fp::shl_assign`] accepts.
+    ///
+    /// fiat-crypto doesn't provide shifts, so we double one bit at a time.
+    pub(super) const MAX_SHIFT: u32 = 1;
+
+    /// Multiply this element by 2ᵏ, in place, for 1 ≤ k ≤ [`Fp::MAX_SHIFT`].
+    pub(super) fn shl_assign(&mut self, k: u32) {
+        debug_assert_eq!(k, 1);
+        let mut loose = fiat_25519_loose_field_element([0; N]);
+        fiat_25519_add(&mut loose, &self.0, &self.0);
+        fiat_25519_carry(&mut self.0, &loose);
+        wipe(&mut loose.0);
+    }
+
+    /// Square this element, in place.
+    pub(super) fn square_assign(&mut self) {
+        let mut loose = self.relax();
+        fiat_25519_carry_square(&mut self.0, &loose);
+        wipe(&mut loose.0);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for Fp {
+    fn zeroize(&mut self) {
+        self.0 .0.zeroize();
+    }
+}
+
+impl ConditionallySelectable for Fp {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut out = Self::ZERO;
+        fiat_25519_selectznz(&mut out.0 .0, choice.unwrap_u8(), &a.0 .0, &b.0 .0);
+        out
+    }
+}
+
+impl AddAssign<&Fp> for Fp {
+    fn add_assign(&mut self, other: &Fp) {
+        let mut loose = fiat_25519_loose_field_element([0; N]);
+        fiat_25519_add(&mut loose, &self.0, &other.0);
+        fiat_25519_carry(&mut self.0, &loose);
+        wipe(&mut loose.0);
+    }
+}
+
+impl SubAssign<&Fp> for Fp {
+    fn sub_assign(&mut self, other: &Fp) {
+        let mut loose = fiat_25519_loose_field_element([0; N]);
+        fiat_25519_sub(&mut loose, &self.0, &other.0);
+        fiat_25519_carry(&mut self.0, &loose);
+        wipe(&mut loose.0);
+    }
+}
+
+impl MulAssign<&Fp> for Fp {
+    fn mul_assign(&mut self, other: &Fp) {
+        let (mut a, mut b) = (self.relax(), other.relax());
+        fiat_25519_carry_mul(&mut self.0, &a, &b);
+        wipe(&mut a.0);
+        wipe(&mut b.0);
+    }
+}
@@ -0,0 +1,606 @@
+use std::{
+    fmt::Debug,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+#[cfg(feature = "rand_core")]
+use rand_core::CryptoRngCore;
+use subtle::{Choice, ConditionallyNegatable, ConditionallySelectable, ConstantTimeEq, CtOption};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::arithmetic::{self, safegcd};
+pub use crate::arithmetic::{adc, sbb};
+use crate::util::wipe;
+
+/// Calculate (x^(2²⁵⁰ - 1), x^11), given a reference to x.
+///
+/// This is the common prefix of the addition chains we use for exponentiation. It works
+/// with anything having `square`, `pow2k`, and multiplication by reference, so the
+/// vectorized arithmetic, declared below, shares it, with every operation inlined.
+macro_rules! pow22501 {
+    ($x:expr) => {{
+        let x = $x;
+        // Each step notes the exponent we've reached for x.
+        let t0 = x.square(); // 2
+        let mut t1 = t0;
+        t1.pow2k(2); // 8
+        t1 *= x; // 9
+        let t3 = &t0 * &t1; // 11
+        let mut t5 = t3.square(); // 22
+        t5 *= &t1; // 2⁵ - 1
+        let mut t7 = t5;
+        t7.pow2k(5);
+        t7 *= &t5; // 2¹⁰ - 1
+        let mut t9 = t7;
+        t9.pow2k(10);
+        t9 *= &t7; // 2²⁰ - 1
+        let mut t11 = t9;
+        t11.pow2k(20);
+        t11 *= &t9; // 2⁴⁰ - 1
+        let mut t13 = t11;
+        t13.pow2k(10);
+        t13 *= &t7; // 2⁵⁰ - 1
+        let mut t15 = t13;
+        t15.pow2k(50);
+        t15 *= &t13; // 2¹⁰⁰ - 1
+        let mut t17 = t15;
+        t17.pow2k(100);
+        t17 *= &t15; // 2²⁰⁰ - 1
+        let mut t19 = t17;
+        t19.pow2k(50);
+        t19 *= &t13; // 2²⁵⁰ - 1
+        (t19, t3)
+    }};
+}
+// We use 25.5 bit limbs on 32 bit targets by default, since the other backends
+// rely on fast 64 × 64 → 128 bit multiplication. This includes wasm32, which has
+// native 64 bit multiplication, but no way to get the high half of a product.
+// Under wasmtime, in a release build, an inversion takes about 5.6µs with this backend,
+// against 6.7µs with the fiat-crypto one, and 23µs with 51 bit limbs.
+//
+// If several backends are requested, the fiat-crypto backend takes precedence,
+// followed by the 51 bit backend.
+#[cfg(feature = "fiat-backend")]
+mod fiat;
+// The vectorized backends below share the limb layout of this one, so it's compiled for
+// them too, even when `Fp` uses another backend.
+#[cfg(any(
+    all(
+        not(feature = "fiat-backend"),
+        not(feature = "u51-backend"),
+        any(feature = "u32-backend", target_pointer_width = "32")
+    ),
+    target_arch = "x86_64",
+    all(target_arch = "aarch64", target_feature = "neon")
+))]
+#[cfg_attr(
+    not(all(
+        not(feature = "fiat-backend"),
+        not(feature = "u51-backend"),
+        any(feature = "u32-backend", target_pointer_width = "32")
+    )),
+    allow(dead_code)
+)]
+mod radix25;
+#[cfg(all(not(feature = "fiat-backend"), feature = "u51-backend"))]
+mod radix51;
+#[cfg(not(any(
+    feature = "fiat-backend",
+    feature = "u51-backend",
+    feature = "u32-backend",
+    target_pointer_width = "32"
+)))]
+pub(crate) mod saturated;
+
+#[cfg(feature = "fiat-backend")]
+pub use fiat::Fp;
+#[cfg(all(
+    not(feature = "fiat-backend"),
+    not(feature = "u51-backend"),
+    any(feature = "u32-backend", target_pointer_width = "32")
+))]
+pub use radix25::Fp;
+#[cfg(all(not(feature = "fiat-backend"), feature = "u51-backend"))]
+pub use radix51::Fp;
+#[cfg(not(any(
+    feature = "fiat-backend",
+    feature = "u51-backend",
+    feature = "u32-backend",
+    target_pointer_width = "32"
+)))]
+pub use saturated::Fp;
+
+pub mod reference;
+
+// Vectorized arithmetic needs the relevant extensions to be enabled at compile time,
+// e.g. with `-C target-cpu=native`, since checking for them at runtime for each operation
+// would defeat the point. NEON is enabled by default on aarch64, but AVX2 isn't on x86_64.
+// Within the crate, the AVX2 arithmetic is also used by whole point algorithms, which
+// check for it once, at runtime. The NEON arithmetic is slower than two scalar
+// multiplications, so nothing uses it internally.
+#[cfg(target_arch = "x86_64")]
+pub(crate) mod avx2;
+#[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+pub use avx2::FpVec4;
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+mod neon;
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+pub use neon::FpVec2;
+
+/// P, as four 64 bit limbs, in little endian order.
+const P: [u64; 4] = [
+    0xFFFFFFFFFFFFFFED,
+    0xFFFFFFFFFFFFFFFF,
+    0xFFFFFFFFFFFFFFFF,
+    0x7FFFFFFFFFFFFFFF,
+];
+
+/// P, prepared for inversion with safegcd.
+const P_SAFEGCD: safegcd::Modulus = safegcd::Modulus::new(&P);
+
+/// Read the (up to) 8 bytes starting at a given offset, as a little endian integer.
+///
+/// Bytes past the end of the input are treated as zero. Backends use this to
+/// implement `from_bytes` as a const fn, so that we can define constants from bytes.
+#[cfg_attr(feature = "fiat-backend", allow(dead_code))]
+#[inline(always)]
+const fn load8(bytes: &[u8; 32], start: usize) -> u64 {
+    let mut out = 0;
+    let mut i = 0;
+    while i < 8 && start + i < 32 {
+        out |= (bytes[start + i] as u64) << (8 * i);
+        i += 1;
+    }
+    out
+}
+
+impl Fp {
+    /// A square root of -1, in particular, the non-negative one.
+    pub(crate) const SQRT_M1: Self = Self::from_bytes(&[
+        0xB0, 0xA0, 0x0E, 0x4A, 0x27, 0x1B, 0xEE, 0xC4, 0x78, 0xE4, 0x2F, 0xAD, 0x06, 0x18, 0x43,
+        0x2F, 0xA7, 0xD7, 0xFB, 0x3D, 0x99, 0x00, 0x4D, 0x2B, 0x0B, 0xDF, 0xC1, 0x4F, 0x80, 0x24,
+        0x83, 0x2B,
+    ]);
+
+    /// Create an element from a 64 byte little endian encoding, reducing it mod P.
+    ///
+    /// When the input is uniformly random, the result is statistically close to uniform.
+    pub fn from_bytes_wide(bytes: &[u8; 64]) -> Self {
+        // Unwrapping is fine, since each half has exactly 32 bytes.
+        let lo = Self::from_bytes(bytes[..32].try_into().unwrap());
+        let hi = Self::from_bytes(bytes[32..].try_into().unwrap());
+        // Since 2²⁵⁶ ≡ 38 mod P, we have hi⋅2²⁵⁶ + lo ≡ lo + 38⋅hi
+        lo + hi.mul_small(38)
+    }
+
+    /// Generate a uniformly random element.
+    ///
+    /// This works by reducing 64 random bytes, so the bias is negligible.
+    #[cfg(feature = "rand_core")]
+    pub fn random(rng: &mut impl CryptoRngCore) -> Self {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        Self::from_bytes_wide(&bytes)
+    }
+
+    /// Create an element from its 32 byte little endian encoding, rejecting non-canonical values.
+    ///
+    /// This will fail if the value encoded is ≥ P. This check runs in constant time.
+    pub fn from_canonical_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
+        let out = Self::from_bytes(bytes);
+        // The encoding is canonical exactly when encoding the element again round trips.
+        let is_canonical = out.to_bytes()[..].ct_eq(&bytes[..]);
+        CtOption::new(out, is_canonical)
+    }
+
+    /// Reduce this element to its canonical representative, in the range [0, P).
+    ///
+    /// The result records that it's been reduced in its type, which lets us skip
+    /// reducing it again when encoding it, or extracting its sign.
+    /// This runs in constant time.
+    pub fn reduce(&self) -> ReducedFp {
+        let mut out = *self;
+        out.reduce_assign();
+        ReducedFp(out)
+    }
+
+    /// Convert this element to its canonical 32 byte little endian encoding.
+    ///
+    /// This runs in constant time.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.reduce().to_bytes()
+    }
+
+    /// Check whether or not this element is zero, in constant time.
+    pub fn is_zero(&self) -> Choice {
+        self.reduce().is_zero()
+    }
+
+    /// Convert this element to its canonical representative, as four 64 bit limbs.
+    ///
+    /// This is the format the generic routines in [`crate::arithmetic`] work with.
+    fn canonical_limbs(&self) -> [u64; 4] {
+        let bytes = self.to_bytes();
+        let mut out = [0u64; 4];
+        for (limb, chunk) in out.iter_mut().zip(bytes.chunks_exact(8)) {
+            // Unwrapping is fine, since each chunk has exactly 8 bytes.
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        out
+    }
+
+    /// Create an element from four 64 bit limbs, in little endian order.
+    fn from_canonical_limbs(limbs: &[u64; 4]) -> Self {
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(limbs.iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// An element of the field of integers mod P = 2²⁵⁵ - 19, which is known to be fully reduced.
+///
+/// Operations which need the canonical representative of an element, like encoding,
+/// live here, so that they can't accidentally be called on an unreduced [`Fp`].
+/// Arithmetic happens on [`Fp`], which this converts into freely.
+#[derive(Clone, Copy, Debug)]
+pub struct ReducedFp(Fp);
+
+impl ReducedFp {
+    /// Convert this element to its canonical 32 byte little endian encoding.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.pack()
+    }
+
+    /// Check whether or not this element is zero, in constant time.
+    pub fn is_zero(&self) -> Choice {
+        self.to_bytes()[..].ct_eq(&[0u8; 32][..])
+    }
+
+    /// Whether or not this element is negative, i.e. if its canonical encoding is odd.
+    ///
+    /// This runs in constant time.
+    pub fn is_negative(&self) -> Choice {
+        Choice::from(self.to_bytes()[0] & 1)
+    }
+}
+
+impl From<ReducedFp> for Fp {
+    fn from(x: ReducedFp) -> Self {
+        x.0
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for ReducedFp {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ConstantTimeEq for ReducedFp {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // Canonical representatives are unique, so we can compare encodings directly.
+        self.to_bytes()[..].ct_eq(&other.to_bytes()[..])
+    }
+}
+
+impl Debug for Fp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Elements often hold secrets, which shouldn't end up in logs by accident,
+        // so we only print out their value in tests, or if explicitly asked to.
+        #[cfg(any(test, feature = "debug-secrets"))]
+        {
+            write!(f, "Fp(0x")?;
+            for x in self.to_bytes().iter().rev() {
+                write!(f, "{:02X}", x)?;
+            }
+            write!(f, ")")
+        }
+        #[cfg(not(any(test, feature = "debug-secrets")))]
+        {
+            write!(f, "Fp(<redacted>)")
+        }
+    }
+}
+
+impl From<u64> for Fp {
+    fn from(x: u64) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&x.to_le_bytes());
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl ConstantTimeEq for Fp {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // Our representation isn't unique, so we need to compare canonical representatives.
+        self.reduce().ct_eq(&other.reduce())
+    }
+}
+
+// Only implement equality for tests. This is to avoid the temptation to introduce
+// a timing leak through equality comparison.
+#[cfg(test)]
+impl PartialEq for Fp {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Fp {
+    /// Multiply this element by 121666 = (486662 + 2) / 4.
+    ///
+    /// This constant shows up in every step of the Montgomery ladder for Curve25519.
+    pub fn mul121666(&self) -> Self {
+        self.mul_small(121666)
+    }
+
+    /// Calculate 2⋅self, using shifts and carries.
+    pub fn double(&self) -> Self {
+        let mut out = *self;
+        out.double_assign();
+        out
+    }
+
+    /// Double this element, in place.
+    pub fn double_assign(&mut self) {
+        self.shl_assign(1);
+    }
+
+    /// Calculate 2ᵏ⋅self.
+    ///
+    /// This only needs shifts and carries, unlike a general multiplication.
+    /// The exponent k isn't secret: this takes time proportional to it.
+    pub fn mul_pow2(&self, mut k: u32) -> Self {
+        let mut out = *self;
+        while k > 0 {
+            let step = k.min(Self::MAX_SHIFT);
+            out.shl_assign(step);
+            k -= step;
+        }
+        out
+    }
+
+    /// Calculate the square of this element.
+    pub fn square(&self) -> Self {
+        let mut out = *self;
+        out.square_assign();
+        out
+    }
+
+    /// Square this element k times in place, calculating self^(2^k).
+    pub fn pow2k(&mut self, k: u32) {
+        for _ in 0..k {
+            self.square_assign();
+        }
+    }
+
+    /// Calculate (self^(2²⁵⁰ - 1), self^11).
+    ///
+    /// This is the common prefix of the addition chains we use for exponentiation.
+    fn pow22501(&self) -> (Self, Self) {
+        pow22501!(self)
+    }
+
+    /// Calculate the multiplicative inverse of this element.
+    ///
+    /// This uses Fermat's little theorem, computing self^(P - 2), in constant time.
+    /// The inverse of zero is defined to be zero.
+    pub fn invert(&self) -> Self {
+        let (t19, t3) = self.pow22501();
+        let mut out = t19;
+        out.pow2k(5); // 2²⁵⁵ - 2⁵
+        out *= t3; // 2²⁵⁵ - 21 = P - 2
+        out
+    }
+
+    /// Calculate the multiplicative inverse of this element, using Bernstein and Yang's
+    /// safegcd algorithm.
+    ///
+    /// This gives the same result as [`Fp::invert`], and also runs in constant time.
+    /// The inverse of zero is defined to be zero.
+    pub fn invert_safegcd(&self) -> Self {
+        let mut limbs = self.canonical_limbs();
+        let mut inverse = safegcd::invert(&limbs, &P_SAFEGCD);
+        let out = Self::from_canonical_limbs(&inverse);
+        wipe(&mut limbs);
+        wipe(&mut inverse);
+        out
+    }
+
+    /// Calculate the multiplicative inverse of this element, in variable time.
+    ///
+    /// This is faster than [`Fp::invert`], but leaks information about this element
+    /// through timing, so it should only be used on public values, like when
+    /// verifying signatures. The inverse of zero is defined to be zero.
+    pub fn vartime_invert(&self) -> Self {
+        Self::from_canonical_limbs(&arithmetic::invert_vartime(&self.canonical_limbs(), &P))
+    }
+
+    /// Calculate self^((P - 5) / 8) = self^(2²⁵² - 3).
+    ///
+    /// This is the exponentiation at the heart of computing square roots, since P ≡ 5 mod 8.
+    /// It's sometimes called `pow22523` elsewhere.
+    pub fn pow_p58(&self) -> Self {
+        let (t19, _) = self.pow22501();
+        let mut out = t19;
+        out.pow2k(2); // 2²⁵² - 2²
+        out *= self; // 2²⁵² - 3
+        out
+    }
+
+    /// Calculate self^((P + 3) / 8) = self^(2²⁵² - 2).
+    ///
+    /// When self is a square, this is one of its square roots, possibly multiplied by sqrt(-1).
+    pub fn pow_p38(&self) -> Self {
+        let (t19, _) = self.pow22501();
+        let mut out = t19;
+        out.pow2k(2); // 2²⁵² - 2²
+        out *= self.square(); // 2²⁵² - 2
+        out
+    }
+
+    /// Check whether or not this element is a square, i.e. a quadratic residue.
+    ///
+    /// Zero is considered to be a square. This runs in constant time.
+    pub fn is_square(&self) -> Choice {
+        // We compute the Legendre symbol self^((P - 1) / 2), which is 0, 1, or -1.
+        let x3 = self.square() * self;
+        let (mut out, _) = self.pow22501();
+        out.pow2k(4); // 2²⁵⁴ - 2⁴
+        out *= x3.square(); // 2²⁵⁴ - 10 = (P - 1) / 2
+        out.ct_eq(&Self::ONE) | out.is_zero()
+    }
+
+    /// Whether or not this element is negative, i.e. if its canonical encoding is odd.
+    ///
+    /// This is the sign bit used when compressing points. This runs in constant time.
+    pub fn is_negative(&self) -> Choice {
+        self.reduce().is_negative()
+    }
+
+    /// Calculate the absolute value of this element, i.e. the non-negative one of ±self.
+    ///
+    /// This runs in constant time.
+    pub fn abs(&self) -> Self {
+        let mut out = *self;
+        out.conditional_negate(self.is_negative());
+        out
+    }
+
+    /// Calculate a square root of u / v, if it exists.
+    ///
+    /// This returns a flag indicating whether or not u / v was a square, as well as:
+    ///
+    /// - `(true, 0)` if u is zero,
+    /// - `(false, 0)` if v is zero, but u isn't,
+    /// - `(true, sqrt(u / v))` if u / v is a square,
+    /// - `(false, sqrt(i * u / v))` otherwise, with i = sqrt(-1).
+    ///
+    /// The root returned is always non-negative. This runs in constant time.
+    pub fn sqrt_ratio_i(u: &Self, v: &Self) -> (Choice, Self) {
+        // Since P ≡ 5 mod 8, a candidate root is given by:
+        //     r = (u / v)^((P + 3) / 8) = u v³ (u v⁷)^((P - 5) / 8)
+        // We then have v r² = ±u or ±i u, and we can fix up the first and last
+        // cases, by multiplying by i = sqrt(-1).
+        let v3 = v.square() * v;
+        let v7 = v3.square() * v;
+        let r = u * v3 * (u * v7).pow_p58();
+        Self::sqrt_ratio_i_finish(u, v, r)
+    }
+
+    /// Finish [`Fp::sqrt_ratio_i`], given the candidate root r = u v³ (u v⁷)^((P - 5) / 8).
+    ///
+    /// The vectorized arithmetic computes r for several ratios at once, and then uses this.
+    pub(crate) fn sqrt_ratio_i_finish(u: &Self, v: &Self, mut r: Self) -> (Choice, Self) {
+        let check = v * r.square();
+
+        let minus_u = -u;
+        let correct_sign = check.ct_eq(u);
+        let flipped_sign = check.ct_eq(&minus_u);
+        let flipped_sign_i = check.ct_eq(&(minus_u * Self::SQRT_M1));
+
+        let r_prime = r * Self::SQRT_M1;
+        r.conditional_assign(&r_prime, flipped_sign | flipped_sign_i);
+
+        // We always return the non-negative root.
+        (correct_sign | flipped_sign, r.abs())
+    }
+
+    /// Calculate a square root of this element, if it exists.
+    ///
+    /// The root returned is always non-negative. This runs in constant time.
+    pub fn sqrt(&self) -> CtOption<Self> {
+        let (is_square, r) = Self::sqrt_ratio_i(self, &Self::ONE);
+        CtOption::new(r, is_square)
+    }
+
+    /// Invert every element of a slice, in place.
+    ///
+    /// This uses Montgomery's trick, which only requires a single inversion,
+    /// along with 3 multiplications per element. Zero elements are left as zero,
+    /// like with [`Fp::invert`]. This runs in constant time, with respect to the
+    /// values of the elements.
+    pub fn batch_invert(elements: &mut [Self]) {
+        // Zero elements get replaced with one, so that they don't destroy the product,
+        // and then we zero them again at the end.
+        let mut scratch = Vec::with_capacity(elements.len());
+        let mut acc = Self::ONE;
+        for x in elements.iter() {
+            scratch.push(acc);
+            acc *= Self::conditional_select(x, &Self::ONE, x.is_zero());
+        }
+        // acc is now the product of all the (non zero) elements.
+        acc = acc.invert();
+        // Now, we walk backwards, peeling off one element at a time.
+        for (x, prefix) in elements.iter_mut().rev().zip(scratch.iter().rev()) {
+            let is_zero = x.is_zero();
+            let next_acc = acc * Self::conditional_select(x, &Self::ONE, is_zero);
+            // acc is the inverse of the product up to, and including x, so
+            // multiplying by the product of the elements before x gives us 1 / x.
+            *x = Self::conditional_select(&(acc * prefix), &Self::ZERO, is_zero);
+            acc = next_acc;
+        }
+        // The prefix products reveal as much as the elements themselves.
+        wipe(&mut scratch);
+        wipe(&mut acc);
+    }
+}
+
+impl Add<&Fp> for &Fp {
+    type Output = Fp;
+
+    fn add(self, other: &Fp) -> Self::Output {
+        let mut out = *self;
+        out += other;
+        out
+    }
+}
+
+define_op_variants!(impl Add, add, AddAssign, add_assign for Fp);
+
+impl Sub<&Fp> for &Fp {
+    type Output = Fp;
+
+    fn sub(self, other: &Fp) -> Self::Output {
+        let mut out = *self;
+        out -= other;
+        out
+    }
+}
+
+define_op_variants!(impl Sub, sub, SubAssign, sub_assign for Fp);
+
+impl Mul<&Fp> for &Fp {
+    type Output = Fp;
+
+    fn mul(self, other: &Fp) -> Self::Output {
+        let mut out = *self;
+        out *= other;
+        out
+    }
+}
+
+define_op_variants!(impl Mul, mul, MulAssign, mul_assign for Fp);
+
+impl Neg for Fp {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        let mut out = Fp::ZERO;
+        out -= self;
+        out
+    }
+}
+
+// This also gives us an implementation of ConditionallyNegatable.
+impl Neg for &Fp {
+    type Output = Fp;
+
+    fn neg(self) -> Self::Output {
+        -*self
+    }
+}
@@ -0,0 +1,418 @@
+//! A portable backend storing elements as ten limbs of alternately 26 and 25 bits.
+//!
+//! This only needs 32 × 32 → 64 bit multiplications, which makes it much faster
+//! than the other backends on 32 bit targets, where 128 bit arithmetic is emulated.
+use std::ops::{AddAssign, MulAssign, SubAssign};
+
+use subtle::{Choice, ConditionallySelectable};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use super::load8;
+use crate::util::wipe;
+
+/// N is the number of limbs in our representation.
+pub(super) const N: usize = 10;
+
+/// The bit offset of each limb, i.e. ⌈25.5⋅i⌉.
+pub(super) const OFFSETS: [usize; N] = [0, 26, 51, 77, 102, 128, 153, 179, 204, 230];
+
+/// The number of bits in a given limb: even limbs have 26 bits, and odd limbs 25.
+#[inline(always)]
+pub(super) const fn limb_bits(i: usize) -> u32 {
+    26 - (i as u32 & 1)
+}
+
+/// A mask for the bits of a given limb.
+#[inline(always)]
+pub(super) const fn limb_mask(i: usize) -> u64 {
+    (1 << limb_bits(i)) - 1
+}
+
+/// Every operation leaves limbs below their nominal size plus this slack, which
+/// the others rely on.
+///
+/// Only the second limb can exceed its nominal size after carrying, and only
+/// by a little, as `carry` explains.
+const LIMB_SLACK: u64 = 1 << 18;
+
+/// Split the low 255 bits of an encoding into limbs.
+#[inline(always)]
+pub(super) const fn bytes_to_limbs(bytes: &[u8; 32]) -> [u64; N] {
+    let mut limbs = [0u64; N];
+    let mut i = 0;
+    while i < N {
+        // We read the (up to) 8 bytes containing the start of this limb, which
+        // always leaves us with enough bits after shifting.
+        let word = load8(bytes, OFFSETS[i] / 8) >> (OFFSETS[i] % 8);
+        limbs[i] = word & limb_mask(i);
+        i += 1;
+    }
+    limbs
+}
+
+/// Pack limbs back into a 32 byte encoding, which is only canonical if they're reduced.
+///
+/// The limbs can be a little larger than their nominal size, as long as their value
+/// is < 2²⁵⁶.
+#[inline(always)]
+pub(super) fn limbs_to_bytes(limbs: &[u64; N]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    // We add the limbs into a bit buffer, rather than or-ing them in, since they might
+    // overlap, and then flush out every full byte.
+    let mut acc = 0u64;
+    let mut acc_bits = 0;
+    let mut j = 0;
+    for (i, &limb) in limbs.iter().enumerate() {
+        acc += limb << acc_bits;
+        acc_bits += limb_bits(i);
+        while acc_bits >= 8 {
+            out[j] = acc as u8;
+            acc >>= 8;
+            acc_bits -= 8;
+            j += 1;
+        }
+    }
+    // Whatever's left over fits in the last byte.
+    out[31] = acc as u8;
+    out
+}
+
+/// An element of the field of integers mod P = 2²⁵⁵ - 19.
+///
+/// Elements are stored as Σ limbᵢ⋅2^⌈25.5⋅i⌉, with each limb holding a little more
+/// than its 26 or 25 bits, so their representation isn't unique. To compare elements, use
+/// [`ConstantTimeEq`](subtle::ConstantTimeEq), which compares canonical representatives.
+#[derive(Clone, Copy)]
+pub struct Fp {
+    limbs: [u32; N],
+}
+
+/// Multiply a 64 bit word by 19, as x + 2⋅x + 16⋅x.
+///
+/// This takes functions to add words, and to shift them left by a constant, so that the
+/// vectorized backends can use it too, since their multiplications only take 32 bit
+/// inputs.
+macro_rules! mul19 {
+    ($x:expr, $add:ident, $shl:ident) => {{
+        let x = $x;
+        $add($add(x, $shl::<1>(x)), $shl::<4>(x))
+    }};
+}
+pub(super) use mul19;
+
+/// Carry wide limbs, held in 64 bit words, back into limbs, wrapping around with a
+/// factor of 19.
+///
+/// Each word must be < 2⁶², and then only the second limb of the output can exceed
+/// its nominal size, and only by a little.
+///
+/// The vectorized backends hold the same limb of several elements in one vector, and
+/// carry them all at once with this, so it takes the operations on words as functions:
+/// adding two words, shifting out the bits of limb i, keeping the bits of limb i, and
+/// shifting left by a constant. This only uses `while` loops, so that it works in a
+/// const fn.
+macro_rules! carry_limbs {
+    ($z:ident, $add:ident, $shift_limb:ident, $mask_limb:ident, $shl:ident) => {{
+        let mut i = 0;
+        while i < N - 1 {
+            $z[i + 1] = $add($z[i + 1], $shift_limb($z[i], i));
+            $z[i] = $mask_limb($z[i], i);
+            i += 1;
+        }
+        // Since 2²⁵⁵ ≡ 19 mod P, the carry out of the top limb wraps around.
+        let c = $shift_limb($z[9], 9);
+        $z[9] = $mask_limb($z[9], 9);
+        $z[0] = $add($z[0], super::radix25::mul19!(c, $add, $shl));
+        $z[1] = $add($z[1], $shift_limb($z[0], 0));
+        $z[0] = $mask_limb($z[0], 0);
+    }};
+}
+// Only the vectorized backends use this from outside, and they aren't always compiled.
+#[allow(unused_imports)]
+pub(super) use carry_limbs;
+
+#[inline(always)]
+const fn add(a: u64, b: u64) -> u64 {
+    a + b
+}
+
+#[inline(always)]
+const fn shift_limb(x: u64, i: usize) -> u64 {
+    x >> limb_bits(i)
+}
+
+#[inline(always)]
+const fn mask_limb(x: u64, i: usize) -> u64 {
+    x & limb_mask(i)
+}
+
+#[inline(always)]
+const fn shl<const K: i32>(x: u64) -> u64 {
+    x << K
+}
+
+/// Carry a wide result back into limbs, wrapping around with a factor of 19.
+///
+/// Each input must be < 2⁶², and then only the second limb of the output can exceed
+/// its nominal size, and only by a little.
+#[inline(always)]
+const fn carry(mut z: [u64; N]) -> [u32; N] {
+    carry_limbs!(z, add, shift_limb, mask_limb, shl);
+    let mut out = [0u32; N];
+    let mut i = 0;
+    while i < N {
+        out[i] = z[i] as u32;
+        i += 1;
+    }
+    out
+}
+
+/// Carry the high bits of each limb into the next one.
+#[inline(always)]
+const fn weak_reduce(limbs: [u32; N]) -> [u32; N] {
+    let mut z = [0u64; N];
+    let mut i = 0;
+    while i < N {
+        z[i] = limbs[i] as u64;
+        i += 1;
+    }
+    carry(z)
+}
+
+/// Given weakly reduced limbs, return 1 if their value is ≥ P, and 0 otherwise.
+///
+/// The value must be < 2P, which is the case for weakly reduced limbs.
+#[inline(always)]
+fn ge_p(limbs: &[u32; N]) -> u32 {
+    // We have x ≥ P exactly when x + 19 ≥ 2²⁵⁵, so we calculate that bit of
+    // x + 19, by propagating the carries.
+    let mut q = (limbs[0] + 19) >> 26;
+    for (i, limb) in limbs.iter().enumerate().skip(1) {
+        q = (limb + q) >> limb_bits(i);
+    }
+    q
+}
+
+impl Fp {
+    /// The additive identity.
+    pub const ZERO: Self = Self::from_limbs([0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    /// The multiplicative identity.
+    pub const ONE: Self = Self::from_limbs([1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+    /// Create an element directly from its limbs, in little endian order.
+    ///
+    /// This is intended for defining constants.
+    pub(crate) const fn from_limbs(limbs: [u32; N]) -> Self {
+        Self { limbs }
+    }
+
+    /// Reduce this element to its canonical representative, in the range [0, P), in place.
+    ///
+    /// This runs in constant time.
+    pub(super) fn reduce_assign(&mut self) {
+        let mut limbs = weak_reduce(self.limbs);
+        // We now have a value < 2P, so we need to subtract P at most once.
+        let q = ge_p(&limbs);
+        // self - q⋅P = self + 19⋅q - q⋅2²⁵⁵, so we add 19⋅q, and then drop the top bit.
+        limbs[0] += 19 * q;
+        for i in 0..N - 1 {
+            limbs[i + 1] += limbs[i] >> limb_bits(i);
+            limbs[i] &= limb_mask(i) as u32;
+        }
+        limbs[9] &= limb_mask(9) as u32;
+        self.limbs = limbs;
+        debug_assert!(self.is_canonical());
+    }
+
+    /// Check that every limb is within [`LIMB_SLACK`] of its nominal size, in variable time.
+    ///
+    /// This is only intended for debug assertions.
+    fn is_weakly_reduced(&self) -> bool {
+        (0..N).all(|i| u64::from(self.limbs[i]) <= limb_mask(i) + LIMB_SLACK)
+    }
+
+    /// Check whether this element is fully reduced, in variable time.
+    ///
+    /// This is only intended for debug assertions.
+    fn is_canonical(&self) -> bool {
+        (0..N).all(|i| u64::from(self.limbs[i]) <= limb_mask(i)) && ge_p(&self.limbs) == 0
+    }
+
+    /// Create an element from its 32 byte little endian encoding.
+    ///
+    /// All 256 bits are used, and the value is implicitly reduced mod P. This means
+    /// that non-canonical encodings are accepted: see [`Fp::from_canonical_bytes`]
+    /// for a stricter variant.
+    pub const fn from_bytes(bytes: &[u8; 32]) -> Self {
+        let wide = bytes_to_limbs(bytes);
+        let mut limbs = [0u32; N];
+        let mut i = 0;
+        while i < N {
+            limbs[i] = wide[i] as u32;
+            i += 1;
+        }
+        // The top bit is worth 2²⁵⁵ ≡ 19 mod P.
+        limbs[0] += 19 * (bytes[31] >> 7) as u32;
+        Self::from_limbs(limbs)
+    }
+
+    /// Add two elements together, in a const context.
+    ///
+    /// This is intended for defining constants: at runtime, use `+` instead.
+    pub const fn const_add(&self, other: &Self) -> Self {
+        let mut limbs = self.limbs;
+        let mut i = 0;
+        while i < N {
+            limbs[i] += other.limbs[i];
+            i += 1;
+        }
+        Self::from_limbs(weak_reduce(limbs))
+    }
+
+    /// Convert this element to its 32 byte little endian encoding, without reducing it.
+    ///
+    /// This element must already be reduced, for the encoding to be canonical, which
+    /// is why this is only called through [`ReducedFp`](super::ReducedFp).
+    pub(super) fn pack(&self) -> [u8; 32] {
+        limbs_to_bytes(&self.limbs.map(u64::from))
+    }
+
+    /// Multiply this element by a small constant.
+    ///
+    /// This is much cheaper than a full multiplication, since we only need
+    /// one 32 × 32 bit product per limb.
+    pub fn mul_small(&self, k: u32) -> Self {
+        debug_assert!(self.is_weakly_reduced());
+        let mut z = [0u64; N];
+        for (zi, &a) in z.iter_mut().zip(self.limbs.iter()) {
+            // Limbs have at most 27 bits, so this is < 2⁵⁹.
+            *zi = u64::from(a) * u64::from(k);
+        }
+        let out = Self::from_limbs(carry(z));
+        wipe(&mut z);
+        debug_assert!(out.is_weakly_reduced());
+        out
+    }
+
+    /// The largest k that [`Fp::shl_assign`] accepts.
+    pub(super) const MAX_SHIFT: u32 = 32;
+
+    /// Multiply this element by 2ᵏ, in place, for 1 ≤ k ≤ [`Fp::MAX_SHIFT`].
+    pub(super) fn shl_assign(&mut self, k: u32) {
+        debug_assert!((1..=Self::MAX_SHIFT).contains(&k));
+        debug_assert!(self.is_weakly_reduced());
+        let mut z = [0u64; N];
+        for (zi, &a) in z.iter_mut().zip(self.limbs.iter()) {
+            // Limbs have at most 27 bits, so this is < 2⁵⁹.
+            *zi = u64::from(a) << k;
+        }
+        self.limbs = carry(z);
+        wipe(&mut z);
+        debug_assert!(self.is_weakly_reduced());
+    }
+
+    /// Square this element, in place.
+    ///
+    /// This is cheaper than a general multiplication, since we only need to
+    /// calculate half of the cross products.
+    pub(super) fn square_assign(&mut self) {
+        debug_assert!(self.is_weakly_reduced());
+        let a = &self.limbs;
+        let mut z = [0u64; N];
+        for i in 0..N {
+            for j in i..N {
+                let mut p = u64::from(a[i]) * u64::from(a[j]);
+                // Cross products appear twice.
+                if i != j {
+                    p *= 2;
+                }
+                // See the comments in `mul_assign` for these two factors.
+                if i & j & 1 == 1 {
+                    p *= 2;
+                }
+                if i + j >= N {
+                    p *= 19;
+                }
+                z[(i + j) % N] += p;
+            }
+        }
+        self.limbs = carry(z);
+        wipe(&mut z);
+        debug_assert!(self.is_weakly_reduced());
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for Fp {
+    fn zeroize(&mut self) {
+        self.limbs.zeroize();
+    }
+}
+
+impl ConditionallySelectable for Fp {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut out = Self::ZERO;
+        for i in 0..N {
+            out.limbs[i] = u32::conditional_select(&a.limbs[i], &b.limbs[i], choice);
+        }
+        out
+    }
+}
+
+impl AddAssign<&Fp> for Fp {
+    fn add_assign(&mut self, other: &Fp) {
+        debug_assert!(self.is_weakly_reduced() && other.is_weakly_reduced());
+        for (x, y) in self.limbs.iter_mut().zip(other.limbs.iter()) {
+            *x += y;
+        }
+        self.limbs = weak_reduce(self.limbs);
+        debug_assert!(self.is_weakly_reduced());
+    }
+}
+
+impl SubAssign<&Fp> for Fp {
+    fn sub_assign(&mut self, other: &Fp) {
+        debug_assert!(self.is_weakly_reduced() && other.is_weakly_reduced());
+        // To avoid underflow, we add 16P first, which is larger than any limb of other.
+        let mut sixteen_p = [0u32; N];
+        for (i, x) in sixteen_p.iter_mut().enumerate() {
+            *x = 16 * limb_mask(i) as u32;
+        }
+        sixteen_p[0] -= 16 * 18;
+        for ((x, y), p16) in self.limbs.iter_mut().zip(other.limbs.iter()).zip(sixteen_p) {
+            *x = (*x + p16) - y;
+        }
+        self.limbs = weak_reduce(self.limbs);
+        debug_assert!(self.is_weakly_reduced());
+    }
+}
+
+impl MulAssign<&Fp> for Fp {
+    fn mul_assign(&mut self, other: &Fp) {
+        debug_assert!(self.is_weakly_reduced() && other.is_weakly_reduced());
+        let a = &self.limbs;
+        let b = &other.limbs;
+        let mut z = [0u64; N];
+        // Limbs exceed their nominal size by very little, so every product, including
+        // the extra factors below, is < 2⁵²⋅19 < 2⁵⁷, and a column of 10 fits easily.
+        for i in 0..N {
+            for j in 0..N {
+                let mut p = u64::from(a[i]) * u64::from(b[j]);
+                // When both limbs are odd, their offsets are each half a bit short,
+                // so the product lands one bit above the offset of limb i + j.
+                if i & j & 1 == 1 {
+                    p *= 2;
+                }
+                // Since 2²⁵⁵ ≡ 19 mod P, products which land past the top limb wrap around.
+                if i + j >= N {
+                    p *= 19;
+                }
+                z[(i + j) % N] += p;
+            }
+        }
+        self.limbs = carry(z);
+        wipe(&mut z);
+        debug_assert!(self.is_weakly_reduced());
+    }
+}
@@ -0,0 +1,329 @@
+//! A backend storing elements as five 51 bit limbs, with lazy carries.
+//!
+//! The extra headroom in each limb means that products can be accumulated
+//! without any carry handling, which is faster than the saturated backend on
+//! targets without dedicated carry chain instructions, like ADX and MULX.
+use std::ops::{AddAssign, MulAssign, SubAssign};
+
+use subtle::{Choice, ConditionallySelectable};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use super::load8;
+use crate::util::wipe;
+
+/// N is the number of limbs in our representation.
+const N: usize = 5;
+
+/// A mask for the low 51 bits of a limb.
+const LOW_51_BITS: u64 = (1 << 51) - 1;
+
+/// Every operation leaves limbs below this bound, which the others rely on.
+///
+/// This is what `weak_reduce` guarantees, and a little more than what the carries
+/// after a multiplication do.
+const LIMB_BOUND: u64 = (1 << 51) + (1 << 18);
+
+/// An element of the field of integers mod P = 2²⁵⁵ - 19.
+///
+/// Elements are stored as Σ limbᵢ⋅2⁵¹ⁱ, with each limb holding a little more than
+/// 51 bits, so their representation isn't unique. To compare elements, use
+/// [`ConstantTimeEq`](subtle::ConstantTimeEq), which compares canonical representatives.
+#[derive(Clone, Copy)]
+pub struct Fp {
+    limbs: [u64; N],
+}
+
+/// Carry the high bits of each limb into the next one, wrapping around with a factor of 19.
+///
+/// Afterwards, each limb is < 2⁵¹ + 2¹⁸, as long as the inputs were < 2⁶⁴.
+#[inline(always)]
+const fn weak_reduce(mut limbs: [u64; N]) -> [u64; N] {
+    let c0 = limbs[0] >> 51;
+    let c1 = limbs[1] >> 51;
+    let c2 = limbs[2] >> 51;
+    let c3 = limbs[3] >> 51;
+    let c4 = limbs[4] >> 51;
+    limbs[0] &= LOW_51_BITS;
+    limbs[1] &= LOW_51_BITS;
+    limbs[2] &= LOW_51_BITS;
+    limbs[3] &= LOW_51_BITS;
+    limbs[4] &= LOW_51_BITS;
+    // Since 2²⁵⁵ ≡ 19 mod P, the carry out of the top limb wraps around.
+    limbs[0] += c4 * 19;
+    limbs[1] += c0;
+    limbs[2] += c1;
+    limbs[3] += c2;
+    limbs[4] += c3;
+    limbs
+}
+
+/// Given the five 128 bit column sums of a product, carry them back into 51 bit limbs.
+#[inline(always)]
+fn carry_wide(mut c: [u128; N]) -> [u64; N] {
+    let mut out = [0u64; N];
+    for i in 0..4 {
+        c[i + 1] += c[i] >> 51;
+        out[i] = (c[i] as u64) & LOW_51_BITS;
+    }
+    out[4] = (c[4] as u64) & LOW_51_BITS;
+    // The columns are < 2¹¹², so the final carry is < 2⁶¹ / 19, and this fits in 64 bits.
+    out[0] += ((c[4] >> 51) as u64) * 19;
+    // This may leave the first limb with more than 51 bits, which we fix with one more carry.
+    out[1] += out[0] >> 51;
+    out[0] &= LOW_51_BITS;
+    out
+}
+
+/// Given weakly reduced limbs, return 1 if their value is ≥ P, and 0 otherwise.
+///
+/// The value must be < 2P, which is the case for weakly reduced limbs.
+#[inline(always)]
+fn ge_p(limbs: &[u64; N]) -> u64 {
+    // We have x ≥ P exactly when x + 19 ≥ 2²⁵⁵, so we calculate that bit of
+    // x + 19, by propagating the carries.
+    let mut q = (limbs[0] + 19) >> 51;
+    for limb in &limbs[1..] {
+        q = (limb + q) >> 51;
+    }
+    q
+}
+
+/// Multiply two u64 values into a u128.
+#[inline(always)]
+fn m(a: u64, b: u64) -> u128 {
+    u128::from(a) * u128::from(b)
+}
+
+impl Fp {
+    /// The additive identity.
+    pub const ZERO: Self = Self::from_limbs([0, 0, 0, 0, 0]);
+    /// The multiplicative identity.
+    pub const ONE: Self = Self::from_limbs([1, 0, 0, 0, 0]);
+
+    /// Create an element directly from its 51 bit limbs, in little endian order.
+    ///
+    /// This is intended for defining constants.
+    pub(crate) const fn from_limbs(limbs: [u64; N]) -> Self {
+        Self { limbs }
+    }
+
+    /// Reduce this element to its canonical representative, in the range [0, P), in place.
+    ///
+    /// This runs in constant time.
+    pub(super) fn reduce_assign(&mut self) {
+        let mut limbs = weak_reduce(self.limbs);
+        // We now have a value < 2P, so we need to subtract P at most once.
+        let q = ge_p(&limbs);
+        // self - q⋅P = self + 19⋅q - q⋅2²⁵⁵, so we add 19⋅q, and then drop the top bit.
+        limbs[0] += 19 * q;
+        for i in 0..4 {
+            limbs[i + 1] += limbs[i] >> 51;
+            limbs[i] &= LOW_51_BITS;
+        }
+        limbs[4] &= LOW_51_BITS;
+        self.limbs = limbs;
+        debug_assert!(self.is_canonical());
+    }
+
+    /// Check that every limb is below [`LIMB_BOUND`], in variable time.
+    ///
+    /// This is only intended for debug assertions.
+    fn is_weakly_reduced(&self) -> bool {
+        self.limbs.iter().all(|&limb| limb < LIMB_BOUND)
+    }
+
+    /// Check whether this element is fully reduced, in variable time.
+    ///
+    /// This is only intended for debug assertions.
+    fn is_canonical(&self) -> bool {
+        self.limbs.iter().all(|&limb| limb <= LOW_51_BITS) && ge_p(&self.limbs) == 0
+    }
+
+    /// Create an element from its 32 byte little endian encoding.
+    ///
+    /// All 256 bits are used, and the value is implicitly reduced mod P. This means
+    /// that non-canonical encodings are accepted: see [`Fp::from_canonical_bytes`]
+    /// for a stricter variant.
+    pub const fn from_bytes(bytes: &[u8; 32]) -> Self {
+        // Each limb starts at bit 51⋅i, which we read from the byte containing that bit.
+        let mut limbs = [
+            load8(bytes, 0) & LOW_51_BITS,
+            (load8(bytes, 6) >> 3) & LOW_51_BITS,
+            (load8(bytes, 12) >> 6) & LOW_51_BITS,
+            (load8(bytes, 19) >> 1) & LOW_51_BITS,
+            (load8(bytes, 24) >> 12) & LOW_51_BITS,
+        ];
+        // The top bit is worth 2²⁵⁵ ≡ 19 mod P.
+        limbs[0] += 19 * (bytes[31] >> 7) as u64;
+        Self::from_limbs(limbs)
+    }
+
+    /// Add two elements together, in a const context.
+    ///
+    /// This is intended for defining constants: at runtime, use `+` instead.
+    pub const fn const_add(&self, other: &Self) -> Self {
+        let mut limbs = self.limbs;
+        let mut i = 0;
+        while i < N {
+            limbs[i] += other.limbs[i];
+            i += 1;
+        }
+        Self::from_limbs(weak_reduce(limbs))
+    }
+
+    /// Convert this element to its 32 byte little endian encoding, without reducing it.
+    ///
+    /// This element must already be reduced, for the encoding to be canonical, which
+    /// is why this is only called through [`ReducedFp`](super::ReducedFp).
+    pub(super) fn pack(&self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        // We pack the limbs into a bit buffer, and then flush out every full byte.
+        let mut acc = 0u128;
+        let mut acc_bits = 0;
+        let mut j = 0;
+        for limb in self.limbs {
+            acc |= u128::from(limb) << acc_bits;
+            acc_bits += 51;
+            while acc_bits >= 8 {
+                out[j] = acc as u8;
+                acc >>= 8;
+                acc_bits -= 8;
+                j += 1;
+            }
+        }
+        // 255 = 31⋅8 + 7, so there are 7 bits left over for the last byte.
+        out[31] = acc as u8;
+        out
+    }
+
+    /// Multiply this element by a small constant.
+    ///
+    /// This is much cheaper than a full multiplication, since we only need
+    /// one 64 × 64 bit product per limb.
+    pub fn mul_small(&self, k: u32) -> Self {
+        debug_assert!(self.is_weakly_reduced());
+        let k = u64::from(k);
+        let mut c = [0u128; N];
+        for (ci, &a) in c.iter_mut().zip(self.limbs.iter()) {
+            *ci = m(a, k);
+        }
+        let out = Self::from_limbs(carry_wide(c));
+        wipe(&mut c);
+        debug_assert!(out.is_weakly_reduced());
+        out
+    }
+
+    /// The largest k that [`Fp::shl_assign`] accepts.
+    pub(super) const MAX_SHIFT: u32 = 12;
+
+    /// Multiply this element by 2ᵏ, in place, for 1 ≤ k ≤ [`Fp::MAX_SHIFT`].
+    pub(super) fn shl_assign(&mut self, k: u32) {
+        debug_assert!((1..=Self::MAX_SHIFT).contains(&k));
+        debug_assert!(self.is_weakly_reduced());
+        // Limbs are < 2⁵², so shifting them by 12 bits still fits in 64 bits.
+        for limb in self.limbs.iter_mut() {
+            *limb <<= k;
+        }
+        self.limbs = weak_reduce(self.limbs);
+        debug_assert!(self.is_weakly_reduced());
+    }
+
+    /// Square this element, in place.
+    ///
+    /// This is cheaper than a general multiplication, since we only need to
+    /// calculate half of the cross products.
+    pub(super) fn square_assign(&mut self) {
+        debug_assert!(self.is_weakly_reduced());
+        let a = &self.limbs;
+        let a3_19 = 19 * a[3];
+        let a4_19 = 19 * a[4];
+        // Each column collects the products aᵢ⋅aⱼ with i + j ≡ k mod 5, with a factor
+        // of 19 whenever i + j ≥ 5, since 2²⁵⁵ ≡ 19 mod P.
+        let mut c = [
+            m(a[0], a[0]) + 2 * (m(a[1], a4_19) + m(a[2], a3_19)),
+            m(a[3], a3_19) + 2 * (m(a[0], a[1]) + m(a[2], a4_19)),
+            m(a[1], a[1]) + 2 * (m(a[0], a[2]) + m(a[4], a3_19)),
+            m(a[4], a4_19) + 2 * (m(a[0], a[3]) + m(a[1], a[2])),
+            m(a[2], a[2]) + 2 * (m(a[0], a[4]) + m(a[1], a[3])),
+        ];
+        self.limbs = carry_wide(c);
+        wipe(&mut c);
+        debug_assert!(self.is_weakly_reduced());
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for Fp {
+    fn zeroize(&mut self) {
+        self.limbs.zeroize();
+    }
+}
+
+impl ConditionallySelectable for Fp {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut out = Self::ZERO;
+        for i in 0..N {
+            out.limbs[i] = u64::conditional_select(&a.limbs[i], &b.limbs[i], choice);
+        }
+        out
+    }
+}
+
+impl AddAssign<&Fp> for Fp {
+    fn add_assign(&mut self, other: &Fp) {
+        debug_assert!(self.is_weakly_reduced() && other.is_weakly_reduced());
+        for (x, y) in self.limbs.iter_mut().zip(other.limbs.iter()) {
+            *x += y;
+        }
+        // Carrying here is cheap, and keeps the limbs small enough for long chains of
+        // additions to never overflow.
+        self.limbs = weak_reduce(self.limbs);
+        debug_assert!(self.is_weakly_reduced());
+    }
+}
+
+impl SubAssign<&Fp> for Fp {
+    fn sub_assign(&mut self, other: &Fp) {
+        debug_assert!(self.is_weakly_reduced() && other.is_weakly_reduced());
+        // To avoid underflow, we add 16P first, which is larger than any limb of other.
+        const SIXTEEN_P: [u64; N] = [
+            16 * (LOW_51_BITS - 18),
+            16 * LOW_51_BITS,
+            16 * LOW_51_BITS,
+            16 * LOW_51_BITS,
+            16 * LOW_51_BITS,
+        ];
+        for ((x, y), p16) in self.limbs.iter_mut().zip(other.limbs.iter()).zip(SIXTEEN_P) {
+            *x = (*x + p16) - y;
+        }
+        self.limbs = weak_reduce(self.limbs);
+        debug_assert!(self.is_weakly_reduced());
+    }
+}
+
+impl MulAssign<&Fp> for Fp {
+    fn mul_assign(&mut self, other: &Fp) {
+        debug_assert!(self.is_weakly_reduced() && other.is_weakly_reduced());
+        let a = &self.limbs;
+        let b = &other.limbs;
+        // Since 2²⁵⁵ ≡ 19 mod P, products which land past the top limb wrap around
+        // with a factor of 19. Limbs are < 2⁵², so these fit comfortably in 64 bits.
+        let b1_19 = 19 * b[1];
+        let b2_19 = 19 * b[2];
+        let b3_19 = 19 * b[3];
+        let b4_19 = 19 * b[4];
+        // Each column is a sum of 5 products of at most 2⁵² ⋅ 2⁵⁷, which is < 2¹¹²,
+        // so we can accumulate everything before doing any carries.
+        let mut c = [
+            m(a[0], b[0]) + m(a[4], b1_19) + m(a[3], b2_19) + m(a[2], b3_19) + m(a[1], b4_19),
+            m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2_19) + m(a[3], b3_19) + m(a[2], b4_19),
+            m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3_19) + m(a[3], b4_19),
+            m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4_19),
+            m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]),
+        ];
+        self.limbs = carry_wide(c);
+        wipe(&mut c);
+        debug_assert!(self.is_weakly_reduced());
+    }
+}
@@ -0,0 +1,47 @@
+//! Straightforward implementations of the field, using generic prime field arithmetic.
+//!
+//! These are much slower than [`Fp`](super::Fp), since they don't take advantage of the
+//! special form of P, but the code is simple enough to serve as a reference to check
+//! the optimized backends against.
+
+define_prime_field! {
+    /// An element of the field of integers mod P = 2²⁵⁵ - 19, using generic arithmetic.
+    ///
+    /// Elements are always fully reduced, so their representation is unique.
+    pub struct Fp([u64; 4]);
+    modulus = [
+        0xFFFFFFFFFFFFFFED,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0x7FFFFFFFFFFFFFFF,
+    ];
+    reduction = bitwise;
+}
+
+define_prime_field! {
+    /// An element of the field of integers mod P = 2²⁵⁵ - 19, stored in Montgomery form.
+    ///
+    /// Elements are always fully reduced, so their representation is unique.
+    pub struct MontgomeryFp([u64; 4]);
+    modulus = [
+        0xFFFFFFFFFFFFFFED,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0x7FFFFFFFFFFFFFFF,
+    ];
+    reduction = montgomery;
+}
+
+define_prime_field! {
+    /// An element of the field of integers mod P = 2²⁵⁵ - 19, using Barrett reduction.
+    ///
+    /// Elements are always fully reduced, so their representation is unique.
+    pub struct BarrettFp([u64; 4]);
+    modulus = [
+        0xFFFFFFFFFFFFFFED,
+        0xFFFFFFFFFFFFFFFF,
+        0xFFFFFFFFFFFFFFFF,
+        0x7FFFFFFFFFFFFFFF,
+    ];
+    reduction = barrett;
+}
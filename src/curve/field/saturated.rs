@@ -0,0 +1,495 @@
+//! The default backend, storing elements as four saturated 64 bit limbs.
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64 as arch;
+#[cfg(target_arch = "aarch64")]
+use std::arch::asm;
+use std::ops::{AddAssign, MulAssign, SubAssign};
+
+use subtle::{Choice, ConditionallySelectable};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use super::{adc, load8, sbb, P};
+use crate::arithmetic::sub;
+use crate::util::wipe;
+
+/// N is the number of limbs in our representation.
+const N: usize = 4;
+
+/// An element of the field of integers mod P = 2²⁵⁵ - 19.
+///
+/// Elements are stored as integers < 2²⁵⁶, so their representation isn't unique.
+/// To compare elements, use [`ConstantTimeEq`](subtle::ConstantTimeEq), which compares
+/// canonical representatives.
+#[derive(Clone, Copy)]
+pub struct Fp {
+    limbs: [u64; N],
+}
+
+impl Fp {
+    /// The additive identity.
+    pub const ZERO: Self = Self::from_limbs([0, 0, 0, 0]);
+    /// The multiplicative identity.
+    pub const ONE: Self = Self::from_limbs([1, 0, 0, 0]);
+
+    /// Create an element directly from its limbs, in little endian order.
+    ///
+    /// This is intended for defining constants.
+    pub(crate) const fn from_limbs(limbs: [u64; N]) -> Self {
+        Self { limbs }
+    }
+
+    /// Given self + carry⋅2²⁵⁶, fold the carry back in, so that self fits in 4 limbs again.
+    ///
+    /// `carry` must be < 2⁵⁸, so that 38⋅carry fits in a single limb. This is the
+    /// case after additions, multiplications, and multiplications by small constants.
+    #[inline]
+    fn reduce_after_scaling(&mut self, carry: u64) {
+        debug_assert!(carry < 1 << 58);
+        // Since 2²⁵⁶ ≡ 38 mod P, we just need to add in 38⋅carry.
+        let mut c = adc(0, self.limbs[0], 38 * carry, &mut self.limbs[0]);
+        for i in 1..4 {
+            c = adc(c, self.limbs[i], 0, &mut self.limbs[i]);
+        }
+        // We can carry a second time, but only if the result overflowed. In that case,
+        // the result is now smaller than 38⋅carry, so adding 38 into the lowest limb
+        // can't carry again.
+        self.limbs[0] += 38 * u64::from(c);
+    }
+
+    /// Reduce this element to its canonical representative, in the range [0, P), in place.
+    ///
+    /// This runs in constant time.
+    pub(super) fn reduce_assign(&mut self) {
+        // First, fold the top bit back in, using 2²⁵⁵ ≡ 19 mod P. This leaves us with
+        // a value < 2²⁵⁵ + 19 < 2P.
+        let top = self.limbs[3] >> 63;
+        self.limbs[3] &= (1 << 63) - 1;
+        let mut carry = adc(0, self.limbs[0], 19 * top, &mut self.limbs[0]);
+        for i in 1..4 {
+            carry = adc(carry, self.limbs[i], 0, &mut self.limbs[i]);
+        }
+        // Now, we need to subtract P at most once. We have self ≥ P exactly when
+        // self + 19 ≥ 2²⁵⁵, in which case self - P = (self + 19) mod 2²⁵⁵.
+        let mut plus_19 = [0u64; N];
+        carry = adc(0, self.limbs[0], 19, &mut plus_19[0]);
+        for (i, out) in plus_19.iter_mut().enumerate().skip(1) {
+            carry = adc(carry, self.limbs[i], 0, out);
+        }
+        let overflow = Choice::from((plus_19[3] >> 63) as u8);
+        plus_19[3] &= (1 << 63) - 1;
+        for (limb, x) in self.limbs.iter_mut().zip(plus_19.iter()) {
+            *limb = u64::conditional_select(limb, x, overflow);
+        }
+        debug_assert!(self.is_canonical());
+    }
+
+    /// Check whether this element is fully reduced, in variable time.
+    ///
+    /// This is only intended for debug assertions.
+    fn is_canonical(&self) -> bool {
+        sub(&self.limbs, &P).1 == 1
+    }
+
+    /// Create an element from its 32 byte little endian encoding.
+    ///
+    /// All 256 bits are used, and the value is implicitly reduced mod P. This means
+    /// that non-canonical encodings are accepted: see [`Fp::from_canonical_bytes`]
+    /// for a stricter variant.
+    pub const fn from_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; N];
+        let mut i = 0;
+        while i < N {
+            limbs[i] = load8(bytes, 8 * i);
+            i += 1;
+        }
+        Self::from_limbs(limbs)
+    }
+
+    /// Add two elements together, in a const context.
+    ///
+    /// This is intended for defining constants: at runtime, use `+` instead,
+    /// which is faster.
+    pub const fn const_add(&self, other: &Self) -> Self {
+        let mut limbs = [0u64; N];
+        let mut carry = 0u128;
+        let mut i = 0;
+        while i < N {
+            let full_res = self.limbs[i] as u128 + other.limbs[i] as u128 + carry;
+            limbs[i] = full_res as u64;
+            carry = full_res >> 64;
+            i += 1;
+        }
+        // This folds the carry back in, like `reduce_after_scaling`.
+        carry *= 38;
+        i = 0;
+        while i < N {
+            let full_res = limbs[i] as u128 + carry;
+            limbs[i] = full_res as u64;
+            carry = full_res >> 64;
+            i += 1;
+        }
+        limbs[0] += 38 * carry as u64;
+        Self::from_limbs(limbs)
+    }
+
+    /// Convert this element to its 32 byte little endian encoding, without reducing it.
+    ///
+    /// This element must already be reduced, for the encoding to be canonical, which
+    /// is why this is only called through [`ReducedFp`](super::ReducedFp).
+    pub(super) fn pack(&self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (chunk, limb) in out.chunks_exact_mut(8).zip(self.limbs.iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        out
+    }
+
+    /// Given a full 512 bit result, reduce it back into an element.
+    #[inline(always)]
+    fn reduce_wide(wide: &[u64; 2 * N]) -> Self {
+        // Since 2²⁵⁶ ≡ 38 mod P, we have hi⋅2²⁵⁶ + lo ≡ lo + 38⋅hi
+        let mut out = Self::ZERO;
+        let mut carry = 0u64;
+        for i in 0..4 {
+            let full_res = u128::from(carry) + u128::from(wide[i]) + 38 * u128::from(wide[4 + i]);
+            out.limbs[i] = full_res as u64;
+            carry = (full_res >> 64) as u64;
+        }
+        out.reduce_after_scaling(carry);
+        out
+    }
+
+    /// Multiply this element by a small constant.
+    ///
+    /// This is much cheaper than a full multiplication, since we only need
+    /// one 64 × 64 bit product per limb.
+    pub fn mul_small(&self, k: u32) -> Self {
+        let mut out = Self::ZERO;
+        let mut carry = 0u64;
+        for i in 0..N {
+            // This can't overflow, since (2⁶⁴ - 1)⋅(2³² - 1) + 2³² < 2¹²⁸
+            let full_res = u128::from(self.limbs[i]) * u128::from(k) + u128::from(carry);
+            out.limbs[i] = full_res as u64;
+            carry = (full_res >> 64) as u64;
+        }
+        // The final carry is < 2³², so this is fine.
+        out.reduce_after_scaling(carry);
+        out
+    }
+
+    /// The largest k that [`Fp::shl_assign`] accepts.
+    pub(super) const MAX_SHIFT: u32 = 58;
+
+    /// Multiply this element by 2ᵏ, in place, for 1 ≤ k ≤ [`Fp::MAX_SHIFT`].
+    pub(super) fn shl_assign(&mut self, k: u32) {
+        debug_assert!((1..=Self::MAX_SHIFT).contains(&k));
+        // The bits shifted out of the top are < 2⁵⁸, as `reduce_after_scaling` needs.
+        let carry = self.limbs[3] >> (64 - k);
+        for i in (1..N).rev() {
+            self.limbs[i] = (self.limbs[i] << k) | (self.limbs[i - 1] >> (64 - k));
+        }
+        self.limbs[0] <<= k;
+        self.reduce_after_scaling(carry);
+    }
+
+    /// Square this element, in place.
+    ///
+    /// This is cheaper than a general multiplication, since we only need to
+    /// calculate half of the cross products.
+    pub(super) fn square_assign(&mut self) {
+        let a = &self.limbs;
+        let mut wide = [0u64; 2 * N];
+        // First, calculate the sum of aᵢ⋅aⱼ for i < j.
+        for i in 0..N {
+            let mut carry = 0u64;
+            for j in (i + 1)..N {
+                // This can't overflow, since (2⁶⁴ - 1)² + 2⋅(2⁶⁴ - 1) = 2¹²⁸ - 1
+                let full_res = u128::from(a[i]) * u128::from(a[j])
+                    + u128::from(wide[i + j])
+                    + u128::from(carry);
+                wide[i + j] = full_res as u64;
+                carry = (full_res >> 64) as u64;
+            }
+            wide[i + N] = carry;
+        }
+        // Then, double the cross products, which fits, because they're at most a² / 2.
+        for i in (1..2 * N).rev() {
+            wide[i] = (wide[i] << 1) | (wide[i - 1] >> 63);
+        }
+        wide[0] <<= 1;
+        // Finally, add in each aᵢ², which can't overflow, since the result is just a².
+        let mut carry = 0u8;
+        for i in 0..N {
+            let sq = u128::from(a[i]) * u128::from(a[i]);
+            carry = adc(carry, wide[2 * i], sq as u64, &mut wide[2 * i]);
+            carry = adc(
+                carry,
+                wide[2 * i + 1],
+                (sq >> 64) as u64,
+                &mut wide[2 * i + 1],
+            );
+        }
+        *self = Self::reduce_wide(&wide);
+        wipe(&mut wide);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for Fp {
+    fn zeroize(&mut self) {
+        self.limbs.zeroize();
+    }
+}
+
+impl ConditionallySelectable for Fp {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut out = Self::ZERO;
+        for i in 0..N {
+            out.limbs[i] = u64::conditional_select(&a.limbs[i], &b.limbs[i], choice);
+        }
+        out
+    }
+}
+
+impl AddAssign<&Fp> for Fp {
+    fn add_assign(&mut self, other: &Fp) {
+        let mut carry: u8 = 0;
+        // Let's have confidence in Rust's ability to unroll this loop.
+        for i in 0..4 {
+            // Each intermediate result may generate up to 65 bits of output.
+            // We need to daisy-chain the carries together, to get the right result.
+            carry = adc(carry, self.limbs[i], other.limbs[i], &mut self.limbs[i]);
+        }
+        self.reduce_after_scaling(u64::from(carry));
+    }
+}
+
+impl SubAssign<&Fp> for Fp {
+    fn sub_assign(&mut self, other: &Fp) {
+        let mut borrow: u8 = 0;
+        for i in 0..4 {
+            borrow = sbb(borrow, self.limbs[i], other.limbs[i], &mut self.limbs[i]);
+        }
+        // If we borrowed, then we've actually calculated self - other + 2²⁵⁶.
+        // Since 2²⁵⁶ ≡ 38 mod P, we need to subtract 38 to get the right result.
+        let mut fixup = 38 * u64::from(borrow);
+        borrow = sbb(0, self.limbs[0], fixup, &mut self.limbs[0]);
+        for i in 1..4 {
+            borrow = sbb(borrow, self.limbs[i], 0, &mut self.limbs[i]);
+        }
+        // We can borrow a second time, but only if the result was smaller than 38.
+        // In that case, we've wrapped around to a value ≥ 2²⁵⁶ - 38, so subtracting
+        // 38 from the lowest limb can't borrow again.
+        fixup = 38 * u64::from(borrow);
+        self.limbs[0] = self.limbs[0].wrapping_sub(fixup);
+    }
+}
+
+/// Calculate the full 512 bit product of a and b, using the BMI2 and ADX extensions.
+///
+/// This lets us use `mulx`, which doesn't touch the flags, along with the two independent
+/// carry chains provided by `adcx` and `adox`.
+///
+/// # Safety
+///
+/// The CPU running this function must support both BMI2 and ADX.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi2,adx")]
+pub(crate) unsafe fn mul_wide_bmi2_adx(a: &[u64; N], b: &[u64; N]) -> [u64; 2 * N] {
+    let mut out = [0u64; 2 * N];
+    let mut lo = [0u64; N];
+    let mut hi = [0u64; N];
+    // We add in a[i]⋅b to out, shifted by i limbs, one row at a time.
+    for i in 0..N {
+        for j in 0..N {
+            lo[j] = arch::_mulx_u64(a[i], b[j], &mut hi[j]);
+        }
+        // Add in the low halves with one carry chain, and then the high halves,
+        // shifted up by one limb, with another. The row sum is < 2³²⁰, so the
+        // second chain can't carry out.
+        let mut carry = 0;
+        for j in 0..N {
+            carry = arch::_addcarryx_u64(carry, out[i + j], lo[j], &mut out[i + j]);
+        }
+        out[i + N] = u64::from(carry);
+        carry = 0;
+        for j in 0..N {
+            carry = arch::_addcarryx_u64(carry, out[i + j + 1], hi[j], &mut out[i + j + 1]);
+        }
+    }
+    wipe(&mut lo);
+    wipe(&mut hi);
+    out
+}
+
+/// Check whether the CPU we're running on supports both BMI2 and ADX.
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+pub(crate) fn has_bmi2_adx() -> bool {
+    // We can skip the runtime check if these features are enabled at compile time.
+    cfg!(all(target_feature = "bmi2", target_feature = "adx"))
+        || (is_x86_feature_detected!("bmi2") && is_x86_feature_detected!("adx"))
+}
+
+/// Calculate the full 512 bit product of a and b, using inline assembly.
+///
+/// Writing each row out by hand lets us keep both of its carry chains in the flags.
+#[cfg(target_arch = "aarch64")]
+pub(crate) fn mul_wide_aarch64(a: &[u64; N], b: &[u64; N]) -> [u64; 2 * N] {
+    let mut out = [0u64; 2 * N];
+    // We add in a[i]⋅b to out, shifted by i limbs, one row at a time.
+    for i in 0..N {
+        let (mut r0, mut r1, mut r2, mut r3) = (out[i], out[i + 1], out[i + 2], out[i + 3]);
+        // This row hasn't touched out[i + 4] yet, so it doesn't need to be an input.
+        let r4: u64;
+        unsafe {
+            asm!(
+                // Calculate the low and high halves of each product aᵢ⋅bⱼ.
+                "mul {l0}, {a}, {b0}",
+                "umulh {h0}, {a}, {b0}",
+                "mul {l1}, {a}, {b1}",
+                "umulh {h1}, {a}, {b1}",
+                "mul {l2}, {a}, {b2}",
+                "umulh {h2}, {a}, {b2}",
+                "mul {l3}, {a}, {b3}",
+                "umulh {h3}, {a}, {b3}",
+                // Add in the low halves, with one carry chain.
+                "adds {r0}, {r0}, {l0}",
+                "adcs {r1}, {r1}, {l1}",
+                "adcs {r2}, {r2}, {l2}",
+                "adcs {r3}, {r3}, {l3}",
+                "adc {r4}, xzr, xzr",
+                // Then add in the high halves, shifted up by one limb, with another.
+                // The row sum is < 2³²⁰, so the final carry can't overflow.
+                "adds {r1}, {r1}, {h0}",
+                "adcs {r2}, {r2}, {h1}",
+                "adcs {r3}, {r3}, {h2}",
+                "adc {r4}, {r4}, {h3}",
+                a = in(reg) a[i],
+                b0 = in(reg) b[0],
+                b1 = in(reg) b[1],
+                b2 = in(reg) b[2],
+                b3 = in(reg) b[3],
+                l0 = out(reg) _,
+                l1 = out(reg) _,
+                l2 = out(reg) _,
+                l3 = out(reg) _,
+                h0 = out(reg) _,
+                h1 = out(reg) _,
+                h2 = out(reg) _,
+                h3 = out(reg) _,
+                r0 = inout(reg) r0,
+                r1 = inout(reg) r1,
+                r2 = inout(reg) r2,
+                r3 = inout(reg) r3,
+                r4 = out(reg) r4,
+                options(pure, nomem, nostack),
+            );
+        }
+        out[i..i + 5].copy_from_slice(&[r0, r1, r2, r3, r4]);
+    }
+    out
+}
+
+/// Calculate the full 512 bit product of a and b, without relying on any special instructions.
+///
+/// This is always compiled, even when it isn't used for multiplication, so that we
+/// can test the other methods against it.
+#[inline(always)]
+pub(crate) fn mul_wide_portable(a: &[u64; N], b: &[u64; N]) -> [u64; 2 * N] {
+    // You can treat both of these functions as macros. They just exist to avoid
+    // repeating this logic multiple times.
+
+    // This calculates u:v = a * b, and then adds u:v to r2:r1:r0
+    #[inline(always)]
+    fn multiply_in(a: u64, b: u64, r0: &mut u64, r1: &mut u64, r2: &mut u64) {
+        let uv = u128::from(a) * u128::from(b);
+        let mut carry = 0;
+        carry = adc(carry, uv as u64, *r0, r0);
+        carry = adc(carry, (uv >> 64) as u64, *r1, r1);
+        *r2 += u64::from(carry);
+    }
+
+    // Given r2:r1:r0, this sets limb = r0, and then shifts to get 0:r2:r1
+    #[inline(always)]
+    fn propagate(limb: &mut u64, r0: &mut u64, r1: &mut u64, r2: &mut u64) {
+        *limb = *r0;
+        *r0 = *r1;
+        *r1 = *r2;
+        *r2 = 0;
+    }
+
+    let mut out = [0u64; 2 * N];
+
+    // This is essentially a 192 bit number
+    let mut r0 = 0u64;
+    let mut r1 = 0u64;
+    let mut r2 = 0u64;
+
+    // This is an unrolling of big loop that looks like:
+    //    for k = 0..6
+    //      for i in 0..3, j in 0..3 with i + j = k:
+    //        multiply_in(a[i], b[j])
+    //      propagate(out[k])
+    //    propagate(out[7])
+    //
+    // The rough idea here is to add in all of the factors that contribute to a given
+    // limb of the output, adding in carries from the previous step, and then propagating
+    // a carry to the next step.
+
+    multiply_in(a[0], b[0], &mut r0, &mut r1, &mut r2);
+    propagate(&mut out[0], &mut r0, &mut r1, &mut r2);
+
+    multiply_in(a[0], b[1], &mut r0, &mut r1, &mut r2);
+    multiply_in(a[1], b[0], &mut r0, &mut r1, &mut r2);
+    propagate(&mut out[1], &mut r0, &mut r1, &mut r2);
+
+    multiply_in(a[0], b[2], &mut r0, &mut r1, &mut r2);
+    multiply_in(a[1], b[1], &mut r0, &mut r1, &mut r2);
+    multiply_in(a[2], b[0], &mut r0, &mut r1, &mut r2);
+    propagate(&mut out[2], &mut r0, &mut r1, &mut r2);
+
+    multiply_in(a[0], b[3], &mut r0, &mut r1, &mut r2);
+    multiply_in(a[1], b[2], &mut r0, &mut r1, &mut r2);
+    multiply_in(a[2], b[1], &mut r0, &mut r1, &mut r2);
+    multiply_in(a[3], b[0], &mut r0, &mut r1, &mut r2);
+    propagate(&mut out[3], &mut r0, &mut r1, &mut r2);
+
+    multiply_in(a[1], b[3], &mut r0, &mut r1, &mut r2);
+    multiply_in(a[2], b[2], &mut r0, &mut r1, &mut r2);
+    multiply_in(a[3], b[1], &mut r0, &mut r1, &mut r2);
+    propagate(&mut out[4], &mut r0, &mut r1, &mut r2);
+
+    multiply_in(a[2], b[3], &mut r0, &mut r1, &mut r2);
+    multiply_in(a[3], b[2], &mut r0, &mut r1, &mut r2);
+    propagate(&mut out[5], &mut r0, &mut r1, &mut r2);
+
+    multiply_in(a[3], b[3], &mut r0, &mut r1, &mut r2);
+    propagate(&mut out[6], &mut r0, &mut r1, &mut r2);
+
+    out[7] = r0;
+    out
+}
+
+/// Calculate the full 512 bit product of a and b, using the fastest method available.
+#[inline(always)]
+fn mul_wide(a: &[u64; N], b: &[u64; N]) -> [u64; 2 * N] {
+    #[cfg(target_arch = "x86_64")]
+    if has_bmi2_adx() {
+        // Safe, since we've checked that the CPU supports these features.
+        return unsafe { mul_wide_bmi2_adx(a, b) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    return mul_wide_aarch64(a, b);
+    #[cfg(not(target_arch = "aarch64"))]
+    mul_wide_portable(a, b)
+}
+
+impl MulAssign<&Fp> for Fp {
+    fn mul_assign(&mut self, other: &Fp) {
+        let mut wide = mul_wide(&self.limbs, &other.limbs);
+        *self = Self::reduce_wide(&wide);
+        wipe(&mut wide);
+    }
+}
@@ -1 +1,6 @@
+pub mod edwards;
 pub mod field;
+pub mod montgomery;
+pub mod ristretto;
+pub mod scalar;
+pub mod traits;
@@ -0,0 +1,346 @@
+//! Points on the Montgomery form of Curve25519, using only their u coordinate.
+//!
+//! The curve is given by v² = u³ + A⋅u² + u, with A = 486662, and is birationally
+//! equivalent to edwards25519. Scalar multiplication only needs the u coordinate, using
+//! the Montgomery ladder, which is what X25519, from RFC 7748, is built on.
+use std::{
+    fmt::{Debug, Display, LowerHex},
+    hash::{Hash, Hasher},
+    ops::Mul,
+};
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+use crate::{
+    curve::{
+        edwards::{EdwardsPoint, EIGHT_TORSION},
+        field::Fp,
+        scalar::Scalar,
+    },
+    util::{wipe, write_hex},
+};
+
+/// The constant A = 486662 in the Montgomery curve equation, v² = u³ + A⋅u² + u.
+pub(crate) const MONTGOMERY_A: Fp = Fp::from_bytes(&[
+    0x06, 0x6D, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+]);
+
+/// The non-negative square root of -(A + 2), which scales x in the
+/// birational map to edwards25519.
+pub(crate) const SQRT_NEG_A_PLUS_2: Fp = Fp::from_bytes(&[
+    0x06, 0x7E, 0x45, 0xFF, 0xAA, 0x04, 0x6E, 0xCC, 0x82, 0x1A, 0x7D, 0x4B, 0xD1, 0xD3, 0xA1, 0xC5,
+    0x7E, 0x4F, 0xFC, 0x03, 0xDC, 0x08, 0x7B, 0xD2, 0xBB, 0x06, 0xA0, 0x60, 0xF4, 0xED, 0x26, 0x0F,
+]);
+
+/// The constant (A - 2) / 4 = 121665, used when doubling.
+const A24: u32 = 121665;
+
+/// A point on Curve25519, represented by the 32 byte encoding of its u coordinate.
+///
+/// Both P and -P share the same u coordinate, so this only identifies a point up
+/// to sign. Any 32 bytes are a valid encoding, as in RFC 7748: the top bit is ignored,
+/// and values ≥ P are reduced. Equality takes this into account, comparing the
+/// decoded coordinates, rather than the bytes.
+#[derive(Clone, Copy)]
+pub struct MontgomeryPoint(pub [u8; 32]);
+
+/// The basepoint of X25519, with u = 9.
+///
+/// This is the image of [`ED25519_BASEPOINT_POINT`](crate::curve::edwards::ED25519_BASEPOINT_POINT).
+pub const X25519_BASEPOINT: MontgomeryPoint = MontgomeryPoint([
+    0x09, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+]);
+
+impl MontgomeryPoint {
+    /// View this encoding as an array of bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Copy this encoding into an array of bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Decode the u coordinate, ignoring the top bit, as RFC 7748 requires.
+    fn u(&self) -> Fp {
+        let mut bytes = self.0;
+        bytes[31] &= 0x7F;
+        Fp::from_bytes(&bytes)
+    }
+
+    /// Calculate k⋅self, for the integer k given by its bits, from most to least significant.
+    fn mul_bits(&self, bits: impl Iterator<Item = Choice>) -> Self {
+        let (mut x2, mut x3) = ladder(&self.u(), bits);
+        let out = x2.to_affine();
+        wipe(&mut x2);
+        wipe(&mut x3);
+        out
+    }
+
+    /// Calculate k⋅self, where k is a 32 byte X25519 private key, after clamping it.
+    ///
+    /// Clamping clears the low 3 bits of k, and sets bit 254 while clearing bit 255,
+    /// as in RFC 7748. The result is a multiple of the cofactor, and has a fixed bit
+    /// length, so this is exactly the X25519 function. This runs in constant time.
+    pub fn mul_clamped(&self, mut bytes: [u8; 32]) -> Self {
+        bytes[0] &= 0xF8;
+        bytes[31] &= 0x7F;
+        bytes[31] |= 0x40;
+        let out = self.mul_bits(
+            (0..255)
+                .rev()
+                .map(|i| Choice::from((bytes[i / 8] >> (i % 8)) & 1)),
+        );
+        wipe(&mut bytes);
+        out
+    }
+}
+
+/// This prints the 64 hex characters of the encoding, in order, as is.
+impl LowerHex for MontgomeryPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+impl Display for MontgomeryPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+impl Debug for MontgomeryPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MontgomeryPoint({})", self)
+    }
+}
+
+impl ConstantTimeEq for MontgomeryPoint {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.u().ct_eq(&other.u())
+    }
+}
+
+// Both P and -P share the same encoding, so negation is meaningless here, and we don't
+// implement ConditionallyNegatable.
+impl ConditionallySelectable for MontgomeryPoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(std::array::from_fn(|i| {
+            u8::conditional_select(&a.0[i], &b.0[i], choice)
+        }))
+    }
+}
+
+impl PartialEq for MontgomeryPoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for MontgomeryPoint {}
+
+impl Hash for MontgomeryPoint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Equal points must hash the same, so we hash the canonical encoding.
+        self.u().to_bytes().hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for MontgomeryPoint {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::util::serialize_bytes(&self.0, serializer)
+    }
+}
+
+/// Any 32 bytes encode a u coordinate, so there's nothing to validate, and the bytes
+/// are kept as is, even when they aren't canonical.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MontgomeryPoint {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::util::deserialize_bytes(deserializer, "a 32 byte u coordinate").map(Self)
+    }
+}
+
+impl Mul<&Scalar> for &MontgomeryPoint {
+    type Output = MontgomeryPoint;
+
+    /// Multiply this point by a scalar, using the Montgomery ladder.
+    ///
+    /// Unlike [`MontgomeryPoint::mul_clamped`], this uses the scalar as is, reduced mod ℓ.
+    /// This runs in constant time.
+    fn mul(self, scalar: &Scalar) -> Self::Output {
+        self.mul_bits(scalar.iter_bits())
+    }
+}
+
+impl Mul<&MontgomeryPoint> for &Scalar {
+    type Output = MontgomeryPoint;
+
+    fn mul(self, point: &MontgomeryPoint) -> Self::Output {
+        point * self
+    }
+}
+
+/// Calculate (k⋅P, (k + 1)⋅P), given the affine u coordinate of P, and the bits of the
+/// integer k, from most to least significant.
+///
+/// This is the Montgomery ladder, which does the same differential addition and
+/// doubling for every bit, only swapping its two accumulators, in constant time,
+/// according to the bit.
+fn ladder(u: &Fp, bits: impl Iterator<Item = Choice>) -> (ProjectivePoint, ProjectivePoint) {
+    // We keep x₂ = k'⋅P and x₃ = (k' + 1)⋅P, for the prefix k' of k processed so far,
+    // so that x₃ - x₂ = P is always known.
+    let mut x2 = ProjectivePoint::IDENTITY;
+    let mut x3 = ProjectivePoint { u: *u, w: Fp::ONE };
+    let mut swap = Choice::from(0);
+    for bit in bits {
+        // We only swap back when the next bit differs, saving a swap for each run.
+        swap ^= bit;
+        ProjectivePoint::conditional_swap(&mut x2, &mut x3, swap);
+        swap = bit;
+        ProjectivePoint::differential_add_and_double(&mut x2, &mut x3, u);
+    }
+    ProjectivePoint::conditional_swap(&mut x2, &mut x3, swap);
+    (x2, x3)
+}
+
+/// A u coordinate, in projective form, as (U : W), with u = U / W.
+#[derive(Clone, Copy)]
+struct ProjectivePoint {
+    u: Fp,
+    w: Fp,
+}
+
+impl ProjectivePoint {
+    /// The identity, i.e. the point at infinity, with W = 0.
+    const IDENTITY: Self = Self {
+        u: Fp::ONE,
+        w: Fp::ZERO,
+    };
+
+    /// Calculate (2⋅P, P + Q), given (P, Q), and the affine u coordinate of Q - P.
+    ///
+    /// This is one step of the ladder, from RFC 7748, Section 5, which costs
+    /// 5 multiplications, 4 squarings, and 1 multiplication by (A - 2) / 4.
+    fn differential_add_and_double(p: &mut Self, q: &mut Self, u_diff: &Fp) {
+        let a = p.u + p.w;
+        let aa = a.square();
+        let b = p.u - p.w;
+        let bb = b.square();
+        let e = aa - bb;
+        let c = q.u + q.w;
+        let d = q.u - q.w;
+        let da = d * a;
+        let cb = c * b;
+        q.u = (da + cb).square();
+        q.w = u_diff * (da - cb).square();
+        p.u = aa * bb;
+        p.w = e * (aa + e.mul_small(A24));
+    }
+
+    /// Convert this point to its affine encoding.
+    ///
+    /// The identity, with W = 0, gets encoded as u = 0, since inverting 0 gives 0.
+    fn to_affine(self) -> MontgomeryPoint {
+        MontgomeryPoint((self.u * self.w.invert()).to_bytes())
+    }
+}
+
+impl ConditionallySelectable for ProjectivePoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self {
+            u: Fp::conditional_select(&a.u, &b.u, choice),
+            w: Fp::conditional_select(&a.w, &b.w, choice),
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for ProjectivePoint {
+    fn zeroize(&mut self) {
+        self.u.zeroize();
+        self.w.zeroize();
+    }
+}
+
+impl EdwardsPoint {
+    /// Convert this point to Montgomery form, keeping only its u coordinate.
+    ///
+    /// This uses the birational map u = (1 + y) / (1 - y). The identity, with y = 1,
+    /// maps to u = 0. This runs in constant time.
+    pub fn to_montgomery(&self) -> MontgomeryPoint {
+        // u = (Z + Y) / (Z - Y), in projective coordinates.
+        let u = (self.z + self.y) * (self.z - self.y).invert();
+        MontgomeryPoint(u.to_bytes())
+    }
+
+    /// Convert this point to affine Montgomery coordinates, (u, v).
+    ///
+    /// This uses the birational map u = (1 + y) / (1 - y), v = sqrt(-(A + 2))⋅u / x,
+    /// with a single inversion. The points with x = 0, i.e. the identity and (0, -1),
+    /// both map to (0, 0). This runs in constant time.
+    pub(crate) fn montgomery_coordinates(&self) -> (Fp, Fp) {
+        // u = (Z + Y)⋅X / ((Z - Y)⋅X), v = sqrt(-(A + 2))⋅(Z + Y)⋅Z / ((Z - Y)⋅X).
+        let z_plus_y = self.z + self.y;
+        let inv = ((self.z - self.y) * self.x).invert();
+        let u = z_plus_y * self.x * inv;
+        let v = SQRT_NEG_A_PLUS_2 * z_plus_y * self.z * inv;
+        (u, v)
+    }
+
+    /// Calculate scalar⋅self, using the Montgomery ladder, and then recovering the full
+    /// point, rather than just its u coordinate.
+    ///
+    /// The ladder only tracks the u coordinates of k⋅P and (k + 1)⋅P, but along with
+    /// the full coordinates of P, that's enough to recover the v coordinate of k⋅P,
+    /// using the method of [Okeya and Sakurai](https://doi.org/10.1007/3-540-44709-1_12).
+    /// This needs a single inversion, at the start, and handles the exceptional
+    /// cases, where one of the points involved is the identity, or (0, -1), in
+    /// constant time. This runs in constant time.
+    pub fn mul_ladder(&self, scalar: &Scalar) -> EdwardsPoint {
+        let (x, y) = self.montgomery_coordinates();
+        let (mut q, mut q_plus_p) = ladder(&x, scalar.iter_bits());
+        let (x1, z1, x2, z2) = (q.u, q.w, q_plus_p.u, q_plus_p.w);
+
+        // With Q = (X₁ : Z₁) = k⋅P, and Q + P = (X₂ : Z₂), Okeya-Sakurai recovery gives
+        // Q in projective Montgomery coordinates, as (X' : Y' : Z').
+        let t1 = x * z1;
+        let t3 = (x1 - t1).square() * x2;
+        let t1 = MONTGOMERY_A.double() * z1;
+        let t2 = (x1 + x * z1 + t1) * (x * x1 + z1) - t1 * z1;
+        let y_q = t2 * z2 - t3;
+        let t1 = y.double() * z1 * z2;
+        let x_q = t1 * x1;
+        let z_q = t1 * z1;
+
+        // Mapping back to Edwards form, with x = sqrt(-(A + 2))⋅u / v, and
+        // y = (u - 1) / (u + 1), over the common denominator Y'⋅(X' + Z').
+        let x_plus_z = x_q + z_q;
+        let x_minus_z = x_q - z_q;
+        let c_x = SQRT_NEG_A_PLUS_2 * x_q;
+        let mut out = EdwardsPoint {
+            x: c_x * x_plus_z,
+            y: x_minus_z * y_q,
+            z: y_q * x_plus_z,
+            t: c_x * x_minus_z,
+        };
+
+        // Recovery fails when Q, Q + P, or P, has v = 0, or is the identity. The only
+        // point with v = 0 is (0, -1), in Edwards form, which has u = 0.
+        out.conditional_assign(&EIGHT_TORSION[4], x1.is_zero());
+        out.conditional_assign(&-self, z2.is_zero());
+        out.conditional_assign(&EdwardsPoint::IDENTITY, z1.is_zero());
+        // With u = 0, the ladder itself breaks down, but P is then either the identity,
+        // or (0, -1), of order 2, so k⋅P only depends on the parity of k.
+        let odd = Choice::from(scalar.to_bytes()[0] & 1);
+        let small = EdwardsPoint::conditional_select(&EdwardsPoint::IDENTITY, self, odd);
+        out.conditional_assign(&small, self.x.is_zero());
+        wipe(&mut q);
+        wipe(&mut q_plus_p);
+        out
+    }
+}
@@ -0,0 +1,234 @@
+//! The canonical 32 byte encoding of ristretto255 elements.
+use std::fmt::{Debug, Display, LowerHex};
+
+use subtle::{Choice, ConditionallyNegatable, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+use super::RistrettoPoint;
+#[cfg(feature = "serde")]
+use crate::util::{deserialize_bytes, serialize_bytes};
+use crate::{
+    curve::{
+        edwards::{EdwardsPoint, EDWARDS_D},
+        field::Fp,
+    },
+    util::write_hex,
+};
+
+/// 1 / sqrt(a - d), with a = -1.
+const INVSQRT_A_MINUS_D: Fp = Fp::from_bytes(&[
+    0xEA, 0x40, 0x5D, 0x80, 0xAA, 0xFD, 0xC8, 0x99, 0xBE, 0x72, 0x41, 0x5A, 0x17, 0x16, 0x2F, 0x9D,
+    0x40, 0xD8, 0x01, 0xFE, 0x91, 0x7B, 0xC2, 0x16, 0xA2, 0xFC, 0xAF, 0xCF, 0x05, 0x89, 0x6C, 0x78,
+]);
+
+/// An element of ristretto255, compressed to 32 bytes.
+///
+/// This is the little endian encoding of a non-negative field element s, as specified
+/// in RFC 9496. Each element has exactly one encoding, but this type doesn't guarantee
+/// that its bytes are one; that only gets checked by [`Self::decompress`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompressedRistretto(pub [u8; 32]);
+
+impl CompressedRistretto {
+    /// View this encoding as an array of bytes.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Copy this encoding into an array of bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Attempt to recover the element this encodes.
+    ///
+    /// Decoding is strict, so that every element has exactly one accepted encoding.
+    /// Following RFC 9496, this fails if:
+    ///
+    /// - s isn't canonical, i.e. s ≥ P, including when the top bit is set,
+    /// - s is negative, i.e. its encoding is odd,
+    /// - the x coordinate of the candidate point would be the root of a non-square,
+    /// - x⋅y would be negative, or y would be zero, in which case a different s
+    ///   encodes the same element, or none at all.
+    ///
+    /// This runs in constant time.
+    pub fn decompress(&self) -> CtOption<RistrettoPoint> {
+        let s = Fp::from_canonical_bytes(&self.0);
+        // The value is only used if the encoding is canonical.
+        let s_value = s.unwrap_or(Fp::ZERO);
+
+        // This follows the decoding procedure of RFC 9496, Section 4.3.1, with a = -1.
+        let ss = s_value.square();
+        let u1 = Fp::ONE - ss;
+        let u2 = Fp::ONE + ss;
+        let u2_sqr = u2.square();
+        let v = -(EDWARDS_D * u1.square()) - u2_sqr;
+        let (was_square, invsqrt) = Fp::sqrt_ratio_i(&Fp::ONE, &(v * u2_sqr));
+        let den_x = invsqrt * u2;
+        let den_y = invsqrt * den_x * v;
+        let x = (s_value.double() * den_x).abs();
+        let y = u1 * den_y;
+        let t = x * y;
+
+        let point = RistrettoPoint(EdwardsPoint {
+            x,
+            y,
+            z: Fp::ONE,
+            t,
+        });
+        let valid =
+            s.is_some() & !s_value.is_negative() & was_square & !t.is_negative() & !y.is_zero();
+        CtOption::new(point, valid)
+    }
+}
+
+/// This prints the 64 hex characters of the encoding, in order.
+impl LowerHex for CompressedRistretto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+impl Display for CompressedRistretto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+impl Debug for CompressedRistretto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CompressedRistretto({})", self)
+    }
+}
+
+/// Elements are printed out through their compressed encoding, which is unique, unlike
+/// their coordinates.
+impl Debug for RistrettoPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RistrettoPoint({})", self.compress())
+    }
+}
+
+impl RistrettoPoint {
+    /// Compress this element to its canonical 32 byte encoding.
+    ///
+    /// This picks a canonical representative of the class, with a single inverse square
+    /// root, so it's about as expensive as compressing an [`EdwardsPoint`].
+    /// This runs in constant time.
+    pub fn compress(&self) -> CompressedRistretto {
+        // This follows the encoding procedure of RFC 9496, Section 4.3.2, with a = -1.
+        let EdwardsPoint { x, y, z, t } = self.0;
+        let u1 = (z + y) * (z - y);
+        let u2 = x * y;
+        // Points of the form 2⋅P always make this a square, so we ignore the flag.
+        let (_, invsqrt) = Fp::sqrt_ratio_i(&Fp::ONE, &(u1 * u2.square()));
+        let den1 = invsqrt * u1;
+        let den2 = invsqrt * u2;
+        let z_inv = den1 * den2 * t;
+        let ix = x * Fp::SQRT_M1;
+        let iy = y * Fp::SQRT_M1;
+        let enchanted_denominator = den1 * INVSQRT_A_MINUS_D;
+
+        // Rotating by (±i, 0) lets us pick a representative with a non-negative x⋅y.
+        let rotate = (t * z_inv).is_negative();
+        let x = Fp::conditional_select(&x, &iy, rotate);
+        let mut y = Fp::conditional_select(&y, &ix, rotate);
+        let den_inv = Fp::conditional_select(&den2, &enchanted_denominator, rotate);
+        // And negating, with (0, -1), one with a non-negative x.
+        y.conditional_negate((x * z_inv).is_negative());
+        let s = (den_inv * (z - y)).abs();
+        CompressedRistretto(s.to_bytes())
+    }
+}
+
+impl RistrettoPoint {
+    /// Compress 2⋅P, for every element P of a slice, in order.
+    ///
+    /// Encoding an element needs an inverse square root, which can't be shared across
+    /// a batch. The encoding of a doubled element, however, can be read off the
+    /// intermediate values of the doubling formula with a plain inversion instead,
+    /// and inversions can be batched, so this only costs a single inversion overall,
+    /// along with a few multiplications per element. This is useful for protocols
+    /// that encode many elements at once, which can produce their elements halved,
+    /// or work with the doubled elements directly.
+    ///
+    /// This runs in constant time, with respect to the values of the elements.
+    pub fn double_and_compress_batch(points: &[Self]) -> Vec<CompressedRistretto> {
+        // For each point, the doubling formula gives 2⋅P = (E⋅F : G⋅H : F⋅G : E⋅H), with:
+        //     E = 2⋅X⋅Y, F = Z² + d⋅T², G = Y² + X², H = Z² - d⋅T².
+        let mut states = Vec::with_capacity(points.len());
+        let mut invs = Vec::with_capacity(points.len());
+        for point in points {
+            let EdwardsPoint { x, y, z, t } = point.0;
+            let (xx, yy, zz) = (x.square(), y.square(), z.square());
+            let dtt = EDWARDS_D * t.square();
+            let e = x * y.double();
+            let f = zz + dtt;
+            let g = yy + xx;
+            let h = zz - dtt;
+            let (eg, fh) = (e * g, f * h);
+            states.push((e, f, g, h, eg, fh));
+            invs.push(eg * fh);
+        }
+        Fp::batch_invert(&mut invs);
+
+        let mut out = Vec::with_capacity(points.len());
+        for ((e, f, g, h, eg, fh), inv) in states.into_iter().zip(invs) {
+            // With the coordinates of 2⋅P, 1 / Z = E⋅G / (E⋅F⋅G⋅H), and 1 / T = F⋅H / (E⋅F⋅G⋅H).
+            let z_inv = eg * inv;
+            let t_inv = fh * inv;
+            // This is then the same as the encoding procedure of RFC 9496, where the
+            // inverse square root is replaced by these inverses.
+            let rotate = (eg * z_inv).is_negative();
+            let e_rot = Fp::conditional_select(&e, &g, rotate);
+            let mut g = Fp::conditional_select(&g, &-e, rotate);
+            let h = Fp::conditional_select(&h, &(f * Fp::SQRT_M1), rotate);
+            let magic = Fp::conditional_select(&INVSQRT_A_MINUS_D, &Fp::SQRT_M1, rotate);
+            g.conditional_negate((h * e_rot * z_inv).is_negative());
+            let s = ((h - g) * (magic * g * t_inv)).abs();
+            out.push(CompressedRistretto(s.to_bytes()));
+        }
+        out
+    }
+}
+
+impl ConstantTimeEq for CompressedRistretto {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CompressedRistretto {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(&self.0, serializer)
+    }
+}
+
+/// Like the type itself, this doesn't check that the bytes encode a valid element.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CompressedRistretto {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_bytes(deserializer, "a 32 byte compressed element").map(Self)
+    }
+}
+
+/// Elements are serialized in compressed form.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RistrettoPoint {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_bytes(&self.compress().0, serializer)
+    }
+}
+
+/// This rejects any encoding that [`CompressedRistretto::decompress`] rejects.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RistrettoPoint {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let bytes =
+            deserialize_bytes(deserializer, "the canonical 32 byte encoding of an element")?;
+        Option::from(CompressedRistretto(bytes).decompress())
+            .ok_or_else(|| D::Error::custom("invalid element encoding"))
+    }
+}
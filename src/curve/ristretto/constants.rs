@@ -0,0 +1,21 @@
+//! Constant elements of ristretto255.
+use super::{CompressedRistretto, RistrettoBasepointTable, RistrettoPoint};
+use crate::curve::edwards::{ED25519_BASEPOINT_POINT, ED25519_BASEPOINT_TABLE};
+
+/// The generator of ristretto255, as specified in RFC 9496.
+///
+/// This is the class of [`ED25519_BASEPOINT_POINT`].
+pub const RISTRETTO_BASEPOINT_POINT: RistrettoPoint = RistrettoPoint(ED25519_BASEPOINT_POINT);
+
+/// The encoding of [`RISTRETTO_BASEPOINT_POINT`].
+pub const RISTRETTO_BASEPOINT_COMPRESSED: CompressedRistretto = CompressedRistretto([
+    0xE2, 0xF2, 0xAE, 0x0A, 0x6A, 0xBC, 0x4E, 0x71, 0xA8, 0x84, 0xA9, 0x61, 0xC5, 0x00, 0x51, 0x5F,
+    0x58, 0xE3, 0x0B, 0x6A, 0xA5, 0x82, 0xDD, 0x8D, 0xB6, 0xA6, 0x59, 0x45, 0xE0, 0x8D, 0x2D, 0x76,
+]);
+
+/// A table of multiples of [`RISTRETTO_BASEPOINT_POINT`], for fast multiplication.
+///
+/// This shares its memory with [`ED25519_BASEPOINT_TABLE`], since both generators have
+/// the same representative, so it's computed by the build script, like that table.
+pub static RISTRETTO_BASEPOINT_TABLE: &RistrettoBasepointTable =
+    RistrettoBasepointTable::from_edwards(&ED25519_BASEPOINT_TABLE);
@@ -0,0 +1,78 @@
+//! The Elligator map for ristretto255, from field elements to elements of the group.
+//!
+//! This follows the map of [RFC 9496, Section 4.3.4](https://www.rfc-editor.org/rfc/rfc9496#section-4.3.4),
+//! which is a variant of Elligator 2 landing directly on points of the form 2⋅P, so that
+//! its output is always a valid element, without any cofactor to clear.
+use subtle::ConditionallySelectable;
+
+use super::RistrettoPoint;
+use crate::curve::{
+    edwards::{EdwardsPoint, EDWARDS_D},
+    field::Fp,
+};
+
+/// 1 - d².
+const ONE_MINUS_D_SQ: Fp = Fp::from_bytes(&[
+    0x76, 0xC1, 0x5F, 0x94, 0xC1, 0x09, 0x7C, 0xE2, 0x0F, 0x35, 0x5E, 0xCD, 0x38, 0xA1, 0x81, 0x2C,
+    0xE4, 0xDF, 0x70, 0xBE, 0xDD, 0xAB, 0x94, 0x99, 0xD7, 0xE0, 0xB3, 0xB2, 0xA8, 0x72, 0x90, 0x02,
+]);
+
+/// (d - 1)².
+const D_MINUS_ONE_SQ: Fp = Fp::from_bytes(&[
+    0x20, 0x4D, 0xED, 0x44, 0xAA, 0x5A, 0xAD, 0x31, 0x99, 0x19, 0x1E, 0xB0, 0x2C, 0x4A, 0x9E, 0xD2,
+    0xEB, 0x4E, 0x9B, 0x52, 0x2F, 0xD3, 0xDC, 0x4C, 0x41, 0x22, 0x6C, 0xF6, 0x7A, 0xB3, 0x68, 0x59,
+]);
+
+/// sqrt(a⋅d - 1), with a = -1, the non-negative root.
+const SQRT_AD_MINUS_ONE: Fp = Fp::from_bytes(&[
+    0x1B, 0x2E, 0x7B, 0x49, 0xA0, 0xF6, 0x97, 0x7E, 0xBD, 0x54, 0x78, 0x1B, 0x0C, 0x8E, 0x9D, 0xAF,
+    0xFD, 0xD1, 0xF5, 0x31, 0xC9, 0xFC, 0x3C, 0x0F, 0xAC, 0x48, 0x83, 0x2B, 0xBF, 0x31, 0x69, 0x37,
+]);
+
+/// Map a field element to an element of ristretto255.
+///
+/// The map isn't surjective, and it isn't uniform on its own: about half of the elements
+/// are reached, and summing the images of two independent field elements is what makes
+/// the result uniform. This runs in constant time.
+pub(crate) fn map_to_group(t: &Fp) -> RistrettoPoint {
+    let r = Fp::SQRT_M1 * t.square();
+    let u = (r + Fp::ONE) * ONE_MINUS_D_SQ;
+    let v = (-Fp::ONE - r * EDWARDS_D) * (r + EDWARDS_D);
+    let (was_square, mut s) = Fp::sqrt_ratio_i(&u, &v);
+    // Otherwise, s is a root of i⋅u / v, from which we get a root of r⋅u / v instead.
+    let s_prime = -(s * t).abs();
+    s.conditional_assign(&s_prime, !was_square);
+    let c = Fp::conditional_select(&r, &-Fp::ONE, was_square);
+
+    let n = c * (r - Fp::ONE) * D_MINUS_ONE_SQ - v;
+    let w0 = (s * v).double();
+    let w1 = n * SQRT_AD_MINUS_ONE;
+    let w2 = Fp::ONE - s.square();
+    let w3 = Fp::ONE + s.square();
+    RistrettoPoint(EdwardsPoint {
+        x: w0 * w3,
+        y: w2 * w1,
+        z: w1 * w3,
+        t: w0 * w2,
+    })
+}
+
+impl RistrettoPoint {
+    /// Map 64 uniformly random bytes to a uniformly random element.
+    ///
+    /// Each half is reduced to a field element, ignoring its top bit, and mapped to the
+    /// group, with the results added together, as specified in RFC 9496. The map is
+    /// one way: nobody knows the discrete logarithm of the output, with respect to any
+    /// other element, which makes this suitable for deriving independent generators
+    /// from a hash, or for hashing to the group. This runs in constant time.
+    pub fn from_uniform_bytes(bytes: &[u8; 64]) -> Self {
+        let mut half = [0u8; 32];
+        half.copy_from_slice(&bytes[..32]);
+        half[31] &= 0x7F;
+        let p1 = map_to_group(&Fp::from_bytes(&half));
+        half.copy_from_slice(&bytes[32..]);
+        half[31] &= 0x7F;
+        let p2 = map_to_group(&Fp::from_bytes(&half));
+        p1 + p2
+    }
+}
@@ -0,0 +1,49 @@
+//! Hashing to ristretto255, following [RFC 9380](https://www.rfc-editor.org/rfc/rfc9380).
+//!
+//! With SHA-512, this implements the ristretto255_XMD:SHA-512_R255MAP_RO_ suite, from
+//! Appendix B, for [`RistrettoPoint::hash_to_group`]. [`RistrettoPoint::hash_to_group_xof`]
+//! is the same construction, with an extendable output function, like SHAKE256.
+use digest::{core_api::BlockSizeUser, Digest, ExtendableOutput, Update};
+
+use super::RistrettoPoint;
+use crate::{
+    curve::edwards::hash::{expand_message_xmd, expand_message_xof},
+    util::wipe,
+};
+
+impl RistrettoPoint {
+    /// Hash a message to an element, using a domain separation tag.
+    ///
+    /// This expands the message to 64 uniformly random bytes, and maps them to the group
+    /// with [`Self::from_uniform_bytes`], so the output is indistinguishable from a
+    /// random element.
+    ///
+    /// With `D` set to SHA-512, this is the ristretto255_XMD:SHA-512_R255MAP_RO_ suite.
+    /// Different protocols, or different uses within a protocol, should use different
+    /// tags. Tags longer than 255 bytes get hashed down, as RFC 9380 specifies.
+    /// This runs in constant time, with respect to the message.
+    pub fn hash_to_group<D: Digest + BlockSizeUser>(msg: &[u8], dst: &[u8]) -> Self {
+        let mut bytes = [0u8; 64];
+        expand_message_xmd::<D>(msg, dst, &mut bytes);
+        let out = Self::from_uniform_bytes(&bytes);
+        wipe(&mut bytes[..]);
+        out
+    }
+
+    /// Hash a message to an element, using a domain separation tag, and an XOF.
+    ///
+    /// This is exactly like [`Self::hash_to_group`], but expands the message with
+    /// expand_message_xof, from RFC 9380, instead. With `X` set to SHAKE256, this is
+    /// the ristretto255_XOF:SHAKE256_R255MAP_RO_ suite. Tags longer than 255 bytes get
+    /// hashed down to 32 bytes. This runs in constant time, with respect to the message.
+    pub fn hash_to_group_xof<X: Default + Update + ExtendableOutput>(
+        msg: &[u8],
+        dst: &[u8],
+    ) -> Self {
+        let mut bytes = [0u8; 64];
+        expand_message_xof::<X>(msg, dst, &mut bytes);
+        let out = Self::from_uniform_bytes(&bytes);
+        wipe(&mut bytes[..]);
+        out
+    }
+}
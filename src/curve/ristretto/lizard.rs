@@ -0,0 +1,215 @@
+//! Lizard, an injective encoding of 16 bytes of data into elements of ristretto255.
+//!
+//! The data gets padded out to a field element with a hash of itself, and mapped to the
+//! group with the Elligator map of RFC 9496. Decoding inverts that map, which gives at
+//! most 8 candidate field elements, and recognizes the one that was produced by the
+//! encoding through its padding. This is the scheme of Westerbaan, as implemented by
+//! the lizard fork of curve25519-dalek, and produces the same elements.
+use digest::{consts::U32, Digest};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+use super::{elligator::map_to_group, RistrettoPoint};
+use crate::curve::field::Fp;
+
+/// sqrt(i⋅d), the non-negative root.
+const SQRT_ID: Fp = Fp::from_bytes(&[
+    0xA8, 0x1B, 0x5C, 0x4A, 0xCB, 0x2A, 0x30, 0x75, 0xAA, 0x6D, 0xEA, 0x0E, 0x2D, 0xA9, 0xBC, 0xCD,
+    0x15, 0x6E, 0xEB, 0x73, 0x99, 0x54, 0x34, 0x75, 0x97, 0xEB, 0x7B, 0xF4, 0x58, 0x55, 0xB3, 0x05,
+]);
+
+/// (d + 1) / (d - 1).
+const DP1_OVER_DM1: Fp = Fp::from_bytes(&[
+    0x2C, 0xBB, 0x81, 0x9B, 0x5F, 0xAC, 0x7F, 0x27, 0xC8, 0x1D, 0x24, 0xCD, 0xF1, 0xE7, 0x9A, 0x48,
+    0x24, 0x18, 0x9F, 0x99, 0x5F, 0xD9, 0xF8, 0xAE, 0x9D, 0xD8, 0xE8, 0xA7, 0x30, 0xC8, 0x67, 0x0E,
+]);
+
+/// -2 / sqrt(a - d), with a = -1.
+const MDOUBLE_INVSQRT_A_MINUS_D: Fp = Fp::from_bytes(&[
+    0x06, 0x7E, 0x45, 0xFF, 0xAA, 0x04, 0x6E, 0xCC, 0x82, 0x1A, 0x7D, 0x4B, 0xD1, 0xD3, 0xA1, 0xC5,
+    0x7E, 0x4F, 0xFC, 0x03, 0xDC, 0x08, 0x7B, 0xD2, 0xBB, 0x06, 0xA0, 0x60, 0xF4, 0xED, 0x26, 0x0F,
+]);
+
+/// -2⋅i / sqrt(a - d), with a = -1.
+const MIDOUBLE_INVSQRT_A_MINUS_D: Fp = Fp::from_bytes(&[
+    0xD8, 0xBB, 0x77, 0x63, 0x10, 0xB7, 0x5D, 0x16, 0x9C, 0x6C, 0xB5, 0xD7, 0x38, 0xEE, 0xA5, 0x9C,
+    0x10, 0x59, 0x0B, 0x28, 0x85, 0x58, 0xE0, 0x3D, 0x50, 0x3D, 0x56, 0x06, 0x68, 0x0B, 0x1B, 0x14,
+]);
+
+/// -1 / sqrt(1 + d), using the non-negative root.
+const MINVSQRT_ONE_PLUS_D: Fp = Fp::from_bytes(&[
+    0x01, 0x22, 0x44, 0xCE, 0x77, 0x24, 0xD1, 0xF4, 0xB1, 0x49, 0x25, 0x94, 0xE3, 0x08, 0xAD, 0xB1,
+    0x77, 0x53, 0xFA, 0x6B, 0xBD, 0xD3, 0x0F, 0xE1, 0x57, 0xE1, 0xD4, 0xFC, 0x4B, 0x7A, 0xF2, 0x75,
+]);
+
+/// A point on the Jacobi quartic t² = s⁴ + 2⋅(a - 2⋅d)⋅s² + 1, which is 2-isogenous
+/// to edwards25519.
+///
+/// The Elligator map of ristretto255 goes through this curve, and each element of the
+/// group corresponds to 8 points on it, which is where its 8 preimages come from.
+#[derive(Clone, Copy)]
+struct JacobiPoint {
+    s: Fp,
+    t: Fp,
+}
+
+impl JacobiPoint {
+    /// The other point on the quartic, (-s, -t), which maps to the same element.
+    fn dual(&self) -> Self {
+        Self {
+            s: -self.s,
+            t: -self.t,
+        }
+    }
+
+    /// Find the non-negative field element mapped to this point by the Elligator map.
+    ///
+    /// Only about half of the points have a preimage, so this also returns whether or
+    /// not it exists. This runs in constant time.
+    fn elligator_inv(&self) -> (Choice, Fp) {
+        let mut out = Fp::ZERO;
+
+        // Special case: s = 0. If t = 1, then the preimage is sqrt(i⋅d), and otherwise
+        // the point is (0, -1), whose preimage is 0.
+        let s_is_zero = self.s.is_zero();
+        out.conditional_assign(&SQRT_ID, self.t.ct_eq(&Fp::ONE));
+        let mut ret = s_is_zero;
+        let mut done = s_is_zero;
+
+        // a := (t + 1)⋅(d + 1) / (d - 1)
+        let a = (self.t + Fp::ONE) * DP1_OVER_DM1;
+        let a2 = a.square();
+        // y := 1 / sqrt(i⋅(s⁴ - a²))
+        let s2 = self.s.square();
+        let s4 = s2.square();
+        let (sq, y) = Fp::sqrt_ratio_i(&Fp::ONE, &(Fp::SQRT_M1 * (s4 - a2)));
+        // If there is no square root, there's no preimage.
+        ret |= sq;
+        done |= !sq;
+
+        // x := (a + sign(s)⋅s²)⋅y
+        let pms2 = Fp::conditional_select(&s2, &-s2, self.s.is_negative());
+        let x = ((a + pms2) * y).abs();
+        out.conditional_assign(&x, !done);
+
+        (ret, out)
+    }
+}
+
+impl RistrettoPoint {
+    /// The 4 points on the Jacobi quartic in the class of this element.
+    ///
+    /// Along with their duals, these give the 8 points which map to this element.
+    fn to_jacobi_quartic(self) -> [JacobiPoint; 4] {
+        let (x, y, z) = (self.0.x, self.0.y, self.0.z);
+        let x2 = x.square();
+        let y2 = y.square();
+        let y4 = y2.square();
+        let z2 = z.square();
+        let z_min_y = z - y;
+        let z_pl_y = z + y;
+        let z2_min_y2 = z2 - y2;
+
+        // γ := 1 / sqrt(y⁴⋅x²⋅(z² - y²))
+        let (_, gamma) = Fp::sqrt_ratio_i(&Fp::ONE, &(y4 * x2 * z2_min_y2));
+
+        let den = gamma * y2;
+        let s_over_x = den * z_min_y;
+        let sp_over_xp = den * z_pl_y;
+        let s0 = s_over_x * x;
+        let s1 = -sp_over_xp * x;
+
+        // t_i := -2⋅z / sqrt(a - d)⋅s_i / x_i
+        let tmp = MDOUBLE_INVSQRT_A_MINUS_D * z;
+        let mut t0 = tmp * s_over_x;
+        let mut t1 = tmp * sp_over_xp;
+
+        // The same thing, for the point rotated by (i, 0), to the other two classes.
+        let den = -z2_min_y2 * MINVSQRT_ONE_PLUS_D * gamma;
+        let iz = Fp::SQRT_M1 * z;
+        let iz_min_x = iz - x;
+        let iz_pl_x = iz + x;
+        let s_over_y = den * iz_min_x;
+        let sp_over_yp = den * iz_pl_x;
+        let mut s2 = s_over_y * y;
+        let mut s3 = -sp_over_yp * y;
+
+        let tmp = MDOUBLE_INVSQRT_A_MINUS_D * iz;
+        let mut t2 = tmp * s_over_y;
+        let mut t3 = tmp * sp_over_yp;
+
+        // Special case: x = 0 or y = 0, where γ is undefined, and the points are fixed.
+        let x_or_y_is_zero = x.is_zero() | y.is_zero();
+        t0.conditional_assign(&Fp::ONE, x_or_y_is_zero);
+        t1.conditional_assign(&Fp::ONE, x_or_y_is_zero);
+        t2.conditional_assign(&MIDOUBLE_INVSQRT_A_MINUS_D, x_or_y_is_zero);
+        t3.conditional_assign(&MIDOUBLE_INVSQRT_A_MINUS_D, x_or_y_is_zero);
+        s2.conditional_assign(&Fp::ONE, x_or_y_is_zero);
+        s3.conditional_assign(&-Fp::ONE, x_or_y_is_zero);
+
+        [
+            JacobiPoint { s: s0, t: t0 },
+            JacobiPoint { s: s1, t: t1 },
+            JacobiPoint { s: s2, t: t2 },
+            JacobiPoint { s: s3, t: t3 },
+        ]
+    }
+
+    /// Find the field elements which the Elligator map sends to this element.
+    ///
+    /// There are at most 8 of them, and each comes with whether or not it exists.
+    fn elligator_inv(&self) -> [(Choice, Fp); 8] {
+        let mut out = [(Choice::from(0), Fp::ZERO); 8];
+        for (i, jc) in self.to_jacobi_quartic().iter().enumerate() {
+            out[2 * i] = jc.elligator_inv();
+            out[2 * i + 1] = jc.dual().elligator_inv();
+        }
+        out
+    }
+}
+
+/// Pad 16 bytes of data out to the encoding of a field element, with a hash of the data.
+///
+/// The result is non-negative, and below 2²⁵⁴, so it's always canonical.
+fn lizard_pad<D: Digest<OutputSize = U32>>(data: &[u8; 16]) -> [u8; 32] {
+    let mut bytes: [u8; 32] = D::digest(data).into();
+    bytes[8..24].copy_from_slice(data);
+    bytes[0] &= 0xFE;
+    bytes[31] &= 0x3F;
+    bytes
+}
+
+impl RistrettoPoint {
+    /// Encode 16 bytes of data as an element of the group, using Lizard.
+    ///
+    /// The data can be recovered with [`Self::lizard_decode`], using the same hash
+    /// function, which needs 32 bytes of output, like SHA-256. The encoding is injective,
+    /// but unlike [`Self::from_uniform_bytes`], it's not one way: the discrete logarithm
+    /// of the result may well be known to whoever picked the data, so these elements
+    /// shouldn't be used as generators. This runs in constant time.
+    pub fn lizard_encode<D: Digest<OutputSize = U32>>(data: &[u8; 16]) -> Self {
+        map_to_group(&Fp::from_bytes(&lizard_pad::<D>(data)))
+    }
+
+    /// Recover the 16 bytes of data encoded by [`Self::lizard_encode`].
+    ///
+    /// This fails for elements that aren't the encoding of any data, which is the case
+    /// for all but a negligible fraction of the group. It also fails in the very unlikely
+    /// event that two different pieces of data have an encoding in this class, in which
+    /// case neither can be recovered. This runs in constant time.
+    pub fn lizard_decode<D: Digest<OutputSize = U32>>(&self) -> CtOption<[u8; 16]> {
+        let mut result = [0u8; 16];
+        let mut n_found = 0u8;
+        for (exists, fe) in self.elligator_inv() {
+            let bytes = fe.to_bytes();
+            let mut data = [0u8; 16];
+            data.copy_from_slice(&bytes[8..24]);
+            // Only the encoding of some data has that data's padding around it.
+            let found = exists & bytes[..].ct_eq(&lizard_pad::<D>(&data)[..]);
+            for (r, d) in result.iter_mut().zip(data.iter()) {
+                r.conditional_assign(d, found);
+            }
+            n_found.conditional_assign(&(n_found + 1), found);
+        }
+        CtOption::new(result, n_found.ct_eq(&1))
+    }
+}
@@ -0,0 +1,327 @@
+//! The ristretto255 group, of prime order ℓ, built on top of edwards25519.
+//!
+//! edwards25519 has order 8⋅ℓ, and its small order points make many protocols subtly
+//! broken, unless they carefully clear, or check, the cofactor. Ristretto, as specified
+//! in [RFC 9496](https://www.rfc-editor.org/rfc/rfc9496), instead gives us a group of
+//! prime order, by identifying points that only differ by an element of the 4-torsion
+//! subgroup, and picking a canonical encoding for each class. The remaining factor of 2
+//! gets eliminated by the encoding only accepting points of the form 2⋅P.
+//!
+//! Internally, a [`RistrettoPoint`] is just one representative of its class, as an
+//! [`EdwardsPoint`], so the arithmetic is exactly that of edwards25519. Only equality,
+//! and the encoding, need to treat the whole class at once.
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[cfg(feature = "group")]
+use subtle::CtOption;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::curve::{
+    edwards::{EdwardsPoint, EIGHT_TORSION},
+    scalar::Scalar,
+    traits::MultiscalarMul,
+};
+
+mod compressed;
+mod constants;
+mod elligator;
+#[cfg(feature = "digest")]
+mod hash;
+#[cfg(feature = "digest")]
+mod lizard;
+mod msm;
+mod table;
+
+pub use compressed::CompressedRistretto;
+pub use constants::{
+    RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_POINT, RISTRETTO_BASEPOINT_TABLE,
+};
+pub use msm::VartimeRistrettoPrecomputation;
+pub use table::RistrettoBasepointTable;
+
+/// An element of the ristretto255 group.
+///
+/// This is represented by any of the 4 points on edwards25519 that make up its class,
+/// so the coordinates aren't unique. To compare elements, use [`ConstantTimeEq`], or
+/// [`PartialEq`], which both account for this.
+#[derive(Clone, Copy)]
+pub struct RistrettoPoint(pub(crate) EdwardsPoint);
+
+impl RistrettoPoint {
+    /// The identity element of the group.
+    pub const IDENTITY: Self = Self(EdwardsPoint::IDENTITY);
+
+    /// Get one of the points on edwards25519 in the class of this element.
+    ///
+    /// Which one isn't specified, and may change as this element goes through arithmetic,
+    /// but it always lies in the prime order subgroup, up to a point of the 4-torsion
+    /// subgroup.
+    pub fn to_edwards(&self) -> EdwardsPoint {
+        self.0
+    }
+
+    /// Get this element with each of the 4 points in its class as the representative.
+    ///
+    /// These are all equal, and have the same encoding, but their coordinates differ,
+    /// which is useful to check that code working with elements doesn't depend on the
+    /// representative. The first one is this element, as is.
+    pub fn representatives(&self) -> [Self; 4] {
+        [
+            *self,
+            Self(self.0 + EIGHT_TORSION[2]),
+            Self(self.0 + EIGHT_TORSION[4]),
+            Self(self.0 + EIGHT_TORSION[6]),
+        ]
+    }
+
+    /// Calculate 2⋅self.
+    ///
+    /// This runs in constant time.
+    pub fn double(&self) -> Self {
+        Self(self.0.double())
+    }
+
+    /// Check whether or not this is the identity, in constant time.
+    pub fn is_identity(&self) -> Choice {
+        self.ct_eq(&Self::IDENTITY)
+    }
+
+    /// Calculate scalar⋅self, for a public scalar.
+    ///
+    /// This runs in variable time, and should only be used with public inputs.
+    pub fn vartime_mul(&self, scalar: &Scalar) -> Self {
+        Self(self.0.vartime_mul(scalar))
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Zeroize for RistrettoPoint {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ConstantTimeEq for RistrettoPoint {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        // Two points are in the same class when they differ by a point of the 4-torsion
+        // subgroup, which is the case when X₁⋅Y₂ = Y₁⋅X₂, or Y₁⋅Y₂ = X₁⋅X₂. The first
+        // covers adding the identity, or (0, -1), and the second adding (±i, 0).
+        let (p, q) = (&self.0, &other.0);
+        (p.x * q.y).ct_eq(&(p.y * q.x)) | (p.y * q.y).ct_eq(&(p.x * q.x))
+    }
+}
+
+impl PartialEq for RistrettoPoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for RistrettoPoint {}
+
+impl ConditionallySelectable for RistrettoPoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Self(EdwardsPoint::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl AddAssign<&RistrettoPoint> for RistrettoPoint {
+    fn add_assign(&mut self, other: &RistrettoPoint) {
+        self.0 += other.0;
+    }
+}
+
+impl Add<&RistrettoPoint> for &RistrettoPoint {
+    type Output = RistrettoPoint;
+
+    fn add(self, other: &RistrettoPoint) -> Self::Output {
+        let mut out = *self;
+        out += other;
+        out
+    }
+}
+
+define_op_variants!(impl Add, add, AddAssign, add_assign for RistrettoPoint);
+
+impl SubAssign<&RistrettoPoint> for RistrettoPoint {
+    fn sub_assign(&mut self, other: &RistrettoPoint) {
+        self.0 -= other.0;
+    }
+}
+
+impl Sub<&RistrettoPoint> for &RistrettoPoint {
+    type Output = RistrettoPoint;
+
+    fn sub(self, other: &RistrettoPoint) -> Self::Output {
+        let mut out = *self;
+        out -= other;
+        out
+    }
+}
+
+define_op_variants!(impl Sub, sub, SubAssign, sub_assign for RistrettoPoint);
+
+impl Neg for RistrettoPoint {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
+impl Neg for &RistrettoPoint {
+    type Output = RistrettoPoint;
+
+    fn neg(self) -> Self::Output {
+        -*self
+    }
+}
+
+impl Mul<&Scalar> for &RistrettoPoint {
+    type Output = RistrettoPoint;
+
+    /// Multiply this point by a scalar.
+    ///
+    /// This runs in constant time, with respect to both the point, and the scalar.
+    fn mul(self, scalar: &Scalar) -> Self::Output {
+        RistrettoPoint(EdwardsPoint::multiscalar_mul([scalar], [&self.0]))
+    }
+}
+
+impl Mul<&Scalar> for RistrettoPoint {
+    type Output = RistrettoPoint;
+
+    fn mul(self, scalar: &Scalar) -> Self::Output {
+        (&self).mul(scalar)
+    }
+}
+
+impl Mul<Scalar> for &RistrettoPoint {
+    type Output = RistrettoPoint;
+
+    fn mul(self, scalar: Scalar) -> Self::Output {
+        self.mul(&scalar)
+    }
+}
+
+impl Mul<Scalar> for RistrettoPoint {
+    type Output = RistrettoPoint;
+
+    fn mul(self, scalar: Scalar) -> Self::Output {
+        (&self).mul(&scalar)
+    }
+}
+
+impl MulAssign<&Scalar> for RistrettoPoint {
+    fn mul_assign(&mut self, scalar: &Scalar) {
+        *self = (&*self).mul(scalar);
+    }
+}
+
+impl MulAssign<Scalar> for RistrettoPoint {
+    fn mul_assign(&mut self, scalar: Scalar) {
+        self.mul_assign(&scalar);
+    }
+}
+
+impl Mul<&RistrettoPoint> for &Scalar {
+    type Output = RistrettoPoint;
+
+    fn mul(self, point: &RistrettoPoint) -> Self::Output {
+        point * self
+    }
+}
+
+impl Mul<RistrettoPoint> for &Scalar {
+    type Output = RistrettoPoint;
+
+    fn mul(self, point: RistrettoPoint) -> Self::Output {
+        (&point).mul(self)
+    }
+}
+
+impl Mul<&RistrettoPoint> for Scalar {
+    type Output = RistrettoPoint;
+
+    fn mul(self, point: &RistrettoPoint) -> Self::Output {
+        point.mul(&self)
+    }
+}
+
+impl Mul<RistrettoPoint> for Scalar {
+    type Output = RistrettoPoint;
+
+    fn mul(self, point: RistrettoPoint) -> Self::Output {
+        (&point).mul(&self)
+    }
+}
+
+impl std::iter::Sum for RistrettoPoint {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::IDENTITY, |acc, x| acc + x)
+    }
+}
+
+impl<'a> std::iter::Sum<&'a RistrettoPoint> for RistrettoPoint {
+    fn sum<I: Iterator<Item = &'a RistrettoPoint>>(iter: I) -> Self {
+        iter.fold(Self::IDENTITY, |acc, x| acc + x)
+    }
+}
+
+#[cfg(feature = "group")]
+impl group::Group for RistrettoPoint {
+    type Scalar = Scalar;
+
+    fn random(mut rng: impl rand_core::RngCore) -> Self {
+        // Hitting the identity is astronomically unlikely, but the trait rules it out.
+        loop {
+            let mut bytes = [0u8; 64];
+            rng.fill_bytes(&mut bytes);
+            let out = Self::from_uniform_bytes(&bytes);
+            if !bool::from(out.is_identity()) {
+                return out;
+            }
+        }
+    }
+
+    fn identity() -> Self {
+        Self::IDENTITY
+    }
+
+    fn generator() -> Self {
+        RISTRETTO_BASEPOINT_POINT
+    }
+
+    fn is_identity(&self) -> Choice {
+        RistrettoPoint::is_identity(self)
+    }
+
+    fn double(&self) -> Self {
+        RistrettoPoint::double(self)
+    }
+}
+
+/// Elements are encoded with [`RistrettoPoint::compress`], and decoding is strict.
+#[cfg(feature = "group")]
+impl group::GroupEncoding for RistrettoPoint {
+    type Repr = [u8; 32];
+
+    fn from_bytes(bytes: &Self::Repr) -> CtOption<Self> {
+        CompressedRistretto(*bytes).decompress()
+    }
+
+    /// Every encoding gets checked anyway, so this is the same as `from_bytes`.
+    fn from_bytes_unchecked(bytes: &Self::Repr) -> CtOption<Self> {
+        CompressedRistretto(*bytes).decompress()
+    }
+
+    fn to_bytes(&self) -> Self::Repr {
+        self.compress().0
+    }
+}
+
+/// ristretto255 has prime order, so every element other than the identity generates it.
+#[cfg(feature = "group")]
+impl group::prime::PrimeGroup for RistrettoPoint {}
@@ -0,0 +1,104 @@
+//! Multiscalar multiplication, computing Σ aᵢ⋅Pᵢ all at once.
+//!
+//! Elements are represented by points on edwards25519, and the sum of the multiples of
+//! any representatives represents the sum of the multiples of the elements, so all of
+//! this just goes through the implementations for [`EdwardsPoint`].
+use std::borrow::Borrow;
+
+use super::RistrettoPoint;
+use crate::curve::{
+    edwards::{EdwardsPoint, VartimeEdwardsPrecomputation},
+    scalar::Scalar,
+    traits::{MultiscalarMul, VartimeMultiscalarMul, VartimePrecomputedMultiscalarMul},
+};
+
+impl MultiscalarMul for RistrettoPoint {
+    type Point = RistrettoPoint;
+
+    fn multiscalar_mul<I, J>(scalars: I, points: J) -> RistrettoPoint
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<RistrettoPoint>,
+    {
+        let points = points.into_iter().map(|p| p.borrow().0);
+        RistrettoPoint(EdwardsPoint::multiscalar_mul(scalars, points))
+    }
+}
+
+impl VartimeMultiscalarMul for RistrettoPoint {
+    type Point = RistrettoPoint;
+
+    fn vartime_multiscalar_mul<I, J>(scalars: I, points: J) -> RistrettoPoint
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<RistrettoPoint>,
+    {
+        let points = points.into_iter().map(|p| p.borrow().0);
+        RistrettoPoint(EdwardsPoint::vartime_multiscalar_mul(scalars, points))
+    }
+}
+
+impl RistrettoPoint {
+    /// Calculate a⋅A + b⋅B, where B is the ristretto255 generator.
+    ///
+    /// This interleaves both multiplications, sharing their doublings, which is what
+    /// verifying a Schnorr signature over ristretto255 needs.
+    ///
+    /// This runs in variable time, and should only be used with public inputs.
+    pub fn vartime_double_scalar_mul_basepoint(
+        a: &Scalar,
+        point: &RistrettoPoint,
+        b: &Scalar,
+    ) -> RistrettoPoint {
+        RistrettoPoint(EdwardsPoint::vartime_double_scalar_mul_basepoint(
+            a, &point.0, b,
+        ))
+    }
+}
+
+/// Precomputed tables for a fixed set of elements, for repeated variable time
+/// multiscalar multiplication.
+///
+/// This uses the same tables as a [`VartimeEdwardsPrecomputation`], for the
+/// representatives of the elements.
+#[derive(Clone, Debug)]
+pub struct VartimeRistrettoPrecomputation(VartimeEdwardsPrecomputation);
+
+impl VartimePrecomputedMultiscalarMul for VartimeRistrettoPrecomputation {
+    type Point = RistrettoPoint;
+
+    fn new<I>(static_points: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<RistrettoPoint>,
+    {
+        Self(VartimeEdwardsPrecomputation::new(
+            static_points.into_iter().map(|p| p.borrow().0),
+        ))
+    }
+
+    fn vartime_mixed_multiscalar_mul<I, J, K>(
+        &self,
+        static_scalars: I,
+        dynamic_scalars: J,
+        dynamic_points: K,
+    ) -> RistrettoPoint
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Scalar>,
+        K: IntoIterator,
+        K::Item: Borrow<RistrettoPoint>,
+    {
+        RistrettoPoint(self.0.vartime_mixed_multiscalar_mul(
+            static_scalars,
+            dynamic_scalars,
+            dynamic_points.into_iter().map(|p| p.borrow().0),
+        ))
+    }
+}
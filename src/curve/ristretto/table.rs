@@ -0,0 +1,48 @@
+//! Precomputed tables, for fast multiplication of a fixed element.
+use std::ops::Mul;
+
+use super::RistrettoPoint;
+use crate::curve::{edwards::EdwardsBasepointTable, scalar::Scalar};
+
+/// Precomputed multiples of a fixed element, usually the generator, for fast scalar
+/// multiplication.
+///
+/// This is an [`EdwardsBasepointTable`] for one of the representatives of the element,
+/// with the same performance, and the same tradeoffs.
+#[derive(Clone, Debug)]
+#[repr(transparent)]
+pub struct RistrettoBasepointTable(pub(crate) EdwardsBasepointTable);
+
+impl RistrettoBasepointTable {
+    /// Precompute the table for a given element.
+    ///
+    /// This runs in constant time.
+    pub fn new(basepoint: &RistrettoPoint) -> Self {
+        Self(EdwardsBasepointTable::new(&basepoint.0))
+    }
+
+    /// View a table for a representative of an element as a table for that element.
+    pub(crate) const fn from_edwards(table: &EdwardsBasepointTable) -> &Self {
+        // Safe, since this type is a transparent wrapper around the Edwards table.
+        unsafe { &*(table as *const EdwardsBasepointTable as *const Self) }
+    }
+}
+
+impl Mul<&Scalar> for &RistrettoBasepointTable {
+    type Output = RistrettoPoint;
+
+    /// Multiply the element this table was built for by a scalar.
+    ///
+    /// This runs in constant time.
+    fn mul(self, scalar: &Scalar) -> Self::Output {
+        RistrettoPoint(&self.0 * scalar)
+    }
+}
+
+impl Mul<&RistrettoBasepointTable> for &Scalar {
+    type Output = RistrettoPoint;
+
+    fn mul(self, table: &RistrettoBasepointTable) -> Self::Output {
+        table * self
+    }
+}
@@ -0,0 +1,480 @@
+//! Scalars, i.e. integers mod ℓ, the order of the prime order subgroup of the curve.
+#[cfg(feature = "digest")]
+use digest::{consts::U64, Digest};
+#[cfg(feature = "rand_core")]
+use rand_core::CryptoRngCore;
+use subtle::{Choice, ConditionallySelectable};
+
+use crate::util::wipe;
+
+/// Define the scalar type, with a given reduction strategy for multiplication.
+///
+/// Like with field elements, we use 32 bit arithmetic on 32 bit targets, or with the
+/// `u32-backend` feature, keeping scalars in Montgomery form, but multiplying 32 bit
+/// words at a time. Otherwise, scalars are kept in Montgomery form with the
+/// `montgomery-scalars` feature, and use Barrett reduction by default. All of these
+/// are constant time, and none of them change the API.
+macro_rules! define_scalar {
+    ($reduction:ident) => {
+        define_prime_field! {
+            /// An integer mod ℓ = 2²⁵² + 27742317777372353535851937790883648493.
+            ///
+            /// ℓ is the order of the prime order subgroup of Curve25519, so scalars are what
+            /// we multiply points by. Elements are always fully reduced, so their representation
+            /// is unique, and every operation runs in constant time.
+            pub struct Scalar([u64; 4]);
+            modulus = [
+                0x5812631A5CF5D3ED,
+                0x14DEF9DEA2F79CD6,
+                0x0000000000000000,
+                0x1000000000000000,
+            ];
+            reduction = $reduction;
+        }
+    };
+}
+
+#[cfg(any(feature = "u32-backend", target_pointer_width = "32"))]
+define_scalar!(montgomery32);
+#[cfg(all(
+    not(any(feature = "u32-backend", target_pointer_width = "32")),
+    feature = "montgomery-scalars"
+))]
+define_scalar!(montgomery);
+#[cfg(not(any(
+    feature = "u32-backend",
+    target_pointer_width = "32",
+    feature = "montgomery-scalars"
+)))]
+define_scalar!(barrett);
+
+/// 2²⁵² mod ℓ, as signed 21 bit digits, i.e. 2²⁵² ≡ Σ FOLD[i]⋅2²¹ⁱ mod ℓ.
+///
+/// Since ℓ = 2²⁵² + c, this is just -c, but written with small digits, so that
+/// we can fold limbs above 2²⁵² back down without overflowing.
+const FOLD: [i64; 6] = [666643, 470296, 654183, -997805, 136657, -683901];
+
+/// Replace limb i ≥ 12 of a radix 2²¹ integer with an equivalent value mod ℓ, in lower limbs.
+#[inline(always)]
+fn fold(s: &mut [i64; 24], i: usize) {
+    for (j, f) in FOLD.iter().enumerate() {
+        s[i - 12 + j] += s[i] * f;
+    }
+    s[i] = 0;
+}
+
+/// Carry limb i into limb i + 1, leaving limb i in [-2²⁰, 2²⁰).
+#[inline(always)]
+fn carry_signed(s: &mut [i64; 24], i: usize) {
+    let c = (s[i] + (1 << 20)) >> 21;
+    s[i + 1] += c;
+    s[i] -= c << 21;
+}
+
+/// Carry limb i into limb i + 1, leaving limb i in [0, 2²¹).
+#[inline(always)]
+fn carry(s: &mut [i64; 24], i: usize) {
+    let c = s[i] >> 21;
+    s[i + 1] += c;
+    s[i] -= c << 21;
+}
+
+/// Reduce a 512 bit integer, given as little endian bytes, mod ℓ.
+///
+/// This is the reduction from the ref10 implementation of Ed25519. We split the input
+/// into 21 bit limbs, and then repeatedly fold the limbs above 2²⁵² back down, using
+/// 2²⁵² ≡ -c mod ℓ. Every step is straight line arithmetic, so this runs in constant time.
+fn reduce_512(bytes: &[u8; 64]) -> [u64; 4] {
+    let mut s = [0i64; 24];
+    for (i, limb) in s.iter_mut().enumerate() {
+        let (start, shift) = (21 * i / 8, 21 * i % 8);
+        let mut buf = [0u8; 8];
+        let end = (start + 8).min(64);
+        buf[..end - start].copy_from_slice(&bytes[start..end]);
+        // The last limb takes all of the remaining 29 bits.
+        let mask = if i < 23 { (1 << 21) - 1 } else { u64::MAX };
+        *limb = ((u64::from_le_bytes(buf) >> shift) & mask) as i64;
+    }
+
+    // Each round of folding shrinks the input by 126 bits, give or take a few
+    // bits for carries, which we need to propagate in between, to avoid overflow.
+    for i in (18..24).rev() {
+        fold(&mut s, i);
+    }
+    for i in (6..17).step_by(2).chain((7..16).step_by(2)) {
+        carry_signed(&mut s, i);
+    }
+    for i in (12..18).rev() {
+        fold(&mut s, i);
+    }
+    for i in (0..11).step_by(2).chain((1..12).step_by(2)) {
+        carry_signed(&mut s, i);
+    }
+    // At this point, only limb 12 can be non zero above 2²⁵², and only a little.
+    // Folding it, and carrying, can leave a small carry in limb 12 again, so we fold twice.
+    fold(&mut s, 12);
+    for i in 0..12 {
+        carry(&mut s, i);
+    }
+    fold(&mut s, 12);
+    for i in 0..11 {
+        carry(&mut s, i);
+    }
+
+    // Each limb is now in [0, 2²¹), and the result is < ℓ.
+    let mut out = [0u64; 4];
+    for (i, &limb) in s[..12].iter().enumerate() {
+        let (j, shift) = (21 * i / 64, 21 * i % 64);
+        out[j] |= (limb as u64) << shift;
+        if shift > 64 - 21 {
+            out[j + 1] |= (limb as u64) >> (64 - shift);
+        }
+    }
+    wipe(&mut s);
+    out
+}
+
+impl Scalar {
+    /// Create a scalar from a 32 byte little endian encoding, reducing it mod ℓ.
+    ///
+    /// All 256 bits are used, so non-canonical encodings are accepted: see
+    /// [`Scalar::from_canonical_bytes`] for a stricter variant.
+    pub fn from_bytes_mod_order(bytes: &[u8; 32]) -> Self {
+        let mut wide = [0u8; 64];
+        wide[..32].copy_from_slice(bytes);
+        let out = Self::from_bytes_mod_order_wide(&wide);
+        wipe(&mut wide);
+        out
+    }
+
+    /// Create a scalar from a 64 byte little endian encoding, reducing it mod ℓ.
+    ///
+    /// This is how the output of a hash function, like SHA-512 in Ed25519, gets
+    /// turned into a scalar. When the input is uniformly random, the result is
+    /// statistically close to uniform.
+    ///
+    /// This gives the same result as [`Scalar::from_bytes_wide`], but takes advantage
+    /// of the special form of ℓ, making it much faster.
+    pub fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Self {
+        let mut limbs = reduce_512(bytes);
+        let out = Self::from_limbs(limbs);
+        wipe(&mut limbs);
+        out
+    }
+
+    /// Generate a uniformly random scalar.
+    ///
+    /// This works by reducing 64 random bytes, so the bias is negligible.
+    #[cfg(feature = "rand_core")]
+    pub fn random(rng: &mut impl CryptoRngCore) -> Self {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        let out = Self::from_bytes_mod_order_wide(&bytes);
+        // Random scalars are often secret, like nonces, so we don't leave the bytes lying around.
+        wipe(&mut bytes);
+        out
+    }
+
+    /// Create a scalar from the output of a hash function with a 64 byte output, like SHA-512.
+    ///
+    /// This finalizes the hash, and reduces its output mod ℓ, so the result is
+    /// statistically close to uniform. See [`hash_to_scalar`] for a variant which
+    /// takes care of domain separation.
+    #[cfg(feature = "digest")]
+    pub fn from_hash<D: Digest<OutputSize = U64>>(hash: D) -> Self {
+        let mut bytes = [0u8; 64];
+        bytes.copy_from_slice(&hash.finalize());
+        let out = Self::from_bytes_mod_order_wide(&bytes);
+        wipe(&mut bytes);
+        out
+    }
+
+    /// Invert every scalar in a slice, in place.
+    ///
+    /// This uses Montgomery's trick, which only requires a single inversion,
+    /// along with 3 multiplications per scalar. Zero scalars are left as zero,
+    /// like with [`Scalar::invert`]. This runs in constant time, with respect to the
+    /// values of the scalars.
+    pub fn batch_invert(scalars: &mut [Self]) {
+        // Zero scalars get replaced with one, so that they don't destroy the product,
+        // and then we zero them again at the end.
+        let mut scratch = Vec::with_capacity(scalars.len());
+        let mut acc = Self::ONE;
+        for x in scalars.iter() {
+            scratch.push(acc);
+            acc *= Self::conditional_select(x, &Self::ONE, x.is_zero());
+        }
+        // acc is now the product of all the (non zero) scalars.
+        acc = acc.invert();
+        // Now, we walk backwards, peeling off one scalar at a time.
+        for (x, prefix) in scalars.iter_mut().rev().zip(scratch.iter().rev()) {
+            let is_zero = x.is_zero();
+            let next_acc = acc * Self::conditional_select(x, &Self::ONE, is_zero);
+            // acc is the inverse of the product up to, and including x, so
+            // multiplying by the product of the scalars before x gives us 1 / x.
+            *x = Self::conditional_select(&(acc * prefix), &Self::ZERO, is_zero);
+            acc = next_acc;
+        }
+        // The prefix products reveal as much as the scalars themselves.
+        wipe(&mut scratch);
+        wipe(&mut acc);
+    }
+    /// Calculate the width w non-adjacent form of this scalar.
+    ///
+    /// This writes the scalar as Σ dᵢ⋅2ⁱ, where each digit dᵢ is either zero, or odd,
+    /// with |dᵢ| < 2ʷ⁻¹, and any w consecutive digits contain at most one non-zero one.
+    /// Scalar multiplication can then get away with a table of odd multiples of a point,
+    /// and few additions.
+    ///
+    /// The width must satisfy 2 ≤ w ≤ 8. This runs in variable time, and should
+    /// only be used on public scalars, like when verifying signatures.
+    pub fn non_adjacent_form(&self, w: usize) -> [i8; 256] {
+        assert!((2..=8).contains(&w), "NAF width must be between 2 and 8");
+        let bytes = self.to_bytes();
+        // We keep an extra zero limb, so that windows can read past the top of the scalar.
+        let mut x = [0u64; 5];
+        for (limb, chunk) in x.iter_mut().zip(bytes.chunks_exact(8)) {
+            // Unwrapping is fine, since each chunk has exactly 8 bytes.
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        let width = 1u64 << w;
+        let mut naf = [0i8; 256];
+        let mut carry = 0;
+        let mut pos = 0;
+        while pos < 256 {
+            let (i, shift) = (pos / 64, pos % 64);
+            let mut bits = x[i] >> shift;
+            if shift > 64 - w {
+                bits |= x[i + 1] << (64 - shift);
+            }
+            let window = carry + (bits & (width - 1));
+            if window & 1 == 0 {
+                // Since the window is even, this digit is zero, and we can move on to the next.
+                pos += 1;
+                continue;
+            }
+            // We pick the digit in (-2ʷ⁻¹, 2ʷ⁻¹) congruent to the window, carrying the
+            // difference into the next window. The next w - 1 digits are then zero.
+            if window < width / 2 {
+                carry = 0;
+                naf[pos] = window as i8;
+            } else {
+                carry = 1;
+                naf[pos] = (window as i64 - width as i64) as i8;
+            }
+            pos += w;
+        }
+        naf
+    }
+
+    /// Write this scalar in radix 16, with signed digits.
+    ///
+    /// This returns digits dᵢ such that the scalar is Σ dᵢ⋅16ⁱ, with -8 ≤ dᵢ < 8,
+    /// except for the last digit, which can also be 8. With signed digits, fixed window
+    /// scalar multiplication only needs a table of 8 multiples of a point, since
+    /// negating a point is cheap.
+    ///
+    /// This runs in constant time.
+    pub fn as_radix_16(&self) -> [i8; 64] {
+        let mut bytes = self.to_bytes();
+        let mut out = [0i8; 64];
+        for (i, &byte) in bytes.iter().enumerate() {
+            out[2 * i] = (byte & 0xF) as i8;
+            out[2 * i + 1] = (byte >> 4) as i8;
+        }
+        // Each digit is now in [0, 16), so we recenter it into [-8, 8), carrying into the
+        // next digit. Since the scalar is < 2²⁵³, the last digit stays ≤ 8.
+        for i in 0..63 {
+            let carry = (out[i] + 8) >> 4;
+            out[i] -= carry << 4;
+            out[i + 1] += carry;
+        }
+        wipe(&mut bytes);
+        out
+    }
+
+    /// Write this scalar in radix 2ʷ, with signed digits.
+    ///
+    /// This returns digits dᵢ such that the scalar is Σ dᵢ⋅2ʷⁱ, with -2ʷ⁻¹ ≤ dᵢ < 2ʷ⁻¹.
+    /// Only the first ⌈256 / w⌉ digits are used, and the rest are zero. Larger windows
+    /// mean fewer digits, which is what bucket based multiscalar multiplication wants.
+    ///
+    /// The width must satisfy 4 ≤ w ≤ 8. This runs in constant time.
+    pub fn as_radix_2w(&self, w: usize) -> [i8; 64] {
+        assert!(
+            (4..=8).contains(&w),
+            "radix 2ʷ width must be between 4 and 8"
+        );
+        let mut bytes = self.to_bytes();
+        // We keep an extra zero limb, so that windows can read past the top of the scalar.
+        let mut x = [0u64; 5];
+        for (limb, chunk) in x.iter_mut().zip(bytes.chunks_exact(8)) {
+            // Unwrapping is fine, since each chunk has exactly 8 bytes.
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        let width = 1u64 << w;
+        let mut out = [0i8; 64];
+        let mut carry = 0;
+        for (i, digit) in out.iter_mut().take(256_usize.div_ceil(w)).enumerate() {
+            let (j, shift) = ((i * w) / 64, (i * w) % 64);
+            let mut bits = x[j] >> shift;
+            if shift > 64 - w {
+                bits |= x[j + 1] << (64 - shift);
+            }
+            // We recenter the window into [-2ʷ⁻¹, 2ʷ⁻¹), carrying into the next one.
+            let window = carry + (bits & (width - 1));
+            carry = (window + width / 2) >> w;
+            *digit = (window as i64 - (carry << w) as i64) as i8;
+        }
+        // Since the scalar is < 2²⁵³, the top window never carries.
+        debug_assert_eq!(carry, 0);
+        wipe(&mut bytes);
+        wipe(&mut x);
+        out
+    }
+
+    /// The bits of this scalar, in little endian order.
+    ///
+    /// Each bit is a [`Choice`], so that using them doesn't require branching.
+    pub fn bits_le(&self) -> [Choice; 256] {
+        let bytes = self.to_bytes();
+        std::array::from_fn(|i| Choice::from((bytes[i / 8] >> (i % 8)) & 1))
+    }
+
+    /// Iterate over the bits of this scalar, starting with the most significant one.
+    ///
+    /// This is the order a Montgomery ladder needs. Every scalar yields exactly 256
+    /// bits, including leading zeros, and each bit is a [`Choice`], so neither the
+    /// iteration, nor using the bits with [`ConditionallySelectable`], branches on them.
+    pub fn iter_bits(&self) -> impl Iterator<Item = Choice> {
+        let bytes = self.to_bytes();
+        (0..256)
+            .rev()
+            .map(move |i| Choice::from((bytes[i / 8] >> (i % 8)) & 1))
+    }
+}
+
+/// Hash a message to a scalar, using a domain separation tag.
+///
+/// Different protocols, or different steps of the same protocol, should use different
+/// domains, so that hashes computed for one purpose can't be reused for another.
+/// The domain is prefixed with its length, so that no choice of domain and message
+/// can collide with another.
+#[cfg(feature = "digest")]
+pub fn hash_to_scalar<D: Digest<OutputSize = U64>>(domain: &[u8], msg: &[u8]) -> Scalar {
+    let mut hash = D::new();
+    hash.update((domain.len() as u64).to_le_bytes());
+    hash.update(domain);
+    hash.update(msg);
+    Scalar::from_hash(hash)
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Scalar {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::util::serialize_bytes(&self.to_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Scalar {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let bytes = crate::util::deserialize_bytes(
+            deserializer,
+            "the canonical 32 byte encoding of a scalar",
+        )?;
+        Option::from(Scalar::from_canonical_bytes(&bytes))
+            .ok_or_else(|| D::Error::custom("scalar encoding is not canonical"))
+    }
+}
+
+#[cfg(feature = "group")]
+impl ff::Field for Scalar {
+    const ZERO: Self = Self::ZERO;
+    const ONE: Self = Self::ONE;
+
+    fn random(mut rng: impl rand_core::RngCore) -> Self {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        let out = Self::from_bytes_mod_order_wide(&bytes);
+        wipe(&mut bytes);
+        out
+    }
+
+    fn square(&self) -> Self {
+        Scalar::square(self)
+    }
+
+    fn double(&self) -> Self {
+        self + self
+    }
+
+    fn invert(&self) -> subtle::CtOption<Self> {
+        subtle::CtOption::new(Scalar::invert(self), !Scalar::is_zero(self))
+    }
+
+    fn sqrt(&self) -> subtle::CtOption<Self> {
+        // (t - 1) / 2, where ℓ - 1 = 2²⋅t.
+        const T_MINUS_ONE_OVER_TWO: [u64; 4] = [
+            0xCB024C634B9EBA7D,
+            0x029BDF3BD45EF39A,
+            0x0000000000000000,
+            0x0200000000000000,
+        ];
+        ff::helpers::sqrt_tonelli_shanks(self, T_MINUS_ONE_OVER_TWO)
+    }
+
+    fn sqrt_ratio(num: &Self, div: &Self) -> (Choice, Self) {
+        ff::helpers::sqrt_ratio_generic(num, div)
+    }
+}
+
+/// ℓ - 1 = 2²⋅t, for an odd t, and 2 generates the multiplicative group.
+#[cfg(feature = "group")]
+impl ff::PrimeField for Scalar {
+    type Repr = [u8; 32];
+
+    const MODULUS: &'static str =
+        "0x1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed";
+    const NUM_BITS: u32 = 253;
+    const CAPACITY: u32 = 252;
+    const TWO_INV: Self = Self::from_limbs([
+        0x2C09318D2E7AE9F7,
+        0x0A6F7CEF517BCE6B,
+        0x0000000000000000,
+        0x0800000000000000,
+    ]);
+    const MULTIPLICATIVE_GENERATOR: Self = Self::from_limbs([2, 0, 0, 0]);
+    const S: u32 = 2;
+    /// 2ᵗ, a primitive 4th root of unity.
+    const ROOT_OF_UNITY: Self = Self::from_limbs([
+        0xBE8775DFEBBE07D4,
+        0x0EF0565342CE83FE,
+        0x7D3D6D60ABC1C27A,
+        0x094A7310E07981E7,
+    ]);
+    const ROOT_OF_UNITY_INV: Self = Self::from_limbs([
+        0x998AED3A7137CC19,
+        0x05EEA38B602918D7,
+        0x82C2929F543E3D86,
+        0x06B58CEF1F867E18,
+    ]);
+    /// 2⁴ = 16, the generator raised to 2ˢ.
+    const DELTA: Self = Self::from_limbs([16, 0, 0, 0]);
+
+    fn from_repr(repr: Self::Repr) -> subtle::CtOption<Self> {
+        Self::from_canonical_bytes(&repr)
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        self.to_bytes()
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from(self.to_bytes()[0] & 1)
+    }
+}
@@ -0,0 +1,108 @@
+//! Traits shared by the different kinds of points.
+use std::borrow::Borrow;
+
+use super::scalar::Scalar;
+
+/// Multiplying several points by several scalars at once, and summing the results.
+///
+/// Computing Σ aᵢ⋅Pᵢ this way is much faster than multiplying each point separately,
+/// since the doublings can be shared between all of the points.
+pub trait MultiscalarMul {
+    /// The type of point being multiplied.
+    type Point;
+
+    /// Calculate Σ aᵢ⋅Pᵢ, for scalars aᵢ and points Pᵢ.
+    ///
+    /// This panics if the number of scalars and points differ.
+    ///
+    /// # Timing
+    ///
+    /// This runs in constant time, with respect to the values of the scalars and points,
+    /// which can thus be secret, e.g. blinding factors in commitments. Only their number
+    /// can leak through timing. Secret intermediate values, like the digits of the scalars,
+    /// get wiped if the `zeroize` feature is enabled.
+    ///
+    /// This comes at a cost: no additions get skipped, and the algorithms that are
+    /// fastest with many points don't apply, so this can be several times slower than
+    /// [`VartimeMultiscalarMul::vartime_multiscalar_mul`], which should be preferred
+    /// with public inputs.
+    fn multiscalar_mul<I, J>(scalars: I, points: J) -> Self::Point
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self::Point>;
+}
+
+/// Multiplying several points by several public scalars at once, and summing the results.
+///
+/// This is like [`MultiscalarMul`], except faster, because the running time depends
+/// on the scalars and points. This should only be used with public inputs, like when
+/// verifying signatures, or commitments.
+pub trait VartimeMultiscalarMul {
+    /// The type of point being multiplied.
+    type Point;
+
+    /// Calculate Σ aᵢ⋅Pᵢ, for scalars aᵢ and points Pᵢ.
+    ///
+    /// This panics if the number of scalars and points differ. This runs in variable time.
+    fn vartime_multiscalar_mul<I, J>(scalars: I, points: J) -> Self::Point
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self::Point>;
+}
+
+/// Multiplying a fixed set of points, and some other points, by public scalars at once.
+///
+/// This precomputes tables for some "static" points, ahead of time, which then get
+/// reused every time they're multiplied, along with some "dynamic" points, which can
+/// change between calls. This pays off when the same points appear in many
+/// equations, like generators when verifying commitments, or proofs.
+pub trait VartimePrecomputedMultiscalarMul: Sized {
+    /// The type of point being multiplied.
+    type Point;
+
+    /// Precompute the tables for some static points.
+    fn new<I>(static_points: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Self::Point>;
+
+    /// Calculate Σ aᵢ⋅Pᵢ, for scalars aᵢ, and the static points Pᵢ.
+    ///
+    /// This panics if the number of scalars differs from the number of static points.
+    /// This runs in variable time.
+    fn vartime_multiscalar_mul<I>(&self, static_scalars: I) -> Self::Point
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+    {
+        self.vartime_mixed_multiscalar_mul(
+            static_scalars,
+            std::iter::empty::<Scalar>(),
+            std::iter::empty::<Self::Point>(),
+        )
+    }
+
+    /// Calculate Σ aᵢ⋅Pᵢ + Σ bⱼ⋅Qⱼ, for scalars aᵢ and bⱼ, the static points Pᵢ,
+    /// and dynamic points Qⱼ.
+    ///
+    /// This panics if the number of static scalars differs from the number of static
+    /// points, or if the number of dynamic scalars differs from the number of dynamic
+    /// points. This runs in variable time.
+    fn vartime_mixed_multiscalar_mul<I, J, K>(
+        &self,
+        static_scalars: I,
+        dynamic_scalars: J,
+        dynamic_points: K,
+    ) -> Self::Point
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Scalar>,
+        K: IntoIterator,
+        K::Item: Borrow<Self::Point>;
+}
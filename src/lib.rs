@@ -1 +1,11 @@
+#[macro_use]
+mod macros;
+
+mod arithmetic;
+
 pub mod curve;
+#[cfg(feature = "ed25519")]
+pub mod sign;
+#[doc(hidden)]
+pub mod testing;
+mod util;
@@ -0,0 +1,509 @@
+//! Macros to cut down on the boilerplate needed to implement operators.
+
+/// Define the by-value variants of an operator, given its by-reference implementations.
+///
+/// This expects that `$Op<&T> for &T` and `$OpAssign<&T> for T` have already been
+/// implemented, and then defines all of the other combinations of owned and borrowed
+/// operands, by delegating to those implementations.
+macro_rules! define_op_variants {
+    (impl $Op:ident, $op:ident, $OpAssign:ident, $op_assign:ident for $T:ty) => {
+        impl ::std::ops::$Op<&$T> for $T {
+            type Output = $T;
+
+            fn $op(self, other: &$T) -> Self::Output {
+                (&self).$op(other)
+            }
+        }
+
+        impl ::std::ops::$Op<$T> for &$T {
+            type Output = $T;
+
+            fn $op(self, other: $T) -> Self::Output {
+                self.$op(&other)
+            }
+        }
+
+        impl ::std::ops::$Op<$T> for $T {
+            type Output = $T;
+
+            fn $op(self, other: $T) -> Self::Output {
+                (&self).$op(&other)
+            }
+        }
+
+        impl ::std::ops::$OpAssign<$T> for $T {
+            fn $op_assign(&mut self, other: $T) {
+                self.$op_assign(&other)
+            }
+        }
+    };
+}
+
+/// Define a prime field, given its modulus, as a number of 64 bit limbs.
+///
+/// Elements are always kept fully reduced, using the generic routines in
+/// [`crate::arithmetic`]. This makes no assumptions about the form of the modulus,
+/// beyond it being odd, and larger than 2, so it's much slower than a field with a
+/// hand written backend, like [`Fp`](crate::curve::field::Fp). Inversion uses safegcd,
+/// which limits the modulus to 4 limbs.
+///
+/// The reduction strategy used for multiplication is one of:
+/// - `bitwise`, which reduces the full product one bit at a time. This is simple, but slow.
+/// - `montgomery`, which stores elements in Montgomery form, x⋅2⁶⁴ᴺ mod m, making
+///   multiplication much faster, at the cost of converting elements in and out.
+/// - `montgomery32`, which uses the same representation as `montgomery`, but multiplies
+///   with 32 bit words, for targets without fast 64 × 64 → 128 bit multiplication.
+/// - `barrett`, which reduces the full product with a couple of multiplications by a
+///   precomputed constant. This is also much faster than `bitwise`, without needing
+///   a special representation, but needs the top limb of the modulus to be non-zero.
+macro_rules! define_prime_field {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $Name:ident([u64; $n:literal]);
+        modulus = $modulus:expr;
+        reduction = $reduction:ident;
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy)]
+        $vis struct $Name {
+            limbs: [u64; $n],
+        }
+
+        impl $Name {
+            /// The modulus of this field, as limbs in little endian order.
+            pub const MODULUS: [u64; $n] = $modulus;
+            /// The additive identity.
+            pub const ZERO: Self = Self { limbs: [0; $n] };
+            /// The multiplicative identity.
+            pub const ONE: Self = {
+                let mut limbs = [0; $n];
+                limbs[0] = 1;
+                Self::from_limbs(limbs)
+            };
+
+            define_prime_field!(@$reduction $n);
+
+            /// Create an element directly from its limbs, in little endian order.
+            ///
+            /// The limbs must represent an integer smaller than the modulus.
+            /// This is intended for defining constants.
+            pub(crate) const fn from_limbs(limbs: [u64; $n]) -> Self {
+                Self { limbs: Self::to_repr(&limbs) }
+            }
+
+            /// Create an element from its canonical little endian encoding.
+            ///
+            /// This fails if the encoding represents an integer which isn't smaller than
+            /// the modulus. Whether or not this fails is only revealed through the
+            /// returned `CtOption`, so this runs in constant time.
+            pub fn from_canonical_bytes(bytes: &[u8; 8 * $n]) -> ::subtle::CtOption<Self> {
+                let mut limbs = [0u64; $n];
+                for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+                    // Unwrapping is fine, since each chunk has exactly 8 bytes.
+                    *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+                }
+                let (_, borrow) = $crate::arithmetic::sub(&limbs, &Self::MODULUS);
+                ::subtle::CtOption::new(Self::from_limbs(limbs), ::subtle::Choice::from(borrow))
+            }
+
+            /// Create an element from a little endian encoding twice as long as the
+            /// canonical one, reducing it mod the modulus.
+            ///
+            /// When the input is uniformly random, the result is statistically close to uniform.
+            pub fn from_bytes_wide(bytes: &[u8; 16 * $n]) -> Self {
+                let mut wide = [0u64; 2 * $n];
+                for (limb, chunk) in wide.iter_mut().zip(bytes.chunks_exact(8)) {
+                    // Unwrapping is fine, since each chunk has exactly 8 bytes.
+                    *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+                }
+                let out = Self::from_limbs($crate::arithmetic::reduce_wide(&wide, &Self::MODULUS));
+                $crate::util::wipe(&mut wide);
+                out
+            }
+
+            /// Convert this element to its canonical little endian encoding.
+            pub fn to_bytes(&self) -> [u8; 8 * $n] {
+                let mut out = [0u8; 8 * $n];
+                for (chunk, limb) in out.chunks_exact_mut(8).zip(Self::from_repr(&self.limbs)) {
+                    chunk.copy_from_slice(&limb.to_le_bytes());
+                }
+                out
+            }
+
+            /// Check whether or not this element is zero, in constant time.
+            pub fn is_zero(&self) -> ::subtle::Choice {
+                ::subtle::ConstantTimeEq::ct_eq(self, &Self::ZERO)
+            }
+
+            /// Calculate the square of this element.
+            pub fn square(&self) -> Self {
+                self * self
+            }
+
+            /// Calculate a⋅b + c.
+            ///
+            /// This can be cheaper than a multiplication followed by an addition, since
+            /// depending on the reduction strategy, c can be added to the full product,
+            /// before it gets reduced.
+            pub fn mul_add(a: &Self, b: &Self, c: &Self) -> Self {
+                Self {
+                    limbs: Self::mul_add_repr(&a.limbs, &b.limbs, &c.limbs),
+                }
+            }
+
+            /// Raise this element to a power, given as little endian limbs.
+            ///
+            /// This runs in constant time with respect to self, but not the exponent,
+            /// which should be public.
+            pub fn pow_vartime(&self, exp: &[u64]) -> Self {
+                let mut out = Self::ONE;
+                for limb in exp.iter().rev() {
+                    for j in (0..64).rev() {
+                        out = out.square();
+                        if (limb >> j) & 1 == 1 {
+                            out *= self;
+                        }
+                    }
+                }
+                out
+            }
+
+            /// Calculate the multiplicative inverse of this element.
+            ///
+            /// This uses Bernstein and Yang's safegcd algorithm, in constant time.
+            /// The inverse of zero is defined to be zero.
+            pub fn invert(&self) -> Self {
+                const MODULUS: $crate::arithmetic::safegcd::Modulus =
+                    $crate::arithmetic::safegcd::Modulus::new(&$Name::MODULUS);
+                let mut limbs = Self::from_repr(&self.limbs);
+                let out = Self::from_limbs($crate::arithmetic::safegcd::invert(&limbs, &MODULUS));
+                $crate::util::wipe(&mut limbs);
+                out
+            }
+
+            /// Calculate the multiplicative inverse of this element, in variable time.
+            ///
+            /// This is faster than [`Self::invert`], but leaks information about this
+            /// element through timing, so it should only be used on public values.
+            /// The inverse of zero is defined to be zero.
+            pub fn vartime_invert(&self) -> Self {
+                let limbs = Self::from_repr(&self.limbs);
+                Self::from_limbs($crate::arithmetic::invert_vartime(&limbs, &Self::MODULUS))
+            }
+        }
+
+        #[cfg(feature = "zeroize")]
+        impl ::zeroize::Zeroize for $Name {
+            fn zeroize(&mut self) {
+                ::zeroize::Zeroize::zeroize(&mut self.limbs);
+            }
+        }
+
+        impl ::std::fmt::Debug for $Name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                // Like with Fp, we only print out the value of elements if asked to.
+                #[cfg(any(test, feature = "debug-secrets"))]
+                {
+                    write!(f, "{}(0x", stringify!($Name))?;
+                    for x in self.to_bytes().iter().rev() {
+                        write!(f, "{:02X}", x)?;
+                    }
+                    write!(f, ")")
+                }
+                #[cfg(not(any(test, feature = "debug-secrets")))]
+                {
+                    write!(f, "{}(<redacted>)", stringify!($Name))
+                }
+            }
+        }
+
+        impl From<u64> for $Name {
+            fn from(x: u64) -> Self {
+                let mut wide = [0u64; 2 * $n];
+                wide[0] = x;
+                Self::from_limbs($crate::arithmetic::reduce_wide(&wide, &Self::MODULUS))
+            }
+        }
+
+        impl From<u128> for $Name {
+            fn from(x: u128) -> Self {
+                let mut wide = [0u64; 2 * $n];
+                wide[0] = x as u64;
+                wide[1] = (x >> 64) as u64;
+                Self::from_limbs($crate::arithmetic::reduce_wide(&wide, &Self::MODULUS))
+            }
+        }
+
+        impl ::subtle::ConditionallySelectable for $Name {
+            fn conditional_select(a: &Self, b: &Self, choice: ::subtle::Choice) -> Self {
+                let mut out = Self::ZERO;
+                for ((o, x), y) in out.limbs.iter_mut().zip(a.limbs.iter()).zip(b.limbs.iter()) {
+                    *o = <u64 as ::subtle::ConditionallySelectable>::conditional_select(x, y, choice);
+                }
+                out
+            }
+        }
+
+        impl ::subtle::ConstantTimeEq for $Name {
+            fn ct_eq(&self, other: &Self) -> ::subtle::Choice {
+                // Elements are always fully reduced, so we can compare limbs directly.
+                ::subtle::ConstantTimeEq::ct_eq(&self.limbs[..], &other.limbs[..])
+            }
+        }
+
+        // Like with Fp, we avoid implementing equality, since it's tempting to use it
+        // on secrets, but the traits of the ff crate need it.
+        #[cfg(feature = "group")]
+        impl PartialEq for $Name {
+            fn eq(&self, other: &Self) -> bool {
+                ::subtle::ConstantTimeEq::ct_eq(self, other).into()
+            }
+        }
+
+        #[cfg(feature = "group")]
+        impl Eq for $Name {}
+
+        impl Default for $Name {
+            fn default() -> Self {
+                Self::ZERO
+            }
+        }
+
+        impl ::std::ops::AddAssign<&$Name> for $Name {
+            fn add_assign(&mut self, other: &$Name) {
+                self.limbs = $crate::arithmetic::add_mod(&self.limbs, &other.limbs, &Self::MODULUS);
+            }
+        }
+
+        impl ::std::ops::Add<&$Name> for &$Name {
+            type Output = $Name;
+
+            fn add(self, other: &$Name) -> Self::Output {
+                let mut out = *self;
+                out += other;
+                out
+            }
+        }
+
+        define_op_variants!(impl Add, add, AddAssign, add_assign for $Name);
+
+        impl ::std::ops::SubAssign<&$Name> for $Name {
+            fn sub_assign(&mut self, other: &$Name) {
+                self.limbs = $crate::arithmetic::sub_mod(&self.limbs, &other.limbs, &Self::MODULUS);
+            }
+        }
+
+        impl ::std::ops::Sub<&$Name> for &$Name {
+            type Output = $Name;
+
+            fn sub(self, other: &$Name) -> Self::Output {
+                let mut out = *self;
+                out -= other;
+                out
+            }
+        }
+
+        define_op_variants!(impl Sub, sub, SubAssign, sub_assign for $Name);
+
+        impl ::std::ops::MulAssign<&$Name> for $Name {
+            fn mul_assign(&mut self, other: &$Name) {
+                self.limbs = Self::mul_repr(&self.limbs, &other.limbs);
+            }
+        }
+
+        impl ::std::ops::Mul<&$Name> for &$Name {
+            type Output = $Name;
+
+            fn mul(self, other: &$Name) -> Self::Output {
+                let mut out = *self;
+                out *= other;
+                out
+            }
+        }
+
+        define_op_variants!(impl Mul, mul, MulAssign, mul_assign for $Name);
+
+        impl ::std::iter::Sum for $Name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, |acc, x| acc + x)
+            }
+        }
+
+        impl<'a> ::std::iter::Sum<&'a $Name> for $Name {
+            fn sum<I: Iterator<Item = &'a $Name>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, |acc, x| acc + x)
+            }
+        }
+
+        impl ::std::iter::Product for $Name {
+            fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::ONE, |acc, x| acc * x)
+            }
+        }
+
+        impl<'a> ::std::iter::Product<&'a $Name> for $Name {
+            fn product<I: Iterator<Item = &'a $Name>>(iter: I) -> Self {
+                iter.fold(Self::ONE, |acc, x| acc * x)
+            }
+        }
+
+        impl ::std::ops::Neg for $Name {
+            type Output = Self;
+
+            fn neg(self) -> Self::Output {
+                Self::ZERO - self
+            }
+        }
+
+        impl ::std::ops::Neg for &$Name {
+            type Output = $Name;
+
+            fn neg(self) -> Self::Output {
+                -*self
+            }
+        }
+    };
+    // Elements are stored as is, and products are reduced one bit at a time.
+    (@bitwise $n:literal) => {
+        /// Convert canonical limbs into our internal representation.
+        #[inline(always)]
+        const fn to_repr(limbs: &[u64; $n]) -> [u64; $n] {
+            *limbs
+        }
+
+        /// Convert our internal representation back into canonical limbs.
+        #[inline(always)]
+        fn from_repr(limbs: &[u64; $n]) -> [u64; $n] {
+            *limbs
+        }
+
+        /// Multiply two elements in our internal representation.
+        #[inline(always)]
+        fn mul_repr(a: &[u64; $n], b: &[u64; $n]) -> [u64; $n] {
+            let mut wide = [0u64; 2 * $n];
+            $crate::arithmetic::mul_wide(a, b, &mut wide);
+            let out = $crate::arithmetic::reduce_wide(&wide, &Self::MODULUS);
+            $crate::util::wipe(&mut wide);
+            out
+        }
+        /// Calculate a⋅b + c in our internal representation, with a single reduction.
+        #[inline(always)]
+        fn mul_add_repr(a: &[u64; $n], b: &[u64; $n], c: &[u64; $n]) -> [u64; $n] {
+            let mut wide = [0u64; 2 * $n];
+            $crate::arithmetic::mul_wide(a, b, &mut wide);
+            $crate::arithmetic::add_into(&mut wide, c);
+            let out = $crate::arithmetic::reduce_wide(&wide, &Self::MODULUS);
+            $crate::util::wipe(&mut wide);
+            out
+        }
+    };
+    // Like the montgomery strategy, but multiplying 32 bit words at a time.
+    (@montgomery32 $n:literal) => {
+        /// -m⁻¹ mod 2⁶⁴, whose low 32 bits Montgomery reduction needs.
+        const M_INV: u64 = $crate::arithmetic::neg_inv64(Self::MODULUS[0]);
+        /// R² mod m, which we multiply by to convert elements into Montgomery form.
+        const R2: [u64; $n] = $crate::arithmetic::pow2_mod(2 * 64 * $n, &Self::MODULUS);
+
+        /// Convert canonical limbs into our internal representation.
+        #[inline(always)]
+        const fn to_repr(limbs: &[u64; $n]) -> [u64; $n] {
+            $crate::arithmetic::mont_mul32(limbs, &Self::R2, &Self::MODULUS, Self::M_INV as u32)
+        }
+
+        /// Convert our internal representation back into canonical limbs.
+        #[inline(always)]
+        fn from_repr(limbs: &[u64; $n]) -> [u64; $n] {
+            let mut one = [0u64; $n];
+            one[0] = 1;
+            $crate::arithmetic::mont_mul32(limbs, &one, &Self::MODULUS, Self::M_INV as u32)
+        }
+
+        /// Multiply two elements in our internal representation.
+        #[inline(always)]
+        fn mul_repr(a: &[u64; $n], b: &[u64; $n]) -> [u64; $n] {
+            $crate::arithmetic::mont_mul32(a, b, &Self::MODULUS, Self::M_INV as u32)
+        }
+        /// Calculate a⋅b + c in our internal representation.
+        ///
+        /// Montgomery multiplication interleaves the reduction with the product, so
+        /// there's no full product to add c into. Instead, we add it afterwards.
+        #[inline(always)]
+        fn mul_add_repr(a: &[u64; $n], b: &[u64; $n], c: &[u64; $n]) -> [u64; $n] {
+            $crate::arithmetic::add_mod(&Self::mul_repr(a, b), c, &Self::MODULUS)
+        }
+    };
+    // Elements are stored as is, and products are reduced using Barrett reduction.
+    (@barrett $n:literal) => {
+        /// ⌊2¹²⁸ᴺ / m⌋, split into its low limbs and its top limb, which Barrett reduction needs.
+        const MU: ([u64; $n], u64) = $crate::arithmetic::barrett_mu(&Self::MODULUS);
+
+        /// Convert canonical limbs into our internal representation.
+        #[inline(always)]
+        const fn to_repr(limbs: &[u64; $n]) -> [u64; $n] {
+            *limbs
+        }
+
+        /// Convert our internal representation back into canonical limbs.
+        #[inline(always)]
+        fn from_repr(limbs: &[u64; $n]) -> [u64; $n] {
+            *limbs
+        }
+
+        /// Multiply two elements in our internal representation.
+        #[inline(always)]
+        fn mul_repr(a: &[u64; $n], b: &[u64; $n]) -> [u64; $n] {
+            let mut wide = [0u64; 2 * $n];
+            $crate::arithmetic::mul_wide(a, b, &mut wide);
+            let out = $crate::arithmetic::barrett_reduce(&wide, &Self::MODULUS, &Self::MU);
+            $crate::util::wipe(&mut wide);
+            out
+        }
+        /// Calculate a⋅b + c in our internal representation, with a single reduction.
+        #[inline(always)]
+        fn mul_add_repr(a: &[u64; $n], b: &[u64; $n], c: &[u64; $n]) -> [u64; $n] {
+            let mut wide = [0u64; 2 * $n];
+            $crate::arithmetic::mul_wide(a, b, &mut wide);
+            // a⋅b + c < m² + m < 2¹²⁸ᴺ, which is all Barrett reduction needs.
+            $crate::arithmetic::add_into(&mut wide, c);
+            let out = $crate::arithmetic::barrett_reduce(&wide, &Self::MODULUS, &Self::MU);
+            $crate::util::wipe(&mut wide);
+            out
+        }
+    };
+    // Elements x are stored as x⋅R mod m, with R = 2⁶⁴ᴺ, so that multiplying x⋅R by y⋅R,
+    // and then dividing by R with Montgomery reduction, gives us (x⋅y)⋅R.
+    (@montgomery $n:literal) => {
+        /// -m⁻¹ mod 2⁶⁴, which Montgomery reduction needs.
+        const M_INV: u64 = $crate::arithmetic::neg_inv64(Self::MODULUS[0]);
+        /// R² mod m, which we multiply by to convert elements into Montgomery form.
+        const R2: [u64; $n] = $crate::arithmetic::pow2_mod(2 * 64 * $n, &Self::MODULUS);
+
+        /// Convert canonical limbs into our internal representation.
+        #[inline(always)]
+        const fn to_repr(limbs: &[u64; $n]) -> [u64; $n] {
+            $crate::arithmetic::mont_mul(limbs, &Self::R2, &Self::MODULUS, Self::M_INV)
+        }
+
+        /// Convert our internal representation back into canonical limbs.
+        #[inline(always)]
+        fn from_repr(limbs: &[u64; $n]) -> [u64; $n] {
+            let mut one = [0u64; $n];
+            one[0] = 1;
+            $crate::arithmetic::mont_mul(limbs, &one, &Self::MODULUS, Self::M_INV)
+        }
+
+        /// Multiply two elements in our internal representation.
+        #[inline(always)]
+        fn mul_repr(a: &[u64; $n], b: &[u64; $n]) -> [u64; $n] {
+            $crate::arithmetic::mont_mul(a, b, &Self::MODULUS, Self::M_INV)
+        }
+        /// Calculate a⋅b + c in our internal representation.
+        ///
+        /// Montgomery multiplication interleaves the reduction with the product, so
+        /// there's no full product to add c into. Instead, we add it afterwards.
+        #[inline(always)]
+        fn mul_add_repr(a: &[u64; $n], b: &[u64; $n], c: &[u64; $n]) -> [u64; $n] {
+            $crate::arithmetic::add_mod(&Self::mul_repr(a, b), c, &Self::MODULUS)
+        }
+    };
+}
@@ -0,0 +1,194 @@
+//! Verifying many signatures at once.
+use std::fmt::Display;
+
+use sha2::{Digest, Sha512};
+
+use super::{keys::challenge, Signature, VerifyingKey};
+use crate::curve::{
+    edwards::{EdwardsPoint, ED25519_BASEPOINT_POINT},
+    scalar::Scalar,
+    traits::VartimeMultiscalarMul,
+};
+
+/// Separates the hash deriving the weights of a batch from any other use of SHA-512.
+const BATCH_DST: &[u8] = b"ck-dodo ed25519 batch verification";
+
+/// The error returned when a batch of signatures fails to verify.
+///
+/// This holds the indices of the invalid signatures, in increasing order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchError {
+    invalid: Vec<usize>,
+}
+
+impl BatchError {
+    /// The indices of the signatures which failed to verify, in increasing order.
+    pub fn invalid_indices(&self) -> &[usize] {
+        &self.invalid
+    }
+}
+
+impl Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid signatures in batch, at indices {:?}",
+            self.invalid
+        )
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+/// A signature which was decoded successfully, along with what's needed to check it.
+struct Entry {
+    index: usize,
+    z: Scalar,
+    s: Scalar,
+    k: Scalar,
+    r: EdwardsPoint,
+    a: EdwardsPoint,
+}
+
+/// Check the weighted sum of the equations of some entries, with one multiscalar
+/// multiplication.
+fn check(entries: &[Entry]) -> bool {
+    let b_coefficient = -entries.iter().map(|e| e.z * e.s).sum::<Scalar>();
+    let scalars = std::iter::once(b_coefficient)
+        .chain(entries.iter().map(|e| e.z))
+        .chain(entries.iter().map(|e| e.z * e.k));
+    let points = std::iter::once(ED25519_BASEPOINT_POINT)
+        .chain(entries.iter().map(|e| e.r))
+        .chain(entries.iter().map(|e| e.a));
+    let check = EdwardsPoint::vartime_multiscalar_mul(scalars, points);
+    bool::from(check.mul_by_cofactor().is_identity())
+}
+
+/// Check the equation of a single entry, multiplied by the cofactor, like [`check`].
+fn check_one(entry: &Entry) -> bool {
+    let check =
+        EdwardsPoint::vartime_double_scalar_mul_basepoint(&entry.k, &-entry.a, &entry.s) - entry.r;
+    bool::from(check.mul_by_cofactor().is_identity())
+}
+
+/// Batches of at most this many entries get checked one entry at a time when bisecting,
+/// since splitting them further would take about as many checks as there are entries.
+const BISECT_THRESHOLD: usize = 4;
+
+/// Find the invalid entries of a batch which failed its check, by splitting it in half,
+/// and checking each half in turn.
+fn bisect(entries: &[Entry], invalid: &mut Vec<usize>) {
+    if entries.len() <= BISECT_THRESHOLD {
+        invalid.extend(entries.iter().filter(|e| !check_one(e)).map(|e| e.index));
+        return;
+    }
+    let (left, right) = entries.split_at(entries.len() / 2);
+    for half in [left, right] {
+        if half.len() <= BISECT_THRESHOLD || !check(half) {
+            bisect(half, invalid);
+        }
+    }
+}
+
+/// Verify many signatures at once, each on its own message, under its own key.
+///
+/// The ith signature gets checked against the ith message and key. Rather than checking
+/// each equation sᵢ⋅B = Rᵢ + kᵢ⋅Aᵢ separately, we pick 128 bit weights zᵢ, and check the
+/// sum of the equations:
+///
+/// 8⋅(-(Σ zᵢ⋅sᵢ)⋅B + Σ zᵢ⋅Rᵢ + Σ (zᵢ⋅kᵢ)⋅Aᵢ) = 0
+///
+/// using a single multiscalar multiplication. For batches of hundreds of signatures,
+/// this is about two and a half times faster than verifying each signature in turn,
+/// with decompressing each Rᵢ taking up a quarter of the time left. If any signature
+/// is invalid, this check fails, except with probability 2⁻¹²⁸.
+///
+/// Rather than sampling the weights at random, we derive them by hashing the entire
+/// batch: the index, R, A, challenge, and s of every signature. This is sound as long
+/// as SHA-512 behaves like a random function. Multiplying by the cofactor puts each
+/// term 8⋅(-sᵢ⋅B + Rᵢ + kᵢ⋅Aᵢ) in the subgroup of order ℓ, making it eᵢ⋅(8⋅B) for some
+/// eᵢ mod ℓ, which is fixed by the hashed inputs, and 0 exactly when the signature is
+/// valid. The check passes when Σ zᵢ⋅eᵢ ≡ 0 mod ℓ, so if some eⱼ ≠ 0, then whatever
+/// the other weights are, only one value of zⱼ passes. Since zⱼ is 128 bits of a hash
+/// of everything determining eⱼ, changing the batch to try again changes zⱼ too, and
+/// each attempt succeeds with probability at most 2⁻¹²⁸, like with random weights.
+///
+/// Unlike [`VerifyingKey::verify`], this multiplies the equation by the cofactor, which
+/// is needed for the random linear combination to be sound. This means that a signature
+/// whose equation only holds up to a point of small order passes here, but not when
+/// verified on its own. Honestly generated signatures pass both checks.
+///
+/// If the batch fails, this splits it in half, checking each half in the same way,
+/// until every invalid signature has been isolated, and returns their indices in the
+/// error. The halves checked along the way add up to about twice the batch for each
+/// invalid signature, so this is only faster than verifying every signature separately
+/// when few of them are invalid. A signature is also invalid if its R isn't the
+/// canonical encoding of a point, or if its s ≥ ℓ.
+///
+/// This panics if the number of messages, signatures, and keys differ. This runs in
+/// variable time, but only handles public data.
+pub fn verify_batch(
+    messages: &[&[u8]],
+    signatures: &[Signature],
+    keys: &[VerifyingKey],
+) -> Result<(), BatchError> {
+    assert_eq!(
+        messages.len(),
+        signatures.len(),
+        "the number of messages and signatures should match"
+    );
+    assert_eq!(
+        messages.len(),
+        keys.len(),
+        "the number of messages and keys should match"
+    );
+    let n = messages.len();
+
+    let mut invalid = Vec::new();
+    let mut entries = Vec::with_capacity(n);
+    let mut transcript = Sha512::new_with_prefix(BATCH_DST);
+    transcript.update((n as u64).to_le_bytes());
+    for (index, ((msg, signature), key)) in messages.iter().zip(signatures).zip(keys).enumerate() {
+        let s = Option::<Scalar>::from(Scalar::from_canonical_bytes(&signature.s));
+        let r = Option::<EdwardsPoint>::from(signature.r.decompress());
+        let (Some(s), Some(r)) = (s, r) else {
+            invalid.push(index);
+            continue;
+        };
+        let k = challenge(None, &signature.r, &key.compressed, msg);
+        // The challenge binds the message, which we don't need to hash again.
+        transcript.update((index as u64).to_le_bytes());
+        transcript.update(signature.r.as_bytes());
+        transcript.update(key.compressed.as_bytes());
+        transcript.update(k.to_bytes());
+        transcript.update(signature.s);
+        entries.push(Entry {
+            index,
+            z: Scalar::ZERO,
+            s,
+            k,
+            r,
+            a: key.point,
+        });
+    }
+    let seed = transcript.finalize();
+    for entry in &mut entries {
+        let hash = Sha512::new()
+            .chain_update(seed)
+            .chain_update((entry.index as u64).to_le_bytes())
+            .finalize();
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(&hash[..16]);
+        entry.z = Scalar::from_bytes_mod_order(&bytes);
+    }
+
+    if !check(&entries) {
+        bisect(&entries, &mut invalid);
+    }
+    if invalid.is_empty() {
+        Ok(())
+    } else {
+        invalid.sort_unstable();
+        Err(BatchError { invalid })
+    }
+}
@@ -0,0 +1,460 @@
+//! Signing keys, and the keys verifying their signatures.
+use std::{
+    fmt::{Debug, Display, LowerHex},
+    hash::{Hash, Hasher},
+};
+
+#[cfg(feature = "rand_core")]
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha512};
+
+use super::{Signature, SignatureError};
+use crate::{
+    curve::{
+        edwards::{CompressedEdwardsY, EdwardsPoint, ED25519_BASEPOINT_TABLE},
+        scalar::Scalar,
+    },
+    util::{wipe, write_hex},
+};
+
+/// The prefix used to separate Ed25519ctx from other variants of Ed25519.
+const DOM2_PREFIX: &[u8] = b"SigEd25519 no Ed25519 collisions";
+
+/// Check that a context string can be used with Ed25519ctx.
+///
+/// RFC 8032 limits contexts to 255 bytes, and says they shouldn't be empty. We enforce
+/// both, so that Ed25519ctx never silently overlaps with plain Ed25519.
+fn check_context(context: &[u8]) -> Result<&[u8], SignatureError> {
+    if context.is_empty() || context.len() > 255 {
+        return Err(SignatureError::InvalidContext);
+    }
+    Ok(context)
+}
+
+/// Start a hash, absorbing dom2(0, context) first, if we're using Ed25519ctx.
+///
+/// This is the framing of RFC 8032, Section 5.1: the prefix, a byte holding the flag
+/// for prehashing, which we never do, and a byte holding the length of the context,
+/// followed by the context itself. Plain Ed25519 uses no prefix at all.
+fn dom2(context: Option<&[u8]>) -> Sha512 {
+    let mut hash = Sha512::new();
+    if let Some(context) = context {
+        hash.update(DOM2_PREFIX);
+        hash.update([0, context.len() as u8]);
+        hash.update(context);
+    }
+    hash
+}
+
+/// Hash the encodings of R, and A, along with the message, into the challenge k.
+pub(super) fn challenge(
+    context: Option<&[u8]>,
+    r: &CompressedEdwardsY,
+    a: &CompressedEdwardsY,
+    msg: &[u8],
+) -> Scalar {
+    Scalar::from_hash(
+        dom2(context)
+            .chain_update(r.as_bytes())
+            .chain_update(a.as_bytes())
+            .chain_update(msg),
+    )
+}
+
+/// A key verifying Ed25519 signatures, i.e. a public key.
+///
+/// This holds the encoding of a point A, which is what gets hashed when signing, and
+/// verifying, along with the decoded point.
+#[derive(Clone, Copy)]
+pub struct VerifyingKey {
+    pub(super) compressed: CompressedEdwardsY,
+    pub(super) point: EdwardsPoint,
+}
+
+impl VerifyingKey {
+    /// Read a verifying key from its 32 byte encoding.
+    ///
+    /// This fails if the bytes aren't the canonical encoding of a point, as checked by
+    /// [`CompressedEdwardsY::decompress`]. Like RFC 8032, this accepts points of small
+    /// order, and points outside of the prime order subgroup.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self, SignatureError> {
+        let compressed = CompressedEdwardsY(*bytes);
+        let point = Option::from(compressed.decompress()).ok_or(SignatureError::InvalidKey)?;
+        Ok(Self { compressed, point })
+    }
+
+    /// Read a verifying key from its 32 byte encoding, following the rules of ZIP-215.
+    ///
+    /// Unlike [`Self::from_bytes`], this accepts non-canonical encodings, as decoded by
+    /// [`CompressedEdwardsY::decompress_non_canonical`], and only fails if the bytes
+    /// don't encode a point at all. The key remembers its original encoding, which is
+    /// what gets hashed when verifying, and compared when checking equality, so two
+    /// encodings of the same point give different keys.
+    pub fn from_bytes_zip215(bytes: &[u8; 32]) -> Result<Self, SignatureError> {
+        let compressed = CompressedEdwardsY(*bytes);
+        let point = Option::from(compressed.decompress_non_canonical())
+            .ok_or(SignatureError::InvalidKey)?;
+        Ok(Self { compressed, point })
+    }
+
+    /// View the 32 byte encoding of this key.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        self.compressed.as_bytes()
+    }
+
+    /// Copy the 32 byte encoding of this key.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.compressed.to_bytes()
+    }
+
+    /// Get the point A this key encodes.
+    pub fn to_edwards(&self) -> EdwardsPoint {
+        self.point
+    }
+
+    /// Verify a signature on a message, as specified in RFC 8032, Section 5.1.7.
+    ///
+    /// This checks the equation s⋅B = R + k⋅A, with k = SHA-512(R || A || M), which
+    /// RFC 8032 allows in place of multiplying both sides by the cofactor. Rather than
+    /// decoding R, we compute s⋅B - k⋅A, and compare its encoding with that of R, which
+    /// rejects any R that isn't canonical, as decoding would. This fails if s ≥ ℓ.
+    ///
+    /// This runs in variable time, but only handles public data.
+    pub fn verify(&self, msg: &[u8], signature: &Signature) -> Result<(), SignatureError> {
+        self.verify_inner(None, msg, signature)
+    }
+
+    /// Verify a signature on a message, made with Ed25519ctx, under some context.
+    ///
+    /// This fails if the context is empty, or longer than 255 bytes, and otherwise works
+    /// like [`Self::verify`], with the context hashed into the challenge. A signature
+    /// only verifies under the context it was made with.
+    pub fn verify_with_context(
+        &self,
+        msg: &[u8],
+        context: &[u8],
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        self.verify_inner(Some(check_context(context)?), msg, signature)
+    }
+
+    /// Verify a signature on a message, rejecting anything but the strictest encodings.
+    ///
+    /// This accepts a subset of what [`Self::verify`] accepts, for applications, like
+    /// consensus, where everyone needs to agree on exactly which signatures are valid,
+    /// and signatures shouldn't be malleable. On top of the checks done by
+    /// [`Self::verify`], this rejects signatures where R isn't the canonical encoding
+    /// of a point, and where either the key A, or R, is a point of small order. A key of
+    /// small order lets one signature verify for many messages, and a small order R
+    /// lets a signature be valid without any knowledge of the secret key.
+    ///
+    /// A key read with [`Self::from_bytes_zip215`] might not be canonically encoded, so
+    /// this rejects such keys too. Like with [`Self::verify`], s ≥ ℓ is rejected, and
+    /// this uses the same cofactorless equation.
+    pub fn verify_strict(&self, msg: &[u8], signature: &Signature) -> Result<(), SignatureError> {
+        if self.point.compress() != self.compressed {
+            return Err(SignatureError::InvalidKey);
+        }
+        let r = Option::<EdwardsPoint>::from(signature.r.decompress())
+            .ok_or(SignatureError::InvalidSignature)?;
+        if bool::from(self.point.is_small_order() | r.is_small_order()) {
+            return Err(SignatureError::SmallOrderPoint);
+        }
+        self.verify_inner(None, msg, signature)
+    }
+
+    /// Verify a signature on a message, following the rules of ZIP-215 exactly.
+    ///
+    /// These rules come from Zcash, and are meant to let everyone agree on which
+    /// signatures are valid, including when verifying them in batches:
+    ///
+    /// - s ≥ ℓ gets rejected.
+    /// - R, like the key, may use a non-canonical encoding, as long as it encodes a
+    ///   point. The challenge k hashes the encodings as they are.
+    /// - Points of small order are accepted.
+    /// - The equation 8⋅s⋅B = 8⋅R + 8⋅k⋅A gets checked, multiplied by the cofactor.
+    ///
+    /// Every signature accepted by [`Self::verify`] is accepted here. For canonical
+    /// encodings, these are the same rules [`super::verify_batch`] follows. To accept
+    /// non-canonical keys, use [`Self::from_bytes_zip215`] to decode them.
+    pub fn verify_zip215(&self, msg: &[u8], signature: &Signature) -> Result<(), SignatureError> {
+        let s = Option::<Scalar>::from(Scalar::from_canonical_bytes(&signature.s))
+            .ok_or(SignatureError::NonCanonicalScalar)?;
+        let r = Option::<EdwardsPoint>::from(signature.r.decompress_non_canonical())
+            .ok_or(SignatureError::InvalidSignature)?;
+        let k = challenge(None, &signature.r, &self.compressed, msg);
+        let check = EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &-self.point, &s) - r;
+        if bool::from(check.mul_by_cofactor().is_identity()) {
+            Ok(())
+        } else {
+            Err(SignatureError::InvalidSignature)
+        }
+    }
+
+    fn verify_inner(
+        &self,
+        context: Option<&[u8]>,
+        msg: &[u8],
+        signature: &Signature,
+    ) -> Result<(), SignatureError> {
+        let s = Option::<Scalar>::from(Scalar::from_canonical_bytes(&signature.s))
+            .ok_or(SignatureError::NonCanonicalScalar)?;
+        let k = challenge(context, &signature.r, &self.compressed, msg);
+        let r = EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &-self.point, &s);
+        if r.compress() == signature.r {
+            Ok(())
+        } else {
+            Err(SignatureError::InvalidSignature)
+        }
+    }
+}
+
+impl PartialEq for VerifyingKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.compressed == other.compressed
+    }
+}
+
+impl Eq for VerifyingKey {}
+
+impl Hash for VerifyingKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.compressed.hash(state);
+    }
+}
+
+/// This prints the 64 hex characters of the encoding, in order.
+impl LowerHex for VerifyingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_hex(f, self.as_bytes())
+    }
+}
+
+impl Display for VerifyingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_hex(f, self.as_bytes())
+    }
+}
+
+impl Debug for VerifyingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "VerifyingKey({})", self)
+    }
+}
+
+/// The secret scalar, and nonce prefix, derived from the seed of a [`SigningKey`].
+///
+/// Deriving these costs a SHA-512 hash, so a [`SigningKey`] does this once, when it
+/// gets created, and keeps the result around, rather than expanding its seed for every
+/// signature. With the `zeroize` feature, both get wiped on drop.
+///
+/// This also holds the matching [`VerifyingKey`], which is what gets hashed when
+/// signing. The nonce only depends on the prefix and the message, so signing the same
+/// message under two different keys would reveal the secret scalar. Keeping the key
+/// here, rather than letting callers pass one in, rules this out.
+#[derive(Clone)]
+pub struct ExpandedSecretKey {
+    scalar: Scalar,
+    prefix: [u8; 32],
+    verifying_key: VerifyingKey,
+}
+
+impl ExpandedSecretKey {
+    /// Hash a seed into the secret scalar, and the prefix used to derive nonces.
+    ///
+    /// This is step 1 and 2 of key generation, in RFC 8032, Section 5.1.5. The lower half
+    /// of the hash gets clamped, clearing the bottom 3 bits, and setting bit 254, before
+    /// getting reduced into a scalar. This also derives the verifying key, which costs
+    /// a multiplication by the basepoint, using the precomputed table.
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        let mut hash: [u8; 64] = Sha512::digest(seed).into();
+        let mut scalar_bytes = [0u8; 32];
+        let mut prefix = [0u8; 32];
+        scalar_bytes.copy_from_slice(&hash[..32]);
+        prefix.copy_from_slice(&hash[32..]);
+        scalar_bytes[0] &= 0xF8;
+        scalar_bytes[31] &= 0x7F;
+        scalar_bytes[31] |= 0x40;
+        // The clamped integer isn't reduced, but the basepoint has order ℓ, so we only ever
+        // need it mod ℓ.
+        let scalar = Scalar::from_bytes_mod_order(&scalar_bytes);
+        wipe(&mut hash);
+        wipe(&mut scalar_bytes);
+        let point = &scalar * &ED25519_BASEPOINT_TABLE;
+        let verifying_key = VerifyingKey {
+            compressed: point.compress(),
+            point,
+        };
+        Self {
+            scalar,
+            prefix,
+            verifying_key,
+        }
+    }
+
+    /// Get the verifying key matching this secret, i.e. the encoding of a⋅B.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.verifying_key
+    }
+
+    /// Sign a message, as specified in RFC 8032, Section 5.1.6.
+    ///
+    /// This runs in constant time, with respect to the key, and the nonce.
+    pub fn sign(&self, msg: &[u8]) -> Signature {
+        self.sign_inner(None, msg)
+    }
+
+    /// Sign a message with Ed25519ctx, under some context.
+    ///
+    /// This fails if the context is empty, or longer than 255 bytes.
+    pub fn sign_with_context(
+        &self,
+        msg: &[u8],
+        context: &[u8],
+    ) -> Result<Signature, SignatureError> {
+        Ok(self.sign_inner(Some(check_context(context)?), msg))
+    }
+
+    fn sign_inner(&self, context: Option<&[u8]>, msg: &[u8]) -> Signature {
+        let mut r = Scalar::from_hash(dom2(context).chain_update(self.prefix).chain_update(msg));
+        let big_r = (&r * &ED25519_BASEPOINT_TABLE).compress();
+        let k = challenge(context, &big_r, &self.verifying_key.compressed, msg);
+        let s = Scalar::mul_add(&k, &self.scalar, &r);
+        wipe(&mut r);
+        Signature {
+            r: big_r,
+            s: s.to_bytes(),
+        }
+    }
+}
+
+/// Neither the scalar, nor the prefix, get printed.
+impl Debug for ExpandedSecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExpandedSecretKey").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for ExpandedSecretKey {
+    fn drop(&mut self) {
+        wipe(&mut self.scalar);
+        wipe(&mut self.prefix);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for ExpandedSecretKey {}
+
+/// A key producing Ed25519 signatures, i.e. a secret key.
+///
+/// This is a 32 byte seed, from which everything else gets derived, along with the
+/// [`ExpandedSecretKey`] derived from it, which holds the matching [`VerifyingKey`].
+/// With the `zeroize` feature, the seed gets wiped on drop.
+#[derive(Clone)]
+pub struct SigningKey {
+    seed: [u8; 32],
+    expanded: ExpandedSecretKey,
+}
+
+impl SigningKey {
+    /// Create a signing key from its 32 byte seed.
+    ///
+    /// Any 32 bytes make a valid key, but the seed needs to be uniformly random, and
+    /// kept secret, of course. This expands the seed, and derives the verifying key,
+    /// which costs a hash, and a multiplication by the basepoint.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self {
+            seed,
+            expanded: ExpandedSecretKey::from_seed(&seed),
+        }
+    }
+
+    /// Create a signing key from its 32 byte encoding, which is just the seed.
+    ///
+    /// This is the same as [`Self::from_seed`].
+    pub fn from_bytes(seed: &[u8; 32]) -> Self {
+        Self::from_seed(*seed)
+    }
+
+    /// Generate a new signing key, by filling its seed with random bytes.
+    #[cfg(feature = "rand_core")]
+    pub fn generate(rng: &mut impl CryptoRngCore) -> Self {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        let out = Self::from_seed(seed);
+        wipe(&mut seed);
+        out
+    }
+
+    /// Copy the 32 byte seed of this key.
+    ///
+    /// Passing this to [`Self::from_seed`] gives back the same key.
+    pub fn to_seed(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    /// View the 32 byte seed of this key.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.seed
+    }
+
+    /// Copy the 32 byte encoding of this key, which is just the seed.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.seed
+    }
+
+    /// Get the key verifying the signatures made with this key.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.expanded.verifying_key
+    }
+
+    /// Get the expanded secret derived from the seed of this key.
+    pub fn expanded_secret_key(&self) -> &ExpandedSecretKey {
+        &self.expanded
+    }
+
+    /// Sign a message, as specified in RFC 8032, Section 5.1.6.
+    ///
+    /// The nonce r gets derived by hashing the message with a secret prefix, so signing
+    /// the same message twice gives the same signature, and no randomness is needed.
+    /// This uses the expanded secret computed when creating the key, so the seed
+    /// doesn't get hashed again.
+    ///
+    /// This runs in constant time, with respect to the key, and the nonce.
+    pub fn sign(&self, msg: &[u8]) -> Signature {
+        self.expanded.sign(msg)
+    }
+
+    /// Sign a message with Ed25519ctx, as specified in RFC 8032, Section 5.1.
+    ///
+    /// The context gets hashed along with the message, which separates signatures made
+    /// for different protocols, or purposes, using the same key. This fails if the
+    /// context is empty, or longer than 255 bytes.
+    pub fn sign_with_context(
+        &self,
+        msg: &[u8],
+        context: &[u8],
+    ) -> Result<Signature, SignatureError> {
+        self.expanded.sign_with_context(msg, context)
+    }
+}
+
+/// The seed isn't printed, only the verifying key.
+impl Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningKey")
+            .field("verifying_key", &self.expanded.verifying_key)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.seed);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for SigningKey {}
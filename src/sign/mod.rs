@@ -0,0 +1,55 @@
+//! Ed25519 signatures, as specified in [RFC 8032](https://www.rfc-editor.org/rfc/rfc8032).
+//!
+//! This is PureEdDSA over edwards25519, with SHA-512: a [`SigningKey`] is a 32 byte
+//! seed, which gets hashed into a secret scalar, and a prefix used to derive nonces,
+//! so that signing is deterministic, and never needs any randomness. The matching
+//! [`VerifyingKey`] is the encoding of a point, and a [`Signature`] the encoding of
+//! a point, followed by the encoding of a scalar.
+//!
+//! Ed25519ctx is also supported, through methods like [`SigningKey::sign_with_context`],
+//! which hash a context string along with the message, to separate signatures made for
+//! different purposes. Many signatures can be checked at once, with [`verify_batch`].
+//!
+//! RFC 8032 leaves some freedom in which signatures get accepted. Besides
+//! [`VerifyingKey::verify`], [`VerifyingKey::verify_strict`] accepts as few signatures
+//! as possible, and [`VerifyingKey::verify_zip215`] follows the rules of ZIP-215, for
+//! applications which need to agree with Zcash.
+use std::fmt::Display;
+
+mod batch;
+mod keys;
+mod signature;
+
+pub use batch::{verify_batch, BatchError};
+pub use keys::{ExpandedSecretKey, SigningKey, VerifyingKey};
+pub use signature::Signature;
+
+/// The reasons signature verification, or decoding a key, can fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignatureError {
+    /// The bytes of a verifying key don't encode a point, or not canonically, when required.
+    InvalidKey,
+    /// The scalar of a signature isn't canonical, i.e. s ≥ ℓ.
+    NonCanonicalScalar,
+    /// The signature doesn't satisfy the verification equation, for this key and message.
+    InvalidSignature,
+    /// The context string for Ed25519ctx is empty, or longer than 255 bytes.
+    InvalidContext,
+    /// The verifying key, or the point R of a signature, has small order.
+    SmallOrderPoint,
+}
+
+impl Display for SignatureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidKey => write!(f, "invalid verifying key encoding"),
+            Self::NonCanonicalScalar => write!(f, "non canonical signature scalar"),
+            Self::InvalidSignature => write!(f, "signature verification failed"),
+            Self::InvalidContext => write!(f, "context must be between 1 and 255 bytes"),
+            Self::SmallOrderPoint => write!(f, "small order point in key or signature"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
@@ -0,0 +1,66 @@
+//! The 64 byte encoding of signatures.
+use std::fmt::{Debug, Display, LowerHex};
+
+use crate::{curve::edwards::CompressedEdwardsY, util::write_hex};
+
+/// An Ed25519 signature.
+///
+/// This is the encoding of a point R, followed by the little endian encoding of a
+/// scalar s. Neither gets checked here: any 64 bytes make a signature, and it's up to
+/// verification to reject those which aren't valid encodings.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Signature {
+    pub(crate) r: CompressedEdwardsY,
+    pub(crate) s: [u8; 32],
+}
+
+impl Signature {
+    /// Read a signature from its 64 byte encoding.
+    pub fn from_bytes(bytes: &[u8; 64]) -> Self {
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&bytes[..32]);
+        s.copy_from_slice(&bytes[32..]);
+        Self {
+            r: CompressedEdwardsY(r),
+            s,
+        }
+    }
+
+    /// Write out the 64 byte encoding of this signature.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut out = [0u8; 64];
+        out[..32].copy_from_slice(self.r.as_bytes());
+        out[32..].copy_from_slice(&self.s);
+        out
+    }
+
+    /// The encoding of the point R, i.e. the first 32 bytes of the signature.
+    pub fn r_bytes(&self) -> &[u8; 32] {
+        self.r.as_bytes()
+    }
+
+    /// The encoding of the scalar s, i.e. the last 32 bytes of the signature.
+    pub fn s_bytes(&self) -> &[u8; 32] {
+        &self.s
+    }
+}
+
+/// This prints the 128 hex characters of the encoding, in order.
+impl LowerHex for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_hex(f, &self.to_bytes())
+    }
+}
+
+impl Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_hex(f, &self.to_bytes())
+    }
+}
+
+impl Debug for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Signature({})", self)
+    }
+}
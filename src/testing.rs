@@ -0,0 +1,96 @@
+//! Internals exposed for testing.
+//!
+//! This includes tiny instances of the generic prime field arithmetic: a modulus with
+//! a single limb is small enough to check exhaustively, or at least exhaustively
+//! around the edges, against plain integer arithmetic. It also includes each of the
+//! ways the default backend has of multiplying limbs, so that they can be compared.
+//!
+//! This isn't part of the public API, and only exists for the sake of this crate's tests.
+
+define_prime_field! {
+    /// The field of integers mod 251, the largest 8 bit prime.
+    pub struct Fp251([u64; 1]);
+    modulus = [251];
+    reduction = bitwise;
+}
+
+define_prime_field! {
+    /// The field of integers mod 251, the largest 8 bit prime, stored in Montgomery form.
+    pub struct MontgomeryFp251([u64; 1]);
+    modulus = [251];
+    reduction = montgomery;
+}
+
+define_prime_field! {
+    /// The field of integers mod 251, the largest 8 bit prime, multiplied with 32 bit words.
+    pub struct Montgomery32Fp251([u64; 1]);
+    modulus = [251];
+    reduction = montgomery32;
+}
+
+define_prime_field! {
+    /// The field of integers mod 251, the largest 8 bit prime, using Barrett reduction.
+    pub struct BarrettFp251([u64; 1]);
+    modulus = [251];
+    reduction = barrett;
+}
+
+define_prime_field! {
+    /// The field of integers mod 2⁶⁴ - 59, the largest 64 bit prime.
+    pub struct Fp64([u64; 1]);
+    modulus = [0xFFFFFFFFFFFFFFC5];
+    reduction = bitwise;
+}
+
+define_prime_field! {
+    /// The field of integers mod 2⁶⁴ - 59, the largest 64 bit prime, stored in Montgomery form.
+    pub struct MontgomeryFp64([u64; 1]);
+    modulus = [0xFFFFFFFFFFFFFFC5];
+    reduction = montgomery;
+}
+
+define_prime_field! {
+    /// The field of integers mod 2⁶⁴ - 59, the largest 64 bit prime, multiplied with 32 bit words.
+    pub struct Montgomery32Fp64([u64; 1]);
+    modulus = [0xFFFFFFFFFFFFFFC5];
+    reduction = montgomery32;
+}
+
+define_prime_field! {
+    /// The field of integers mod 2⁶⁴ - 59, the largest 64 bit prime, using Barrett reduction.
+    pub struct BarrettFp64([u64; 1]);
+    modulus = [0xFFFFFFFFFFFFFFC5];
+    reduction = barrett;
+}
+
+/// The different ways the saturated backend has of calculating the full product of limbs.
+///
+/// Which of these gets used depends on the target, and on the CPU, so we expose all of
+/// them, letting tests check that they agree with each other.
+#[cfg(not(any(
+    feature = "fiat-backend",
+    feature = "u51-backend",
+    feature = "u32-backend",
+    target_pointer_width = "32"
+)))]
+pub mod mul_wide {
+    use crate::curve::field::saturated;
+
+    /// Multiply limbs without relying on any special instructions.
+    pub fn portable(a: &[u64; 4], b: &[u64; 4]) -> [u64; 8] {
+        saturated::mul_wide_portable(a, b)
+    }
+
+    /// Multiply limbs using the BMI2 and ADX extensions, if the CPU supports them.
+    #[cfg(target_arch = "x86_64")]
+    pub fn bmi2_adx(a: &[u64; 4], b: &[u64; 4]) -> Option<[u64; 8]> {
+        // Safe, since we've checked that the CPU supports these features.
+        saturated::has_bmi2_adx().then(|| unsafe { saturated::mul_wide_bmi2_adx(a, b) })
+    }
+
+    /// Multiply limbs using inline assembly.
+    #[cfg(target_arch = "aarch64")]
+    pub fn aarch64(a: &[u64; 4], b: &[u64; 4]) -> [u64; 8] {
+        saturated::mul_wide_aarch64(a, b)
+    }
+}
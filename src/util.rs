@@ -0,0 +1,83 @@
+//! Small helpers shared across the crate.
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Wipe an intermediate buffer holding secret data, if the `zeroize` feature is enabled.
+///
+/// Otherwise, this does nothing, and should get optimized away entirely.
+#[cfg(feature = "zeroize")]
+#[inline(always)]
+pub(crate) fn wipe<Z: Zeroize + ?Sized>(x: &mut Z) {
+    x.zeroize();
+}
+
+/// Wipe an intermediate buffer holding secret data, if the `zeroize` feature is enabled.
+///
+/// Otherwise, this does nothing, and should get optimized away entirely.
+#[cfg(not(feature = "zeroize"))]
+#[inline(always)]
+pub(crate) fn wipe<Z: ?Sized>(_x: &mut Z) {}
+
+/// Write bytes out in lowercase hex, in order, prefixed with 0x in alternate mode.
+///
+/// This is how encodings, like compressed points, get displayed.
+pub(crate) fn write_hex(f: &mut std::fmt::Formatter<'_>, bytes: &[u8]) -> std::fmt::Result {
+    if f.alternate() {
+        f.write_str("0x")?;
+    }
+    for byte in bytes {
+        write!(f, "{:02x}", byte)?;
+    }
+    Ok(())
+}
+
+/// Serialize 32 bytes, as a tuple.
+///
+/// A tuple, rather than a byte string, keeps binary formats from adding a length.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_bytes<S: serde::Serializer>(
+    bytes: &[u8; 32],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeTuple;
+
+    let mut tuple = serializer.serialize_tuple(32)?;
+    for byte in bytes {
+        tuple.serialize_element(byte)?;
+    }
+    tuple.end()
+}
+
+/// Deserialize 32 bytes, as written by [`serialize_bytes`].
+///
+/// The caller is responsible for validating them, and `expecting` describes what
+/// they should encode, for error messages.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_bytes<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+    expecting: &'static str,
+) -> Result<[u8; 32], D::Error> {
+    use serde::de::{Error, SeqAccess, Visitor};
+
+    struct BytesVisitor(&'static str);
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = [u8; 32];
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.0)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; 32], A::Error> {
+            let mut bytes = [0u8; 32];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+            }
+            Ok(bytes)
+        }
+    }
+
+    deserializer.deserialize_tuple(32, BytesVisitor(expecting))
+}
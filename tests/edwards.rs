@@ -0,0 +1,1033 @@
+//! Tests of points on edwards25519, checking the group law against the affine formulas.
+use ck_dodo::curve::{
+    edwards::{
+        AffineNiels, CompressedEdwardsY, EdwardsBasepointTable, EdwardsPoint,
+        EdwardsPrecomputation, ProjectiveNiels, VartimeEdwardsPrecomputation,
+        ED25519_BASEPOINT_COMPRESSED, ED25519_BASEPOINT_POINT, ED25519_BASEPOINT_TABLE,
+        EIGHT_TORSION,
+    },
+    field::Fp,
+    scalar::Scalar,
+    traits::{MultiscalarMul, VartimeMultiscalarMul, VartimePrecomputedMultiscalarMul},
+};
+use subtle::ConstantTimeEq;
+
+/// d = -121665 / 121666.
+fn d() -> Fp {
+    -Fp::ONE.mul_small(121665) * Fp::ONE.mul_small(121666).invert()
+}
+
+/// The basepoint from RFC 8032, with y = 4 / 5, and a positive x.
+fn basepoint() -> (Fp, Fp) {
+    let y = Fp::ONE.mul_small(4) * Fp::ONE.mul_small(5).invert();
+    let y2 = y.square();
+    let x = ((y2 - Fp::ONE) * (d() * y2 + Fp::ONE).invert())
+        .sqrt()
+        .unwrap()
+        .abs();
+    (x, y)
+}
+
+/// Add two points, in affine coordinates.
+fn affine_add((x1, y1): (Fp, Fp), (x2, y2): (Fp, Fp)) -> (Fp, Fp) {
+    let dxy = d() * x1 * x2 * y1 * y2;
+    let x3 = (x1 * y2 + y1 * x2) * (Fp::ONE + dxy).invert();
+    let y3 = (y1 * y2 + x1 * x2) * (Fp::ONE - dxy).invert();
+    (x3, y3)
+}
+
+fn point((x, y): (Fp, Fp)) -> EdwardsPoint {
+    EdwardsPoint::from_affine(&x, &y).unwrap()
+}
+
+fn assert_points_eq(a: &EdwardsPoint, b: &EdwardsPoint) {
+    assert!(bool::from(a.ct_eq(b)));
+    let ((ax, ay), (bx, by)) = (a.to_affine(), b.to_affine());
+    assert_eq!(ax.to_bytes(), bx.to_bytes());
+    assert_eq!(ay.to_bytes(), by.to_bytes());
+}
+
+/// The affine multiples 1⋅B, 2⋅B, ..., n⋅B of the basepoint.
+fn affine_multiples(n: usize) -> Vec<(Fp, Fp)> {
+    let b = basepoint();
+    let mut out = vec![b];
+    for _ in 1..n {
+        out.push(affine_add(*out.last().unwrap(), b));
+    }
+    out
+}
+
+#[test]
+fn test_basepoint_encoding() {
+    let (x, y) = basepoint();
+    let mut expected_y = [0x66; 32];
+    expected_y[0] = 0x58;
+    assert_eq!(y.to_bytes(), expected_y);
+    assert!(!bool::from(x.is_negative()));
+}
+
+#[test]
+fn test_from_affine_rejects_points_off_the_curve() {
+    let (x, y) = basepoint();
+    assert!(bool::from(EdwardsPoint::from_affine(&x, &y).is_some()));
+    assert!(bool::from(
+        EdwardsPoint::from_affine(&x, &(y + Fp::ONE)).is_none()
+    ));
+    assert!(bool::from(
+        EdwardsPoint::from_affine(&Fp::ONE, &Fp::ONE).is_none()
+    ));
+}
+
+#[test]
+fn test_identity() {
+    let b = point(basepoint());
+    let (x, y) = EdwardsPoint::IDENTITY.to_affine();
+    assert_eq!(x.to_bytes(), Fp::ZERO.to_bytes());
+    assert_eq!(y.to_bytes(), Fp::ONE.to_bytes());
+    assert_points_eq(&(b + EdwardsPoint::IDENTITY), &b);
+    assert_points_eq(&(EdwardsPoint::IDENTITY + b), &b);
+    assert_points_eq(&EdwardsPoint::IDENTITY.double(), &EdwardsPoint::IDENTITY);
+    assert!(!bool::from(b.ct_eq(&EdwardsPoint::IDENTITY)));
+}
+
+#[test]
+fn test_add_matches_affine() {
+    let multiples = affine_multiples(16);
+    let b = point(multiples[0]);
+    let mut acc = b;
+    for expected in &multiples[1..] {
+        acc += b;
+        assert_points_eq(&acc, &point(*expected));
+    }
+}
+
+#[test]
+fn test_double_matches_add() {
+    let multiples = affine_multiples(16);
+    for (i, p) in multiples.iter().enumerate() {
+        let p = point(*p);
+        assert_points_eq(&p.double(), &(p + p));
+        if 2 * i + 1 < multiples.len() {
+            assert_points_eq(&p.double(), &point(multiples[2 * i + 1]));
+        }
+    }
+}
+
+#[test]
+fn test_add_is_commutative_and_associative() {
+    let multiples: Vec<EdwardsPoint> = affine_multiples(8).into_iter().map(point).collect();
+    for a in &multiples {
+        for b in &multiples {
+            assert_points_eq(&(a + b), &(b + a));
+            for c in &multiples {
+                assert_points_eq(&((a + b) + c), &(a + (b + c)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_projective_representations_are_equal() {
+    // Points built from different sums, but representing the same affine point.
+    let multiples: Vec<EdwardsPoint> = affine_multiples(6).into_iter().map(point).collect();
+    let lhs = multiples[0] + multiples[4];
+    let rhs = multiples[1] + multiples[3];
+    assert_points_eq(&lhs, &rhs);
+    assert_points_eq(&lhs, &multiples[2].double());
+}
+
+/// The 8 points of small order, in affine coordinates, found by solving the curve equation.
+fn affine_torsion() -> Vec<(Fp, Fp)> {
+    let i = (-Fp::ONE).sqrt().unwrap();
+    let mut out = vec![
+        (Fp::ZERO, Fp::ONE),
+        (Fp::ZERO, -Fp::ONE),
+        (i, Fp::ZERO),
+        (-i, Fp::ZERO),
+    ];
+    // A point of order 8 doubles to (±i, 0), so y² = -x², and d⋅x⁴ - 2⋅x² - 1 = 0.
+    let root = (Fp::ONE + d()).sqrt().unwrap();
+    for x2 in [Fp::ONE + root, Fp::ONE - root] {
+        let x2 = x2 * d().invert();
+        let Some(x) = Option::<Fp>::from(x2.sqrt()) else {
+            continue;
+        };
+        let y = (-x2).sqrt().unwrap();
+        for (x, y) in [(x, y), (x, -y), (-x, y), (-x, -y)] {
+            out.push((x, y));
+        }
+    }
+    assert_eq!(out.len(), 8);
+    out
+}
+
+#[test]
+fn test_formulas_are_complete_on_small_order_points() {
+    let torsion = affine_torsion();
+    for &p in &torsion {
+        let p_point = point(p);
+        assert_points_eq(&p_point.double(), &point(affine_add(p, p)));
+        for &q in &torsion {
+            let sum = point(p) + point(q);
+            assert_points_eq(&sum, &point(affine_add(p, q)));
+        }
+    }
+}
+
+#[test]
+fn test_formulas_are_complete_on_exceptional_inputs() {
+    let torsion: Vec<EdwardsPoint> = affine_torsion().into_iter().map(point).collect();
+    let (x, y) = basepoint();
+    let b = point((x, y));
+    let neg_b = point((-x, y));
+    // Adding a point to its inverse, in both orders.
+    assert_points_eq(&(b + neg_b), &EdwardsPoint::IDENTITY);
+    assert_points_eq(&(neg_b + b), &EdwardsPoint::IDENTITY);
+    // Doubling through addition, with a non-trivial Z coordinate.
+    let b2 = b.double();
+    assert_points_eq(&(b2 + b2), &b2.double());
+    // Points of small order, offset by a point of large order, cancel out properly.
+    for t in &torsion {
+        let mixed = b + t;
+        assert_points_eq(&(mixed + neg_b), t);
+        assert_points_eq(&(mixed + mixed), &(b2 + t.double()));
+    }
+    // The identity, doubled and added to itself.
+    let id = EdwardsPoint::IDENTITY;
+    assert_points_eq(&(id + id), &id);
+    assert_points_eq(&id.double().double(), &id);
+}
+
+#[test]
+fn test_niels_addition_matches_addition() {
+    let mut points: Vec<EdwardsPoint> = affine_multiples(4).into_iter().map(point).collect();
+    points.extend(affine_torsion().into_iter().map(point));
+    points.push(EdwardsPoint::IDENTITY);
+    // Give some points a non-trivial Z coordinate.
+    points.push(points[0].double());
+    points.push(points[1] + points[4]);
+    for p in &points {
+        for q in &points {
+            let sum = p + q;
+            let neg_q = q.to_affine();
+            let diff = p + point((-neg_q.0, neg_q.1));
+            assert_points_eq(&(p + &q.to_projective_niels()), &sum);
+            assert_points_eq(&(p + &q.to_affine_niels()), &sum);
+            assert_points_eq(&(p - &q.to_projective_niels()), &diff);
+            assert_points_eq(&(p - &q.to_affine_niels()), &diff);
+        }
+    }
+}
+
+#[test]
+fn test_niels_identity() {
+    let b = point(basepoint());
+    assert_points_eq(&(&b + &ProjectiveNiels::IDENTITY), &b);
+    assert_points_eq(&(&b + &AffineNiels::IDENTITY), &b);
+    assert_points_eq(&(&b - &ProjectiveNiels::IDENTITY), &b);
+    assert_points_eq(&(&b - &AffineNiels::IDENTITY), &b);
+}
+
+/// The encoding of the basepoint, from RFC 8032.
+const BASEPOINT_COMPRESSED: &str =
+    "5866666666666666666666666666666666666666666666666666666666666666";
+
+fn compressed(hex: &str) -> CompressedEdwardsY {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+    }
+    CompressedEdwardsY(out)
+}
+
+#[test]
+fn test_compress_basepoint() {
+    let b = point(basepoint());
+    assert_eq!(b.compress(), compressed(BASEPOINT_COMPRESSED));
+    assert_eq!(
+        b.double().double().compress(),
+        point(affine_multiples(4)[3]).compress()
+    );
+    assert_points_eq(&compressed(BASEPOINT_COMPRESSED).decompress().unwrap(), &b);
+}
+
+#[test]
+fn test_compress_round_trips() {
+    let mut points: Vec<EdwardsPoint> = affine_multiples(16).into_iter().map(point).collect();
+    points.extend(affine_torsion().into_iter().map(point));
+    points.push(points[3].double() + points[5]);
+    for p in &points {
+        let encoded = p.compress();
+        let decoded = encoded.decompress().unwrap();
+        assert_points_eq(&decoded, p);
+        assert_eq!(decoded.compress(), encoded);
+    }
+    // The sign bit distinguishes a point from its inverse.
+    let (x, y) = basepoint();
+    let neg_b = point((-x, y)).compress();
+    assert_eq!(
+        neg_b.as_bytes()[..31],
+        compressed(BASEPOINT_COMPRESSED).0[..31]
+    );
+    assert_eq!(neg_b.as_bytes()[31], 0x66 | 0x80);
+}
+
+#[test]
+fn test_decompress_rejects_invalid_encodings() {
+    // P, which would decode to y = 0, and P + 1, for the identity.
+    let p = "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f";
+    let p_plus_1 = "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f";
+    let max = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f";
+    for hex in [p, p_plus_1, max] {
+        assert!(bool::from(compressed(hex).decompress().is_none()));
+        // The sign bit doesn't make it canonical.
+        let mut bytes = compressed(hex).0;
+        bytes[31] |= 0x80;
+        assert!(bool::from(CompressedEdwardsY(bytes).decompress().is_none()));
+    }
+    // y = 2 doesn't lie on the curve, while y = 3 does.
+    let mut bytes = [0u8; 32];
+    bytes[0] = 2;
+    assert!(bool::from(CompressedEdwardsY(bytes).decompress().is_none()));
+    bytes[0] = 3;
+    assert!(bool::from(CompressedEdwardsY(bytes).decompress().is_some()));
+    // The identity, and the point of order 2, with x = 0, can't be negative.
+    for y in [Fp::ONE, -Fp::ONE] {
+        let mut bytes = y.to_bytes();
+        assert!(bool::from(CompressedEdwardsY(bytes).decompress().is_some()));
+        bytes[31] |= 0x80;
+        assert!(bool::from(CompressedEdwardsY(bytes).decompress().is_none()));
+    }
+}
+
+#[test]
+fn test_decompress_non_canonical() {
+    let mut points: Vec<EdwardsPoint> = affine_multiples(8).into_iter().map(point).collect();
+    points.extend(affine_torsion().into_iter().map(point));
+    for p in &points {
+        let encoded = p.compress();
+        assert_points_eq(&encoded.decompress_non_canonical().unwrap(), p);
+    }
+    // y ≥ P gets reduced, so P + y decodes like y, for each sign.
+    for y in 0..19u8 {
+        let mut canonical = [0u8; 32];
+        canonical[0] = y;
+        let mut bytes =
+            compressed("edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f").0;
+        bytes[0] += y;
+        for sign in [0, 0x80] {
+            canonical[31] = sign;
+            bytes[31] = 0x7F | sign;
+            let expected = CompressedEdwardsY(canonical).decompress_non_canonical();
+            let decoded = CompressedEdwardsY(bytes).decompress_non_canonical();
+            assert_eq!(
+                bool::from(decoded.is_some()),
+                bool::from(expected.is_some())
+            );
+            if bool::from(expected.is_some()) {
+                assert_points_eq(&decoded.unwrap(), &expected.unwrap());
+            }
+        }
+    }
+    // The sign bit gets ignored when x = 0.
+    for y in [Fp::ONE, -Fp::ONE] {
+        let mut bytes = y.to_bytes();
+        let expected = CompressedEdwardsY(bytes).decompress().unwrap();
+        bytes[31] |= 0x80;
+        assert_points_eq(
+            &CompressedEdwardsY(bytes)
+                .decompress_non_canonical()
+                .unwrap(),
+            &expected,
+        );
+    }
+    // The identity has y = P + 1 too.
+    let p_plus_1 = compressed("eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f");
+    assert_points_eq(
+        &p_plus_1.decompress_non_canonical().unwrap(),
+        &EdwardsPoint::IDENTITY,
+    );
+    // Points still need to be on the curve.
+    let mut bytes = [0u8; 32];
+    bytes[0] = 2;
+    assert!(bool::from(
+        CompressedEdwardsY(bytes)
+            .decompress_non_canonical()
+            .is_none()
+    ));
+}
+
+#[test]
+fn test_batch_decompress() {
+    let mut points: Vec<EdwardsPoint> = affine_multiples(6).into_iter().map(point).collect();
+    points.extend(affine_torsion().into_iter().map(point));
+    let encoded: Vec<CompressedEdwardsY> = points.iter().map(|p| p.compress()).collect();
+    // Including the empty batch.
+    for n in 0..=encoded.len() {
+        let decoded = CompressedEdwardsY::batch_decompress(&encoded[..n]).unwrap();
+        assert_eq!(decoded.len(), n);
+        for (d, p) in decoded.iter().zip(&encoded[..n]) {
+            assert_points_eq(d, &p.decompress().unwrap());
+        }
+    }
+
+    // A non canonical y, a y not on the curve, and a negative zero x, at different offsets.
+    let mut not_canonical = [0xFF; 32];
+    not_canonical[31] = 0x7F;
+    let mut off_curve = [0u8; 32];
+    off_curve[0] = 2;
+    let mut negative_zero = Fp::ONE.to_bytes();
+    negative_zero[31] |= 0x80;
+    let mut mixed = encoded.clone();
+    mixed[1] = CompressedEdwardsY(not_canonical);
+    mixed[4] = CompressedEdwardsY(off_curve);
+    mixed.push(CompressedEdwardsY(negative_zero));
+    assert_eq!(
+        CompressedEdwardsY::batch_decompress(&mixed).unwrap_err(),
+        vec![1, 4, mixed.len() - 1]
+    );
+}
+
+#[test]
+fn test_validation_predicates() {
+    let multiples: Vec<EdwardsPoint> = affine_multiples(4).into_iter().map(point).collect();
+    let torsion: Vec<EdwardsPoint> = affine_torsion().into_iter().map(point).collect();
+    for p in &multiples {
+        assert!(bool::from(p.is_on_curve()));
+        assert!(bool::from(p.double().is_on_curve()));
+        assert!(!bool::from(p.is_identity()));
+        assert!(!bool::from(p.is_small_order()));
+        assert!(bool::from(p.is_torsion_free()));
+    }
+    for (i, t) in torsion.iter().enumerate() {
+        assert!(bool::from(t.is_on_curve()));
+        assert!(bool::from(t.is_small_order()));
+        // Only the identity is both small order and torsion free.
+        assert_eq!(bool::from(t.is_identity()), i == 0);
+        assert_eq!(bool::from(t.is_torsion_free()), i == 0);
+        // A mix of both components is neither.
+        let mixed = multiples[0] + t;
+        assert!(bool::from(mixed.is_on_curve()));
+        assert!(!bool::from(mixed.is_small_order()));
+        assert_eq!(bool::from(mixed.is_torsion_free()), i == 0);
+    }
+    let id = EdwardsPoint::IDENTITY;
+    assert!(bool::from(id.is_identity()));
+    assert!(bool::from(id.is_small_order()));
+    assert!(bool::from(id.is_torsion_free()));
+    let (x, y) = basepoint();
+    assert!(bool::from((multiples[0] + point((-x, y))).is_identity()));
+}
+
+#[test]
+fn test_eight_torsion() {
+    // The encodings of i⋅T, matching curve25519-dalek's EIGHT_TORSION.
+    let encodings = [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc05",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a",
+        "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac03fa",
+        "0000000000000000000000000000000000000000000000000000000000000080",
+        "26e8958fc2b227b045c3f489f2ef98f0d5dfac05d3c63339b13802886d53fc85",
+    ];
+    let mut acc = EdwardsPoint::IDENTITY;
+    for (t, encoding) in EIGHT_TORSION.iter().zip(encodings) {
+        assert!(bool::from(t.is_on_curve()));
+        assert!(bool::from(t.is_small_order()));
+        assert_eq!(t.compress(), compressed(encoding));
+        assert_points_eq(t, &acc);
+        acc += EIGHT_TORSION[1];
+    }
+    assert_points_eq(&acc, &EdwardsPoint::IDENTITY);
+    // These are all of the points of small order.
+    for t in affine_torsion() {
+        let t = point(t);
+        assert!(EIGHT_TORSION.iter().any(|u| bool::from(t.ct_eq(u))));
+    }
+}
+
+#[test]
+fn test_mul_by_cofactor() {
+    let b = point(basepoint());
+    let b8 = b.double().double().double();
+    assert_points_eq(&b.mul_by_cofactor(), &b8);
+    for t in &EIGHT_TORSION {
+        assert_points_eq(&t.mul_by_cofactor(), &EdwardsPoint::IDENTITY);
+        let mixed = b + t;
+        assert!(!bool::from(mixed.is_torsion_free()) || bool::from(t.is_identity()));
+        assert_points_eq(&mixed.mul_by_cofactor(), &b8);
+        assert!(bool::from(mixed.mul_by_cofactor().is_torsion_free()));
+    }
+}
+
+#[test]
+fn test_basepoint_constant() {
+    // From RFC 8032, section 5.1, in little endian hex:
+    // x = 15112221349535400772501151409588531511454012693041857206046113283949847762202,
+    // y = 46316835694926478169428394003475163141307993866256225615783033603165251855960.
+    let x = "1ad5258f602d56c9b2a7259560c72c695cdcd6fd31e2a4c0fe536ecdd3366921";
+    let y = "5866666666666666666666666666666666666666666666666666666666666666";
+    let (bx, by) = ED25519_BASEPOINT_POINT.to_affine();
+    assert_eq!(CompressedEdwardsY(bx.to_bytes()), compressed(x));
+    assert_eq!(CompressedEdwardsY(by.to_bytes()), compressed(y));
+    assert_points_eq(&ED25519_BASEPOINT_POINT, &point(basepoint()));
+    assert_eq!(
+        ED25519_BASEPOINT_POINT.compress(),
+        ED25519_BASEPOINT_COMPRESSED
+    );
+    assert_eq!(
+        ED25519_BASEPOINT_COMPRESSED,
+        compressed(BASEPOINT_COMPRESSED)
+    );
+    assert_points_eq(
+        &ED25519_BASEPOINT_COMPRESSED.decompress().unwrap(),
+        &ED25519_BASEPOINT_POINT,
+    );
+    assert!(bool::from(ED25519_BASEPOINT_POINT.is_on_curve()));
+    assert!(bool::from(ED25519_BASEPOINT_POINT.is_torsion_free()));
+    assert!(!bool::from(ED25519_BASEPOINT_POINT.is_small_order()));
+}
+
+#[test]
+fn test_identity_constant() {
+    let id = EdwardsPoint::IDENTITY;
+    assert_eq!(
+        id.compress(),
+        compressed("0100000000000000000000000000000000000000000000000000000000000000")
+    );
+    assert!(bool::from(id.is_on_curve()));
+    assert_points_eq(&(ED25519_BASEPOINT_POINT + id), &ED25519_BASEPOINT_POINT);
+}
+
+/// Multiply a point by a scalar, with plain double and add.
+fn naive_mul(p: &EdwardsPoint, s: &Scalar) -> EdwardsPoint {
+    let mut out = EdwardsPoint::IDENTITY;
+    for bit in s.iter_bits() {
+        out = out.double();
+        if bool::from(bit) {
+            out += p;
+        }
+    }
+    out
+}
+
+/// Some scalars, including edge cases for the signed digits.
+fn test_scalars() -> Vec<Scalar> {
+    let mut out: Vec<Scalar> = (0..20u64).map(Scalar::from).collect();
+    out.push(-Scalar::ONE);
+    out.push(Scalar::from(8u64) * Scalar::from(16u64).pow_vartime(&[63]));
+    out.push(Scalar::from_bytes_mod_order(&[0x88; 32]));
+    out.push(Scalar::from_bytes_mod_order(&[0xF7; 32]));
+    for i in 0..8u8 {
+        out.push(Scalar::from_bytes_mod_order_wide(
+            &[i.wrapping_mul(0x3D); 64],
+        ));
+    }
+    out
+}
+
+#[test]
+fn test_basepoint_table_mul() {
+    let multiples = affine_multiples(16);
+    for table in [
+        &EdwardsBasepointTable::new(&ED25519_BASEPOINT_POINT),
+        &ED25519_BASEPOINT_TABLE,
+    ] {
+        for (i, expected) in multiples.iter().enumerate() {
+            let s = Scalar::from(i as u64 + 1);
+            assert_points_eq(&(table * &s), &point(*expected));
+        }
+        for s in test_scalars() {
+            let expected = naive_mul(&ED25519_BASEPOINT_POINT, &s);
+            assert_points_eq(&(table * &s), &expected);
+            assert_points_eq(&(&s * table), &expected);
+        }
+        assert_points_eq(&(table * &Scalar::ZERO), &EdwardsPoint::IDENTITY);
+        assert_points_eq(
+            &(table * &-Scalar::ONE),
+            &point((-multiples[0].0, multiples[0].1)),
+        );
+    }
+}
+
+#[test]
+fn test_baked_basepoint_table() {
+    // Between them, the multiples j⋅16ⁱ, for 1 ≤ j ≤ 8, look up every entry of the
+    // table, so this checks each of them against a freshly computed table.
+    let table = EdwardsBasepointTable::new(&ED25519_BASEPOINT_POINT);
+    let sixteen = Scalar::from(16u64);
+    let mut power = Scalar::ONE;
+    for _ in 0..64 {
+        for j in 1..=8u64 {
+            let s = power * Scalar::from(j);
+            assert_points_eq(&(&ED25519_BASEPOINT_TABLE * &s), &(&table * &s));
+        }
+        power *= sixteen;
+    }
+}
+
+#[test]
+fn test_small_tables() {
+    use std::mem::size_of;
+
+    let entries = if cfg!(feature = "small-tables") {
+        4
+    } else {
+        32
+    } * 8;
+    assert_eq!(
+        size_of::<EdwardsBasepointTable>(),
+        entries * size_of::<AffineNiels>()
+    );
+    let entries = if cfg!(feature = "small-tables") {
+        8
+    } else {
+        64
+    } * 4;
+    assert_eq!(
+        size_of::<EdwardsPrecomputation>(),
+        entries * size_of::<AffineNiels>()
+    );
+}
+
+#[test]
+fn test_basepoint_table_with_other_points() {
+    // The table works for any point, including ones with a torsion component.
+    let b3 = ED25519_BASEPOINT_POINT.double() + ED25519_BASEPOINT_POINT;
+    for p in [b3, b3 + EIGHT_TORSION[3], EIGHT_TORSION[1]] {
+        let table = EdwardsBasepointTable::new(&p);
+        for s in test_scalars() {
+            assert_points_eq(&(&table * &s), &naive_mul(&p, &s));
+        }
+    }
+}
+
+#[test]
+fn test_vartime_double_scalar_mul_basepoint() {
+    let b = ED25519_BASEPOINT_POINT;
+    let points = [
+        EdwardsPoint::IDENTITY,
+        b,
+        b.double() + b,
+        b.double() + EIGHT_TORSION[5],
+        EIGHT_TORSION[1],
+    ];
+    let scalars = test_scalars();
+    for p in &points {
+        for (x, y) in scalars.iter().zip(scalars.iter().rev()) {
+            let expected = naive_mul(p, x) + naive_mul(&b, y);
+            let actual = EdwardsPoint::vartime_double_scalar_mul_basepoint(x, p, y);
+            assert_points_eq(&actual, &expected);
+        }
+    }
+    let zero = Scalar::ZERO;
+    assert_points_eq(
+        &EdwardsPoint::vartime_double_scalar_mul_basepoint(&zero, &b, &zero),
+        &EdwardsPoint::IDENTITY,
+    );
+}
+
+/// Some points, and scalars, for multiscalar multiplication, along with Σ aᵢ⋅Pᵢ.
+fn msm_inputs(n: usize) -> (Vec<Scalar>, Vec<EdwardsPoint>, EdwardsPoint) {
+    let scalars: Vec<Scalar> = test_scalars().into_iter().cycle().skip(3).take(n).collect();
+    let mut points = Vec::with_capacity(n);
+    let mut acc = ED25519_BASEPOINT_POINT;
+    for i in 0..n {
+        points.push(acc + EIGHT_TORSION[i % 8]);
+        acc = acc.double() + ED25519_BASEPOINT_POINT;
+    }
+    let mut expected = EdwardsPoint::IDENTITY;
+    for (s, p) in scalars.iter().zip(points.iter()) {
+        expected += naive_mul(p, s);
+    }
+    (scalars, points, expected)
+}
+
+#[test]
+fn test_multiscalar_mul() {
+    for n in [0, 1, 2, 5, 17] {
+        let (scalars, points, expected) = msm_inputs(n);
+        assert_points_eq(&EdwardsPoint::multiscalar_mul(&scalars, &points), &expected);
+        assert_points_eq(
+            &EdwardsPoint::vartime_multiscalar_mul(&scalars, &points),
+            &expected,
+        );
+        // Owned items work too.
+        assert_points_eq(
+            &EdwardsPoint::vartime_multiscalar_mul(scalars.clone(), points.clone()),
+            &expected,
+        );
+    }
+}
+
+#[test]
+fn test_multiscalar_mul_with_zero_scalars() {
+    let (_, points, _) = msm_inputs(4);
+    let zeros = [Scalar::ZERO; 4];
+    assert_points_eq(
+        &EdwardsPoint::multiscalar_mul(&zeros, &points),
+        &EdwardsPoint::IDENTITY,
+    );
+    assert_points_eq(
+        &EdwardsPoint::vartime_multiscalar_mul(&zeros, &points),
+        &EdwardsPoint::IDENTITY,
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_multiscalar_mul_rejects_mismatched_lengths() {
+    let (scalars, points, _) = msm_inputs(3);
+    EdwardsPoint::vartime_multiscalar_mul(&scalars, &points[..2]);
+}
+
+#[test]
+fn test_vartime_precomputed_multiscalar_mul() {
+    for n in [0, 1, 2, 5, 17] {
+        let (scalars, points, expected) = msm_inputs(n);
+        let precomputation = VartimeEdwardsPrecomputation::new(&points);
+        assert_points_eq(&precomputation.vartime_multiscalar_mul(&scalars), &expected);
+        // Every split between static and dynamic points gives the same sum.
+        for k in 0..=n {
+            let precomputation = VartimeEdwardsPrecomputation::new(&points[..k]);
+            assert_points_eq(
+                &precomputation.vartime_mixed_multiscalar_mul(
+                    &scalars[..k],
+                    &scalars[k..],
+                    &points[k..],
+                ),
+                &expected,
+            );
+        }
+    }
+    // The same precomputation works with different scalars, and dynamic points.
+    let (scalars, points, _) = msm_inputs(6);
+    let precomputation = VartimeEdwardsPrecomputation::new(&points[..3]);
+    for shift in 1..4 {
+        let static_scalars: Vec<Scalar> = scalars
+            .iter()
+            .copied()
+            .cycle()
+            .skip(shift)
+            .take(3)
+            .collect();
+        let dynamic_points = [points[3 + shift % 3]; 3];
+        let mut expected = EdwardsPoint::IDENTITY;
+        for (s, p) in static_scalars.iter().zip(&points[..3]) {
+            expected += naive_mul(p, s);
+        }
+        for (s, p) in scalars[3..].iter().zip(&dynamic_points) {
+            expected += naive_mul(p, s);
+        }
+        assert_points_eq(
+            &precomputation.vartime_mixed_multiscalar_mul(
+                &static_scalars,
+                &scalars[3..],
+                &dynamic_points,
+            ),
+            &expected,
+        );
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_vartime_precomputed_multiscalar_mul_rejects_mismatched_lengths() {
+    let (scalars, points, _) = msm_inputs(3);
+    VartimeEdwardsPrecomputation::new(&points).vartime_multiscalar_mul(&scalars[..2]);
+}
+
+#[test]
+fn test_vartime_multiscalar_mul_with_many_points() {
+    // Around the switch to Pippenger's method, and for each of its window sizes.
+    for n in [189, 190, 500, 800] {
+        let (scalars, points, expected) = msm_inputs(n);
+        assert_points_eq(
+            &EdwardsPoint::vartime_multiscalar_mul(&scalars, &points),
+            &expected,
+        );
+    }
+    // With 200 points, w = 6, and digits of -2⁵ land in the top bucket.
+    let (_, points, _) = msm_inputs(200);
+    let scalars: Vec<Scalar> = (0..200)
+        .map(|i| Scalar::from(32u64 << (6 * (i % 8))))
+        .collect();
+    let mut expected = EdwardsPoint::IDENTITY;
+    for (s, p) in scalars.iter().zip(points.iter()) {
+        expected += naive_mul(p, s);
+    }
+    assert_points_eq(
+        &EdwardsPoint::vartime_multiscalar_mul(&scalars, &points),
+        &expected,
+    );
+}
+
+#[test]
+fn test_multiscalar_mul_with_many_points() {
+    // The constant time version keeps using Straus' method, past the vartime switch.
+    let (scalars, points, expected) = msm_inputs(200);
+    assert_points_eq(&EdwardsPoint::multiscalar_mul(&scalars, &points), &expected);
+    assert_points_eq(
+        &EdwardsPoint::multiscalar_mul(&scalars, &points),
+        &EdwardsPoint::vartime_multiscalar_mul(&scalars, &points),
+    );
+}
+
+#[test]
+fn test_vartime_mul() {
+    let b = ED25519_BASEPOINT_POINT;
+    let points = [
+        EdwardsPoint::IDENTITY,
+        b,
+        b.double() + b,
+        b + EIGHT_TORSION[3],
+        EIGHT_TORSION[7],
+    ];
+    for p in &points {
+        for s in test_scalars() {
+            assert_points_eq(&p.vartime_mul(&s), &naive_mul(p, &s));
+        }
+    }
+    // Multiplying by ℓ - 1 negates points in the prime order subgroup.
+    let neg_b = b.vartime_mul(&-Scalar::ONE);
+    assert_points_eq(&(neg_b + b), &EdwardsPoint::IDENTITY);
+}
+
+#[test]
+fn test_precomputation() {
+    let b = ED25519_BASEPOINT_POINT;
+    let points = [
+        EdwardsPoint::IDENTITY,
+        b,
+        b.double() + b,
+        b + EIGHT_TORSION[3],
+        EIGHT_TORSION[7],
+    ];
+    // Scalars with non-zero digits on either side of each chunk boundary.
+    let mut scalars = test_scalars();
+    for j in [63u64, 64, 127, 128, 191, 192, 252] {
+        let power = Scalar::from(2u64).pow_vartime(&[j]);
+        scalars.push(power);
+        scalars.push(power * Scalar::from(127u64));
+        scalars.push(-power);
+    }
+    for p in &points {
+        let precomputation = EdwardsPrecomputation::new(p);
+        for s in &scalars {
+            assert_points_eq(&precomputation.vartime_mul(s), &naive_mul(p, s));
+        }
+    }
+}
+
+#[test]
+fn test_neg_sub_and_sum() {
+    let points: Vec<EdwardsPoint> = affine_multiples(6).into_iter().map(point).collect();
+    let (x, y) = basepoint();
+    assert_points_eq(&-points[0], &point((-x, y)));
+    assert_points_eq(&-EdwardsPoint::IDENTITY, &EdwardsPoint::IDENTITY);
+    assert_points_eq(&-EIGHT_TORSION[4], &EIGHT_TORSION[4]);
+    assert_points_eq(&-EIGHT_TORSION[1], &EIGHT_TORSION[7]);
+    for p in &points {
+        assert_points_eq(&(p - p), &EdwardsPoint::IDENTITY);
+        assert_points_eq(&(p + -p), &EdwardsPoint::IDENTITY);
+        assert_points_eq(&-(-p), p);
+    }
+    // (k + 1)⋅B - (j + 1)⋅B = (k - j)⋅B.
+    for k in 1..6 {
+        for j in 0..k {
+            assert_points_eq(&(points[k] - points[j]), &points[k - j - 1]);
+            let mut acc = points[k];
+            acc -= points[j];
+            assert_points_eq(&acc, &points[k - j - 1]);
+        }
+    }
+    // B + 2⋅B + ... + 6⋅B = 21⋅B.
+    let expected = naive_mul(&ED25519_BASEPOINT_POINT, &Scalar::from(21u64));
+    assert_points_eq(&points.iter().sum(), &expected);
+    assert_points_eq(&points.clone().into_iter().sum(), &expected);
+    assert_points_eq(
+        &Vec::<EdwardsPoint>::new().into_iter().sum(),
+        &EdwardsPoint::IDENTITY,
+    );
+}
+
+#[test]
+fn test_torsion_safe_eq() {
+    let b = ED25519_BASEPOINT_POINT;
+    let b2 = b.double();
+    for t in &EIGHT_TORSION {
+        for u in &EIGHT_TORSION {
+            let (p, q) = (b + t, b + u);
+            assert!(bool::from(p.torsion_safe_eq(&q)));
+            assert_eq!(bool::from(p.ct_eq(&q)), bool::from(t.ct_eq(u)));
+            assert!(!bool::from(p.torsion_safe_eq(&(b2 + u))));
+            assert_eq!(
+                bool::from(p.torsion_safe_eq(&q)),
+                bool::from(p.mul_by_cofactor().ct_eq(&q.mul_by_cofactor()))
+            );
+        }
+        assert!(bool::from(t.torsion_safe_eq(&EdwardsPoint::IDENTITY)));
+    }
+    assert!(!bool::from(b.torsion_safe_eq(&-b)));
+}
+
+#[test]
+fn test_elligator_representative_vector() {
+    // u₀ and Q₀ = map_to_curve(u₀), for the empty message, from RFC 9380, Appendix J.5.1.
+    let u0 = compressed("3a3f202d71eec79a7907b0e20d38d5e7e674e1fa88ef6e8cf9b58c3c81f4fe03").0;
+    let q0 = EdwardsPoint::from_elligator_representative(&u0);
+    let (x, y) = q0.to_affine();
+    assert_eq!(
+        x.to_bytes(),
+        compressed("a7098eb860eeb92e3b6d8096d4ae6e493cc7edec8d438b9e7b61bb658f114965").0
+    );
+    assert_eq!(
+        y.to_bytes(),
+        compressed("eb9fcfb43f979a433f5d7c8ca082330b68c602d6ba228d0468ed47cfc8bc1573").0
+    );
+    // u₀ is non-negative, so it's the representative we get back.
+    assert_eq!(q0.to_elligator_representative(0).unwrap(), u0);
+}
+
+#[test]
+fn test_elligator_representative_round_trip() {
+    let mut p = ED25519_BASEPOINT_POINT;
+    let mut found = 0;
+    for i in 0..64 {
+        let q = p + EIGHT_TORSION[i % 8];
+        let tweak = (i as u8) << 6;
+        let repr = q.to_elligator_representative(tweak);
+        if bool::from(repr.is_some()) {
+            found += 1;
+            let repr = repr.unwrap();
+            assert_eq!(repr[31] & 0xC0, tweak);
+            assert_points_eq(&EdwardsPoint::from_elligator_representative(&repr), &q);
+        }
+        p += ED25519_BASEPOINT_POINT;
+    }
+    // About half of the points have a representative.
+    assert!(
+        (16..=48).contains(&found),
+        "{found} of 64 points were representable"
+    );
+    // The exceptional case of the map sends 0 to the identity.
+    let repr = EdwardsPoint::IDENTITY
+        .to_elligator_representative(0)
+        .unwrap();
+    assert_eq!(repr, [0; 32]);
+    assert_points_eq(
+        &EdwardsPoint::from_elligator_representative(&repr),
+        &EdwardsPoint::IDENTITY,
+    );
+}
+
+#[test]
+fn test_every_representative_decodes() {
+    for i in 0..64u8 {
+        let repr = [i.wrapping_mul(0x9D) ^ 0x5A; 32];
+        let p = EdwardsPoint::from_elligator_representative(&repr);
+        assert!(bool::from(p.is_on_curve()));
+        // The top two bits are ignored.
+        let mut masked = repr;
+        masked[31] &= 0x3F;
+        assert_points_eq(&EdwardsPoint::from_elligator_representative(&masked), &p);
+    }
+}
+
+#[test]
+fn test_conditional_select_and_negate() {
+    use subtle::{Choice, ConditionallyNegatable, ConditionallySelectable};
+
+    let b = ED25519_BASEPOINT_POINT;
+    let p = b.double();
+    let (yes, no) = (Choice::from(1), Choice::from(0));
+    assert_points_eq(&EdwardsPoint::conditional_select(&b, &p, no), &b);
+    assert_points_eq(&EdwardsPoint::conditional_select(&b, &p, yes), &p);
+    let mut q = b;
+    q.conditional_negate(no);
+    assert_points_eq(&q, &b);
+    q.conditional_negate(yes);
+    assert_points_eq(&q, &-b);
+
+    // The cached forms can only be checked by adding them to something.
+    let id = EdwardsPoint::IDENTITY;
+    let (bn, pn) = (b.to_projective_niels(), p.to_projective_niels());
+    assert_points_eq(
+        &(&id + &ProjectiveNiels::conditional_select(&bn, &pn, yes)),
+        &p,
+    );
+    let mut qn = bn;
+    qn.conditional_negate(yes);
+    assert_points_eq(&(&id + &qn), &-b);
+    let (ba, pa) = (b.to_affine_niels(), p.to_affine_niels());
+    assert_points_eq(&(&id + &AffineNiels::conditional_select(&ba, &pa, yes)), &p);
+    let mut qa = ba;
+    qa.conditional_negate(yes);
+    assert_points_eq(&(&id + &qa), &-b);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let b = ED25519_BASEPOINT_POINT;
+    let json = serde_json::to_string(&b).unwrap();
+    // Points go through their compressed encoding, which is serialized as is.
+    assert_eq!(json, serde_json::to_string(&b.compress().0).unwrap());
+    assert_eq!(json, serde_json::to_string(&b.compress()).unwrap());
+    let decoded: EdwardsPoint = serde_json::from_str(&json).unwrap();
+    assert_points_eq(&decoded, &b);
+    let decoded: CompressedEdwardsY = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, ED25519_BASEPOINT_COMPRESSED);
+
+    // y = 2 isn't the y coordinate of any point, and y = P isn't canonical, so neither
+    // decodes to a point, but both are fine as compressed encodings.
+    let mut p_bytes = [0xFF; 32];
+    p_bytes[0] = 0xED;
+    p_bytes[31] = 0x7F;
+    let mut two = [0u8; 32];
+    two[0] = 2;
+    for bytes in [two, p_bytes] {
+        let json = serde_json::to_string(&bytes).unwrap();
+        assert!(serde_json::from_str::<EdwardsPoint>(&json).is_err());
+        let compressed: CompressedEdwardsY = serde_json::from_str(&json).unwrap();
+        assert_eq!(compressed.0, bytes);
+    }
+    assert!(serde_json::from_str::<EdwardsPoint>("[1, 2, 3]").is_err());
+    assert!(serde_json::from_str::<CompressedEdwardsY>("[1, 2, 3]").is_err());
+}
+
+#[test]
+fn test_formatting() {
+    let hex = "5866666666666666666666666666666666666666666666666666666666666666";
+    let b = ED25519_BASEPOINT_COMPRESSED;
+    assert_eq!(format!("{b}"), hex);
+    assert_eq!(format!("{b:x}"), hex);
+    assert_eq!(format!("{b:#x}"), format!("0x{hex}"));
+    assert_eq!(format!("{b:?}"), format!("CompressedEdwardsY({hex})"));
+    // Debug output doesn't depend on the coordinates a point happens to have.
+    let doubled = ED25519_BASEPOINT_POINT + ED25519_BASEPOINT_POINT;
+    assert_eq!(
+        format!("{:?}", doubled),
+        format!("{:?}", ED25519_BASEPOINT_POINT.double())
+    );
+    assert_eq!(
+        format!("{:?}", ED25519_BASEPOINT_POINT),
+        format!("EdwardsPoint({hex})")
+    );
+}
+
+#[test]
+fn test_mul_by_pow_2() {
+    let p = ED25519_BASEPOINT_POINT + EIGHT_TORSION[3];
+    let mut expected = p;
+    for k in 0..12 {
+        let out = p.mul_by_pow_2(k);
+        assert!(bool::from(out.is_on_curve()));
+        assert_points_eq(&out, &expected);
+        assert_points_eq(&out, &p.vartime_mul(&Scalar::from(1u64 << k)));
+        expected = expected.double();
+    }
+    assert_points_eq(&p.mul_by_pow_2(3), &p.mul_by_cofactor());
+}
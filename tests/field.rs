@@ -0,0 +1,220 @@
+//! Differential tests of field arithmetic, against num-bigint.
+#![cfg(not(target_arch = "wasm32"))]
+use ck_dodo::curve::field::{reference, Fp};
+use num_bigint::BigUint;
+use proptest::prelude::*;
+
+/// P = 2²⁵⁵ - 19, as little endian 64 bit limbs.
+const P: [u64; 4] = [
+    0xFFFFFFFFFFFFFFED,
+    0xFFFFFFFFFFFFFFFF,
+    0xFFFFFFFFFFFFFFFF,
+    0x7FFFFFFFFFFFFFFF,
+];
+
+fn p() -> BigUint {
+    (BigUint::from(1u8) << 255u32) - 19u8
+}
+
+fn to_big(x: &Fp) -> BigUint {
+    BigUint::from_bytes_le(&x.to_bytes())
+}
+
+/// A 64 bit limb, biased towards values likely to trip up carries.
+fn limb() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        Just(0),
+        Just(1),
+        Just(u64::MAX),
+        Just(u64::MAX - 1),
+        Just(1 << 63),
+        Just((1 << 63) - 1),
+        Just(P[0]),
+        any::<u64>(),
+    ]
+}
+
+/// A 32 byte encoding of any integer < 2²⁵⁶, including ones near P, and above it.
+fn encoding() -> impl Strategy<Value = [u8; 32]> {
+    let from_limbs = |limbs: [u64; 4]| {
+        let mut out = [0u8; 32];
+        for (chunk, limb) in out.chunks_exact_mut(8).zip(limbs) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        out
+    };
+    prop_oneof![
+        any::<[u8; 32]>(),
+        [limb(), limb(), limb(), limb()].prop_map(from_limbs),
+        // P + k, for a small k, which might be negative.
+        (-64i64..64).prop_map(move |k| {
+            let x = BigUint::from_bytes_le(&from_limbs(P));
+            let x = if k < 0 { x - (-k) as u64 } else { x + k as u64 };
+            let mut out = [0u8; 32];
+            let bytes = x.to_bytes_le();
+            out[..bytes.len()].copy_from_slice(&bytes);
+            out
+        }),
+    ]
+}
+
+/// An element, along with its value as an integer mod P.
+///
+/// Besides decoding elements directly, we also go through a few operations, to get
+/// elements whose internal representation isn't as tidy as a freshly decoded one.
+fn element() -> impl Strategy<Value = (Fp, BigUint)> {
+    (encoding(), encoding(), 0..3u8).prop_map(|(a, b, how)| {
+        let (x, y) = (Fp::from_bytes(&a), Fp::from_bytes(&b));
+        let (xi, yi) = (
+            BigUint::from_bytes_le(&a) % p(),
+            BigUint::from_bytes_le(&b) % p(),
+        );
+        match how {
+            0 => (x, xi),
+            1 => (x + y, (xi + yi) % p()),
+            _ => (x * y - x, (&xi * yi + p() - &xi) % p()),
+        }
+    })
+}
+
+/// Multiply two canonical encodings together using one of the reference fields.
+///
+/// The reference fields don't share a trait, so this is a macro.
+macro_rules! reference_mul {
+    ($F:ty, $x:expr, $y:expr) => {{
+        let product =
+            <$F>::from_canonical_bytes($x).unwrap() * <$F>::from_canonical_bytes($y).unwrap();
+        BigUint::from_bytes_le(&product.to_bytes())
+    }};
+}
+
+proptest! {
+    #[test]
+    fn from_bytes_reduces(bytes in encoding()) {
+        let x = Fp::from_bytes(&bytes);
+        prop_assert_eq!(to_big(&x), BigUint::from_bytes_le(&bytes) % p());
+    }
+
+    #[test]
+    fn from_bytes_wide_reduces(lo in encoding(), hi in encoding()) {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&lo);
+        bytes[32..].copy_from_slice(&hi);
+        let x = Fp::from_bytes_wide(&bytes);
+        prop_assert_eq!(to_big(&x), BigUint::from_bytes_le(&bytes) % p());
+    }
+
+    #[test]
+    fn add_matches((x, xi) in element(), (y, yi) in element()) {
+        prop_assert_eq!(to_big(&(x + y)), (xi + yi) % p());
+    }
+
+    #[test]
+    fn sub_matches((x, xi) in element(), (y, yi) in element()) {
+        prop_assert_eq!(to_big(&(x - y)), (xi + p() - yi) % p());
+    }
+
+    #[test]
+    fn neg_matches((x, xi) in element()) {
+        prop_assert_eq!(to_big(&-x), (p() - xi) % p());
+    }
+
+    #[test]
+    fn mul_matches((x, xi) in element(), (y, yi) in element()) {
+        prop_assert_eq!(to_big(&(x * y)), (xi * yi) % p());
+    }
+
+    #[test]
+    fn square_matches((x, xi) in element()) {
+        prop_assert_eq!(to_big(&x.square()), (&xi * &xi) % p());
+    }
+
+    #[test]
+    fn mul_small_matches((x, xi) in element(), k in any::<u32>()) {
+        prop_assert_eq!(to_big(&x.mul_small(k)), (xi * k) % p());
+    }
+
+    #[test]
+    fn mul_pow2_matches((x, xi) in element(), k in 0..300u32) {
+        prop_assert_eq!(to_big(&x.double()), (&xi * 2u8) % p());
+        prop_assert_eq!(to_big(&x.mul_pow2(k)), (xi << k) % p());
+    }
+
+    #[test]
+    fn invert_matches((x, xi) in element()) {
+        // Fermat's little theorem gives us an independent way to calculate the inverse.
+        let expected = xi.modpow(&(p() - 2u8), &p());
+        prop_assert_eq!(to_big(&x.invert()), expected.clone());
+        prop_assert_eq!(to_big(&x.invert_safegcd()), expected.clone());
+        prop_assert_eq!(to_big(&x.vartime_invert()), expected);
+    }
+
+    #[test]
+    fn sqrt_matches((x, xi) in element()) {
+        let square = (&xi * &xi) % p();
+        let root = Option::<Fp>::from(x.square().sqrt());
+        prop_assert!(root.is_some());
+        let root = to_big(&root.unwrap());
+        prop_assert_eq!((&root * &root) % p(), square);
+        // The root we return is always non-negative, i.e. even.
+        prop_assert!(!root.bit(0));
+    }
+
+    #[test]
+    fn reference_fields_match((x, xi) in element(), (y, yi) in element()) {
+        let expected = (xi * yi) % p();
+        let (x, y) = (x.to_bytes(), y.to_bytes());
+        prop_assert_eq!(reference_mul!(reference::Fp, &x, &y), expected.clone());
+        prop_assert_eq!(reference_mul!(reference::MontgomeryFp, &x, &y), expected.clone());
+        prop_assert_eq!(reference_mul!(reference::BarrettFp, &x, &y), expected);
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+proptest! {
+    #[test]
+    fn vec4_matches((x, _) in element(), (y, _) in element()) {
+        use ck_dodo::curve::field::FpVec4;
+
+        let v = FpVec4::new(&x, &y, &x, &-y);
+        let w = FpVec4::new(&y, &x, &x, &Fp::ZERO);
+        let lanes = |f: fn(Fp, Fp) -> Fp| {
+            [f(x, y), f(y, x), f(x, x), f(-y, Fp::ZERO)].map(|z| z.to_bytes())
+        };
+        let split = |v: FpVec4| v.split().map(|z| z.to_bytes());
+        prop_assert_eq!(split(v), lanes(|a, _| a));
+        prop_assert_eq!(split(v + w), lanes(|a, b| a + b));
+        prop_assert_eq!(split(v - w), lanes(|a, b| a - b));
+        prop_assert_eq!(split(v * w), lanes(|a, b| a * b));
+        prop_assert_eq!(split(v.square()), lanes(|a, _| a.square()));
+        prop_assert_eq!(split(v.pow_p58()), lanes(|a, _| a.pow_p58()));
+        // Chaining operations, without going through Fp, keeps the limbs in range.
+        prop_assert_eq!(
+            split(((v - w) * (v + w)).square()),
+            lanes(|a, b| ((a - b) * (a + b)).square())
+        );
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+proptest! {
+    #[test]
+    fn vec2_matches((x, _) in element(), (y, _) in element()) {
+        use ck_dodo::curve::field::FpVec2;
+
+        let v = FpVec2::new(&x, &-y);
+        let w = FpVec2::new(&y, &x);
+        let lanes = |f: fn(Fp, Fp) -> Fp| [f(x, y), f(-y, x)].map(|z| z.to_bytes());
+        let split = |v: FpVec2| v.split().map(|z| z.to_bytes());
+        prop_assert_eq!(split(v), lanes(|a, _| a));
+        prop_assert_eq!(split(v + w), lanes(|a, b| a + b));
+        prop_assert_eq!(split(v - w), lanes(|a, b| a - b));
+        prop_assert_eq!(split(v * w), lanes(|a, b| a * b));
+        prop_assert_eq!(split(v.square()), lanes(|a, _| a.square()));
+        // Chaining operations, without going through Fp, keeps the limbs in range.
+        prop_assert_eq!(
+            split(((v - w) * (v + w)).square()),
+            lanes(|a, b| ((a - b) * (a + b)).square())
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Tests of the implementations of the traits of the group and ff crates.
+#![cfg(feature = "group")]
+use ck_dodo::curve::{
+    ristretto::{RistrettoPoint, RISTRETTO_BASEPOINT_POINT},
+    scalar::Scalar,
+};
+use ff::{Field, PrimeField};
+use group::{Group, GroupEncoding};
+use rand_core::{impls, RngCore};
+
+/// A deterministic generator, SplitMix64, so that failures are reproducible.
+struct TestRng(u64);
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Raise a field element to the power 2ᵏ.
+fn pow2k<F: Field>(x: F, k: u32) -> F {
+    (0..k).fold(x, |acc, _| acc.square())
+}
+
+#[test]
+fn test_prime_field_constants() {
+    let modulus = format!("0x{:016x}", Scalar::MODULUS[3])
+        + &Scalar::MODULUS[..3]
+            .iter()
+            .rev()
+            .map(|limb| format!("{limb:016x}"))
+            .collect::<String>();
+    assert_eq!(<Scalar as PrimeField>::MODULUS, modulus);
+    assert_eq!(Scalar::TWO_INV.double(), Scalar::ONE);
+
+    let g = Scalar::MULTIPLICATIVE_GENERATOR;
+    // g is a non-square, so its square root doesn't exist.
+    assert!(bool::from(g.sqrt().is_none()));
+    assert_eq!(pow2k(g, Scalar::S), Scalar::DELTA);
+    // The root of unity is g to the odd part of ℓ - 1.
+    let mut t = Scalar::MODULUS;
+    t[0] -= 1;
+    for i in 0..4 {
+        t[i] >>= Scalar::S;
+        if i < 3 {
+            t[i] |= t[i + 1] << (64 - Scalar::S);
+        }
+    }
+    assert_eq!(g.pow_vartime(&t), Scalar::ROOT_OF_UNITY);
+    assert_eq!(pow2k(Scalar::ROOT_OF_UNITY, Scalar::S), Scalar::ONE);
+    assert_ne!(pow2k(Scalar::ROOT_OF_UNITY, Scalar::S - 1), Scalar::ONE);
+    assert_eq!(
+        Scalar::ROOT_OF_UNITY * Scalar::ROOT_OF_UNITY_INV,
+        Scalar::ONE
+    );
+}
+
+#[test]
+fn test_field_arithmetic() {
+    let mut rng = TestRng(1);
+    assert!(bool::from(
+        <Scalar as Field>::invert(&Scalar::ZERO).is_none()
+    ));
+    for _ in 0..32 {
+        let x = <Scalar as Field>::random(&mut rng);
+        let y = <Scalar as Field>::random(&mut rng);
+        assert_eq!(x.double(), x + x);
+        assert_eq!(<Scalar as Field>::invert(&x).unwrap() * x, Scalar::ONE);
+
+        let x2 = x.square();
+        let root = x2.sqrt().unwrap();
+        assert!(root == x || root == -x);
+        let (is_square, root) = Scalar::sqrt_ratio(&(x2 * y), &y);
+        assert!(bool::from(is_square));
+        assert_eq!(root.square(), x2);
+        // Exactly one of y, and g⋅y, is a square.
+        let (is_square, _) = Scalar::sqrt_ratio(&y, &Scalar::ONE);
+        let (g_is_square, _) =
+            Scalar::sqrt_ratio(&(y * Scalar::MULTIPLICATIVE_GENERATOR), &Scalar::ONE);
+        assert!(bool::from(is_square ^ g_is_square));
+
+        assert_eq!(Scalar::from_repr(x.to_repr()).unwrap(), x);
+        assert_eq!(bool::from(x.is_odd()), x.to_bytes()[0] & 1 == 1);
+    }
+    assert!(bool::from(Scalar::from_repr([0xFF; 32]).is_none()));
+}
+
+#[test]
+fn test_group() {
+    let mut rng = TestRng(2);
+    let g = RistrettoPoint::generator();
+    assert_eq!(g, RISTRETTO_BASEPOINT_POINT);
+    assert!(bool::from(RistrettoPoint::identity().is_identity()));
+    for _ in 0..8 {
+        let p = RistrettoPoint::random(&mut rng);
+        assert!(!bool::from(p.is_identity()));
+        assert_eq!(Group::double(&p), p + p);
+
+        let s = <Scalar as Field>::random(&mut rng);
+        let mut q = p;
+        q *= s;
+        assert_eq!(q, p * s);
+        assert_eq!(q, p.vartime_mul(&s));
+
+        let bytes = GroupEncoding::to_bytes(&p);
+        assert_eq!(RistrettoPoint::from_bytes(&bytes).unwrap(), p);
+        assert_eq!(RistrettoPoint::from_bytes_unchecked(&bytes).unwrap(), p);
+    }
+    let mut bytes = GroupEncoding::to_bytes(&g);
+    bytes[0] ^= 1;
+    assert!(bool::from(RistrettoPoint::from_bytes(&bytes).is_none()));
+}
+
+/// A Diffie-Hellman exchange, written only against the traits.
+fn diffie_hellman<G: Group + GroupEncoding>(rng: &mut impl RngCore) {
+    let a = G::Scalar::random(&mut *rng);
+    let b = G::Scalar::random(&mut *rng);
+    let (pa, pb) = (G::generator() * a, G::generator() * b);
+    let pa = G::from_bytes(&pa.to_bytes()).unwrap();
+    let pb = G::from_bytes(&pb.to_bytes()).unwrap();
+    assert_eq!((pb * a).to_bytes().as_ref(), (pa * b).to_bytes().as_ref());
+    let sum: G = [pa, pb].iter().sum();
+    assert_eq!(sum, G::generator() * (a + b));
+}
+
+#[test]
+fn test_generic_code() {
+    diffie_hellman::<RistrettoPoint>(&mut TestRng(3));
+}
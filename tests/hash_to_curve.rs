@@ -0,0 +1,111 @@
+//! Test vectors for hashing to edwards25519, from RFC 9380, Appendix J.5.
+#![cfg(feature = "digest")]
+use ck_dodo::curve::edwards::EdwardsPoint;
+use sha2::Sha512;
+
+const RO_DST: &[u8] = b"QUUX-V01-CS02-with-edwards25519_XMD:SHA-512_ELL2_RO_";
+const NU_DST: &[u8] = b"QUUX-V01-CS02-with-edwards25519_XMD:SHA-512_ELL2_NU_";
+
+/// Parse a 32 byte big endian integer, written in hex, into its little endian encoding.
+fn hex32_be(s: &str) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().rev().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    out
+}
+
+/// The messages used by the test vectors, in order.
+fn messages() -> [Vec<u8>; 5] {
+    [
+        b"".to_vec(),
+        b"abc".to_vec(),
+        b"abcdef0123456789".to_vec(),
+        [b"q128_".as_slice(), &[b'q'; 128]].concat(),
+        [b"a512_".as_slice(), &[b'a'; 512]].concat(),
+    ]
+}
+
+fn assert_affine(point: &EdwardsPoint, x: &str, y: &str) {
+    assert!(bool::from(point.is_on_curve()));
+    assert!(bool::from(point.is_torsion_free()));
+    let (px, py) = point.to_affine();
+    assert_eq!(px.to_bytes(), hex32_be(x));
+    assert_eq!(py.to_bytes(), hex32_be(y));
+}
+
+#[test]
+fn hash_to_curve_vectors() {
+    let expected = [
+        (
+            "3c3da6925a3c3c268448dcabb47ccde5439559d9599646a8260e47b1e4822fc6",
+            "09a6c8561a0b22bef63124c588ce4c62ea83a3c899763af26d795302e115dc21",
+        ),
+        (
+            "608040b42285cc0d72cbb3985c6b04c935370c7361f4b7fbdb1ae7f8c1a8ecad",
+            "1a8395b88338f22e435bbd301183e7f20a5f9de643f11882fb237f88268a5531",
+        ),
+        (
+            "6d7fabf47a2dc03fe7d47f7dddd21082c5fb8f86743cd020f3fb147d57161472",
+            "53060a3d140e7fbcda641ed3cf42c88a75411e648a1add71217f70ea8ec561a6",
+        ),
+        (
+            "5fb0b92acedd16f3bcb0ef83f5c7b7a9466b5f1e0d8d217421878ea3686f8524",
+            "2eca15e355fcfa39d2982f67ddb0eea138e2994f5956ed37b7f72eea5e89d2f7",
+        ),
+        (
+            "0efcfde5898a839b00997fbe40d2ebe950bc81181afbd5cd6b9618aa336c1e8c",
+            "6dc2fc04f266c5c27f236a80b14f92ccd051ef1ff027f26a07f8c0f327d8f995",
+        ),
+    ];
+    for (msg, (x, y)) in messages().iter().zip(expected) {
+        assert_affine(&EdwardsPoint::hash_to_curve::<Sha512>(msg, RO_DST), x, y);
+    }
+}
+
+#[test]
+fn encode_to_curve_vectors() {
+    let expected = [
+        (
+            "1ff2b70ecf862799e11b7ae744e3489aa058ce805dd323a936375a84695e76da",
+            "222e314d04a4d5725e9f2aff9fb2a6b69ef375a1214eb19021ceab2d687f0f9b",
+        ),
+        (
+            "5f13cc69c891d86927eb37bd4afc6672360007c63f68a33ab423a3aa040fd2a8",
+            "67732d50f9a26f73111dd1ed5dba225614e538599db58ba30aaea1f5c827fa42",
+        ),
+        (
+            "1dd2fefce934ecfd7aae6ec998de088d7dd03316aa1847198aecf699ba6613f1",
+            "2f8a6c24dd1adde73909cada6a4a137577b0f179d336685c4a955a0a8e1a86fb",
+        ),
+        (
+            "35fbdc5143e8a97afd3096f2b843e07df72e15bfca2eaf6879bf97c5d3362f73",
+            "2af6ff6ef5ebba128b0774f4296cb4c2279a074658b083b8dcca91f57a603450",
+        ),
+        (
+            "6e5e1f37e99345887fc12111575fc1c3e36df4b289b8759d23af14d774b66bff",
+            "2c90c3d39eb18ff291d33441b35f3262cdd307162cc97c31bfcc7a4245891a37",
+        ),
+    ];
+    for (msg, (x, y)) in messages().iter().zip(expected) {
+        assert_affine(&EdwardsPoint::encode_to_curve::<Sha512>(msg, NU_DST), x, y);
+    }
+}
+
+#[test]
+fn oversize_dst_is_hashed() {
+    // A tag longer than 255 bytes is replaced by H("H2C-OVERSIZE-DST-" || tag), so the
+    // two should give the same point.
+    use sha2::Digest;
+
+    let long_dst = [b'x'; 300];
+    let short_dst = Sha512::new()
+        .chain_update(b"H2C-OVERSIZE-DST-")
+        .chain_update(long_dst)
+        .finalize();
+    let a = EdwardsPoint::hash_to_curve::<Sha512>(b"msg", &long_dst);
+    let b = EdwardsPoint::hash_to_curve::<Sha512>(b"msg", &short_dst);
+    assert_eq!(a.compress(), b.compress());
+    let c = EdwardsPoint::hash_to_curve::<Sha512>(b"msg", RO_DST);
+    assert_ne!(a.compress(), c.compress());
+}
@@ -0,0 +1,86 @@
+//! Test vectors for hashing to ristretto255, following RFC 9380, Appendix B.
+//!
+//! Neither RFC has vectors for this suite, so these are regression vectors, generated
+//! with this crate. The map itself is checked against RFC 9496, in the ristretto tests.
+#![cfg(feature = "digest")]
+use ck_dodo::curve::ristretto::{CompressedRistretto, RistrettoPoint};
+use sha2::Sha512;
+use sha3::Shake256;
+
+const XMD_DST: &[u8] = b"QUUX-V01-CS02-with-ristretto255_XMD:SHA-512_R255MAP_RO_";
+const XOF_DST: &[u8] = b"QUUX-V01-CS02-with-ristretto255_XOF:SHAKE256_R255MAP_RO_";
+
+fn hex32(s: &str) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    out
+}
+
+/// The messages used by the test vectors, in order.
+fn messages() -> [Vec<u8>; 5] {
+    [
+        b"".to_vec(),
+        b"abc".to_vec(),
+        b"abcdef0123456789".to_vec(),
+        [b"q128_".as_slice(), &[b'q'; 128]].concat(),
+        [b"a512_".as_slice(), &[b'a'; 512]].concat(),
+    ]
+}
+
+#[test]
+fn hash_to_group_vectors() {
+    let expected = [
+        "bed61e1ee1966329962880e236dfdc83afd52fd1ce116f64fb806f1e8acea926",
+        "627b997b104ee62543358e22576c75a98dff9dc5f348d5ab228689735d77b258",
+        "90348aa2cced1007a4cd1b4cef9c1105d09a4b491766dad0de7f6ea39423ea32",
+        "a83367182a9928a7188576376291816ccab9e8293007401f3db8f1cbf1fc6934",
+        "eacd8dcc6376d75f11c2e8126385bfb9aecd91b8482b6226835c097a6b503d23",
+    ];
+    for (msg, out) in messages().iter().zip(expected) {
+        let p = RistrettoPoint::hash_to_group::<Sha512>(msg, XMD_DST);
+        assert_eq!(p.compress(), CompressedRistretto(hex32(out)));
+    }
+}
+
+#[test]
+fn hash_to_group_xof_vectors() {
+    let expected = [
+        "0880e6305d0b55b19e40fd40157e4f65067a0b02eb5191299e4e8b1afc4dd077",
+        "4865636d4144a815f3753fb7f75fcef0e1849aa02a9fb15a57e5f6e8c55ec02e",
+        "8a2cbab0f35b4df065c11a08e53e8cb0135c4975867bde3922cc2f32072d0106",
+        "66089eae177c06ac57f1e7dcda6093f33a21f1174aa86ee927cb782b5023e033",
+        "76115385ec8447a20a0c223f39200c49a15200eeb5e07592bb52a673d2412161",
+    ];
+    for (msg, out) in messages().iter().zip(expected) {
+        let p = RistrettoPoint::hash_to_group_xof::<Shake256>(msg, XOF_DST);
+        assert_eq!(p.compress(), CompressedRistretto(hex32(out)));
+    }
+}
+
+#[test]
+fn oversize_dst_is_hashed() {
+    let dst = [b'D'; 300];
+    let p = RistrettoPoint::hash_to_group::<Sha512>(b"abc", &dst);
+    assert_eq!(
+        p.compress(),
+        CompressedRistretto(hex32(
+            "4e862ac5c42a57e5d2fd58505bd10570788dc7ce80a68f93cad155760c73314e"
+        ))
+    );
+    let p = RistrettoPoint::hash_to_group_xof::<Shake256>(b"abc", &dst);
+    assert_eq!(
+        p.compress(),
+        CompressedRistretto(hex32(
+            "3ec914b1a044724b19bb2857dac1bca97d1f584c6849e011e725acb13875c325"
+        ))
+    );
+}
+
+#[test]
+fn different_tags_give_different_elements() {
+    let p = RistrettoPoint::hash_to_group::<Sha512>(b"abc", XMD_DST);
+    let q = RistrettoPoint::hash_to_group::<Sha512>(b"abc", XOF_DST);
+    assert_ne!(p, q);
+}
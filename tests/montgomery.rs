@@ -0,0 +1,187 @@
+//! Tests of the Montgomery ladder, against the vectors from RFC 7748, and edwards25519.
+use ck_dodo::curve::{
+    edwards::{EdwardsPoint, ED25519_BASEPOINT_POINT, EIGHT_TORSION},
+    montgomery::{MontgomeryPoint, X25519_BASEPOINT},
+    scalar::Scalar,
+};
+
+/// Parse a 32 byte string, written in hex.
+fn hex32(s: &str) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    out
+}
+
+#[test]
+fn test_x25519_vectors() {
+    // From RFC 7748, Section 5.2.
+    let vectors = [
+        (
+            "a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4",
+            "e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c",
+            "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552",
+        ),
+        (
+            "4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d",
+            "e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493",
+            "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957",
+        ),
+    ];
+    for (k, u, out) in vectors {
+        let point = MontgomeryPoint(hex32(u));
+        assert_eq!(point.mul_clamped(hex32(k)).to_bytes(), hex32(out));
+    }
+}
+
+#[test]
+fn test_x25519_iterated() {
+    // From RFC 7748, Section 5.2: k and u start at 9, and then each step sets
+    // k ← X25519(k, u), u ← k.
+    let mut k = X25519_BASEPOINT.to_bytes();
+    let mut u = X25519_BASEPOINT;
+    for i in 1..=1000 {
+        let out = u.mul_clamped(k);
+        u = MontgomeryPoint(k);
+        k = out.to_bytes();
+        if i == 1 {
+            assert_eq!(
+                k,
+                hex32("422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079")
+            );
+        }
+    }
+    assert_eq!(
+        k,
+        hex32("684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51")
+    );
+}
+
+#[test]
+fn test_x25519_diffie_hellman() {
+    // From RFC 7748, Section 6.1.
+    let a = hex32("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+    let b = hex32("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+    let a_pub = X25519_BASEPOINT.mul_clamped(a);
+    let b_pub = X25519_BASEPOINT.mul_clamped(b);
+    assert_eq!(
+        a_pub.to_bytes(),
+        hex32("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+    );
+    assert_eq!(
+        b_pub.to_bytes(),
+        hex32("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")
+    );
+    let shared = hex32("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+    assert_eq!(b_pub.mul_clamped(a).to_bytes(), shared);
+    assert_eq!(a_pub.mul_clamped(b).to_bytes(), shared);
+}
+
+#[test]
+fn test_basepoint_matches_edwards() {
+    assert_eq!(ED25519_BASEPOINT_POINT.to_montgomery(), X25519_BASEPOINT);
+    assert_eq!(EdwardsPoint::IDENTITY.to_montgomery().to_bytes(), [0; 32]);
+}
+
+#[test]
+fn test_ladder_matches_edwards() {
+    let b = ED25519_BASEPOINT_POINT;
+    let mut s = Scalar::from(0x1234_5678_9ABC_DEF0u64);
+    for t in &EIGHT_TORSION {
+        let p = b + t;
+        let expected = p.vartime_mul(&s).to_montgomery();
+        assert_eq!(&p.to_montgomery() * &s, expected);
+        assert_eq!(&s * &p.to_montgomery(), expected);
+        s = s * s + Scalar::ONE;
+    }
+    // Multiplying by 0 gives the identity, whose u coordinate we encode as 0.
+    assert_eq!((&X25519_BASEPOINT * &Scalar::ZERO).to_bytes(), [0; 32]);
+    assert_eq!(&X25519_BASEPOINT * &Scalar::ONE, X25519_BASEPOINT);
+}
+
+#[test]
+fn test_equality_ignores_non_canonical_encodings() {
+    // The top bit is ignored, and u + P encodes the same coordinate as u.
+    let mut high_bit = X25519_BASEPOINT.to_bytes();
+    high_bit[31] |= 0x80;
+    let mut plus_p = [0xFF; 32];
+    plus_p[0] = 0xED + 9;
+    plus_p[31] = 0x7F;
+    for bytes in [high_bit, plus_p] {
+        let point = MontgomeryPoint(bytes);
+        assert_eq!(point, X25519_BASEPOINT);
+        let k = [0x42; 32];
+        assert_eq!(point.mul_clamped(k), X25519_BASEPOINT.mul_clamped(k));
+    }
+}
+
+#[test]
+fn test_mul_ladder_matches_edwards() {
+    let b = ED25519_BASEPOINT_POINT;
+    let minus_one = -Scalar::ONE;
+    let scalars = [
+        Scalar::ZERO,
+        Scalar::ONE,
+        Scalar::from(2u64),
+        Scalar::from(7u64),
+        Scalar::from(8u64),
+        minus_one,
+        minus_one - Scalar::ONE,
+        Scalar::from_bytes_mod_order_wide(&[0xA5; 64]),
+    ];
+    let mut points = vec![EdwardsPoint::IDENTITY, b, b.double()];
+    points.extend(EIGHT_TORSION.iter().map(|t| b + t));
+    points.extend(EIGHT_TORSION);
+    for p in &points {
+        for s in &scalars {
+            let out = p.mul_ladder(s);
+            assert!(bool::from(out.is_on_curve()));
+            assert_eq!(out.compress(), p.vartime_mul(s).compress());
+        }
+    }
+}
+
+#[test]
+fn test_conditional_select() {
+    use subtle::{Choice, ConditionallySelectable};
+
+    let a = X25519_BASEPOINT;
+    let b = X25519_BASEPOINT.mul_clamped([0x42; 32]);
+    assert_eq!(
+        MontgomeryPoint::conditional_select(&a, &b, Choice::from(0)),
+        a
+    );
+    assert_eq!(
+        MontgomeryPoint::conditional_select(&a, &b, Choice::from(1)),
+        b
+    );
+    let (mut x, mut y) = (a, b);
+    MontgomeryPoint::conditional_swap(&mut x, &mut y, Choice::from(1));
+    assert_eq!((x, y), (b, a));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let p = X25519_BASEPOINT.mul_clamped([0x42; 32]);
+    let json = serde_json::to_string(&p).unwrap();
+    assert_eq!(json, serde_json::to_string(&p.to_bytes()).unwrap());
+    let decoded: MontgomeryPoint = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.to_bytes(), p.to_bytes());
+    // Any bytes are accepted, and kept as they are.
+    let json = serde_json::to_string(&[0xFF; 32]).unwrap();
+    let decoded: MontgomeryPoint = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.to_bytes(), [0xFF; 32]);
+    assert!(serde_json::from_str::<MontgomeryPoint>("[1, 2, 3]").is_err());
+}
+
+#[test]
+fn test_formatting() {
+    let hex = "0900000000000000000000000000000000000000000000000000000000000000";
+    let b = X25519_BASEPOINT;
+    assert_eq!(format!("{b}"), hex);
+    assert_eq!(format!("{b:x}"), hex);
+    assert_eq!(format!("{b:#x}"), format!("0x{hex}"));
+    assert_eq!(format!("{b:?}"), format!("MontgomeryPoint({hex})"));
+}
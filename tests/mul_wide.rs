@@ -0,0 +1,72 @@
+//! Differential tests between the different ways of multiplying limbs in the saturated backend.
+#![cfg(not(any(
+    target_arch = "wasm32",
+    feature = "fiat-backend",
+    feature = "u51-backend",
+    feature = "u32-backend",
+    target_pointer_width = "32"
+)))]
+use ck_dodo::testing::mul_wide;
+use num_bigint::BigUint;
+use proptest::prelude::*;
+
+/// A 64 bit limb, biased towards values likely to trip up carries.
+fn limb() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        Just(0),
+        Just(1),
+        Just(u64::MAX),
+        Just(u64::MAX - 1),
+        Just(1 << 63),
+        Just(0xFFFFFFFFFFFFFFED),
+        any::<u64>(),
+    ]
+}
+
+fn limbs() -> impl Strategy<Value = [u64; 4]> {
+    [limb(), limb(), limb(), limb()]
+}
+
+fn to_big(limbs: &[u64]) -> BigUint {
+    let digits: Vec<u32> = limbs
+        .iter()
+        .flat_map(|&x| [x as u32, (x >> 32) as u32])
+        .collect();
+    BigUint::new(digits)
+}
+
+/// Check every available method against the portable one, and the portable one against num-bigint.
+fn check(a: &[u64; 4], b: &[u64; 4]) -> Result<(), TestCaseError> {
+    let expected = mul_wide::portable(a, b);
+    prop_assert_eq!(to_big(&expected), to_big(a) * to_big(b));
+    #[cfg(target_arch = "x86_64")]
+    if let Some(actual) = mul_wide::bmi2_adx(a, b) {
+        prop_assert_eq!(actual, expected);
+    }
+    #[cfg(target_arch = "aarch64")]
+    prop_assert_eq!(mul_wide::aarch64(a, b), expected);
+    Ok(())
+}
+
+#[test]
+fn mul_wide_agrees_on_edge_cases() {
+    let edges = [
+        [0; 4],
+        [1, 0, 0, 0],
+        [u64::MAX; 4],
+        [0, 0, 0, u64::MAX],
+        [u64::MAX, 0, u64::MAX, 0],
+    ];
+    for a in &edges {
+        for b in &edges {
+            check(a, b).unwrap();
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn mul_wide_agrees(a in limbs(), b in limbs()) {
+        check(&a, &b)?;
+    }
+}
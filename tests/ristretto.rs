@@ -0,0 +1,507 @@
+//! Tests of the ristretto255 group, against edwards25519, and the vectors from RFC 9496.
+use ck_dodo::curve::{
+    edwards::{ED25519_BASEPOINT_POINT, EIGHT_TORSION},
+    ristretto::{
+        CompressedRistretto, RistrettoBasepointTable, RistrettoPoint,
+        VartimeRistrettoPrecomputation, RISTRETTO_BASEPOINT_COMPRESSED, RISTRETTO_BASEPOINT_POINT,
+        RISTRETTO_BASEPOINT_TABLE,
+    },
+    scalar::Scalar,
+    traits::{MultiscalarMul, VartimeMultiscalarMul, VartimePrecomputedMultiscalarMul},
+};
+use subtle::ConstantTimeEq;
+
+/// Some multiples of the generator, along with the scalars.
+fn multiples() -> Vec<(Scalar, RistrettoPoint)> {
+    let b = RISTRETTO_BASEPOINT_POINT;
+    [1u64, 2, 3, 7, 0x1234_5678_9ABC_DEF0]
+        .into_iter()
+        .map(|k| {
+            let k = Scalar::from(k);
+            (k, b.vartime_mul(&k))
+        })
+        .collect()
+}
+
+#[test]
+fn test_basepoint_encoding() {
+    let b = RISTRETTO_BASEPOINT_POINT;
+    assert_eq!(b.compress(), RISTRETTO_BASEPOINT_COMPRESSED);
+    assert_eq!(RISTRETTO_BASEPOINT_COMPRESSED.decompress().unwrap(), b);
+    assert_eq!(
+        RistrettoPoint::IDENTITY.compress(),
+        CompressedRistretto([0; 32])
+    );
+    assert_eq!(
+        CompressedRistretto([0; 32]).decompress().unwrap(),
+        RistrettoPoint::IDENTITY
+    );
+}
+
+#[test]
+fn test_compress_round_trips() {
+    for (_, p) in multiples() {
+        let encoded = p.compress();
+        let decoded = encoded.decompress().unwrap();
+        assert_eq!(decoded, p);
+        assert_eq!(decoded.compress(), encoded);
+        assert_eq!((-p).compress().decompress().unwrap(), -p);
+    }
+}
+
+#[test]
+fn test_double_and_compress_batch() {
+    let mut points: Vec<RistrettoPoint> = multiples().into_iter().map(|(_, p)| p).collect();
+    points.push(RistrettoPoint::IDENTITY);
+    points.extend(points.clone().iter().map(|p| -p));
+    // The result can't depend on the representative.
+    points.extend_from_slice(&points[1].representatives()[1..]);
+    // Including the empty batch.
+    for n in 0..=points.len() {
+        let compressed = RistrettoPoint::double_and_compress_batch(&points[..n]);
+        assert_eq!(compressed.len(), n);
+        for (c, p) in compressed.iter().zip(&points[..n]) {
+            assert_eq!(*c, p.double().compress());
+        }
+    }
+}
+
+#[test]
+fn test_torsion_is_ignored() {
+    // Adding a point of the 4-torsion subgroup gives the same element, with the same
+    // encoding, but a different point on edwards25519.
+    for (_, p) in multiples() {
+        for (i, q) in p.representatives().iter().enumerate() {
+            let t = p.to_edwards() + EIGHT_TORSION[2 * i];
+            assert_eq!(q.to_edwards().compress(), t.compress());
+            assert!(bool::from(q.ct_eq(&p)));
+            assert_eq!(q.compress(), p.compress());
+        }
+    }
+}
+
+#[test]
+fn test_group_laws() {
+    let b = RISTRETTO_BASEPOINT_POINT;
+    let elements = multiples();
+    for (a, p) in &elements {
+        assert_eq!(b * a, *p);
+        assert_eq!(a * b, *p);
+        assert_eq!(p + RistrettoPoint::IDENTITY, *p);
+        assert!(bool::from((p - p).is_identity()));
+        assert_eq!(p.double(), p + p);
+        assert_eq!(-p + p, RistrettoPoint::IDENTITY);
+        for (c, q) in &elements {
+            assert_eq!(p + q, b * (a + c));
+            assert_eq!(p - q, b * (a - c));
+            assert_eq!(q * a, b * (a * c));
+        }
+    }
+    // The generator has order ℓ.
+    let minus_one = -Scalar::ONE;
+    assert_eq!(b * minus_one, -b);
+    assert!(bool::from((b * minus_one + b).is_identity()));
+    assert!(!bool::from(b.is_identity()));
+}
+
+#[test]
+fn test_basepoint_table_mul() {
+    let p = RISTRETTO_BASEPOINT_POINT.double() + RISTRETTO_BASEPOINT_POINT;
+    let built = RistrettoBasepointTable::new(&p);
+    let scalars = [
+        Scalar::ZERO,
+        Scalar::ONE,
+        -Scalar::ONE,
+        Scalar::from_bytes_mod_order_wide(&[0xA5; 64]),
+    ];
+    for s in &scalars {
+        assert_eq!(&built * s, p.vartime_mul(s));
+        assert_eq!(s * &built, p.vartime_mul(s));
+        assert_eq!(
+            RISTRETTO_BASEPOINT_TABLE * s,
+            RISTRETTO_BASEPOINT_POINT.vartime_mul(s)
+        );
+    }
+}
+
+#[test]
+fn test_decompress_rejects_invalid_encodings() {
+    // s = 1 is negative, and P + 2 reduces to 2, which isn't canonical.
+    let mut bytes = [0u8; 32];
+    bytes[0] = 1;
+    assert!(bool::from(
+        CompressedRistretto(bytes).decompress().is_none()
+    ));
+    let mut bytes = [0xFF; 32];
+    bytes[0] = 0xEF;
+    bytes[31] = 0x7F;
+    assert!(bool::from(
+        CompressedRistretto(bytes).decompress().is_none()
+    ));
+    // The encoding of an Edwards point isn't an encoding of an element.
+    let encoded = CompressedRistretto(ED25519_BASEPOINT_POINT.compress().to_bytes());
+    assert!(bool::from(encoded.decompress().is_none()));
+}
+
+/// Parse a 32 byte string, written in hex.
+fn hex32(s: &str) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    out
+}
+
+#[test]
+fn test_multiples_of_generator() {
+    // From RFC 9496, Appendix A.1: the encodings of 0⋅B, 1⋅B, ..., 15⋅B.
+    let vectors = [
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76",
+        "6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919",
+        "94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259",
+        "da80862773358b466ffadfe0b3293ab3d9fd53c5ea6c955358f568322daf6a57",
+        "e882b131016b52c1d3337080187cf768423efccbb517bb495ab812c4160ff44e",
+        "f64746d3c92b13050ed8d80236a7f0007c3b3f962f5ba793d19a601ebb1df403",
+        "44f53520926ec81fbd5a387845beb7df85a96a24ece18738bdcfa6a7822a176d",
+        "903293d8f2287ebe10e2374dc1a53e0bc887e592699f02d077d5263cdd55601c",
+        "02622ace8f7303a31cafc63f8fc48fdc16e1c8c8d234b2f0d6685282a9076031",
+        "20706fd788b2720a1ed2a5dad4952b01f413bcf0e7564de8cdc816689e2db95f",
+        "bce83f8ba5dd2fa572864c24ba1810f9522bc6004afe95877ac73241cafdab42",
+        "e4549ee16b9aa03099ca208c67adafcafa4c3f3e4e5303de6026e3ca8ff84460",
+        "aa52e000df2e16f55fb1032fc33bc42742dad6bd5a8fc0be0167436c5948501f",
+        "46376b80f409b29dc2b5f6f0c52591990896e5716f41477cd30085ab7f10301e",
+        "e0c418f7c8d9c4cdd7395b93ea124f3ad99021bb681dfc3302a9d99a2e53e64e",
+    ];
+    let mut p = RistrettoPoint::IDENTITY;
+    for hex in vectors {
+        let encoded = CompressedRistretto(hex32(hex));
+        assert_eq!(p.compress(), encoded);
+        assert_eq!(encoded.decompress().unwrap(), p);
+        p += RISTRETTO_BASEPOINT_POINT;
+    }
+}
+
+#[test]
+fn test_rfc_invalid_encodings() {
+    // Some of the vectors from RFC 9496, Appendix A.2, by the reason they get rejected.
+    let vectors = [
+        // Non-canonical field encodings.
+        "00ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "f3ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        // Negative field elements.
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "01ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "ed57ffd8c914fb201471d1c3d245ce3c746fcbe63a3679d51b6a516ebebe0e20",
+        "c34c4e1826e5d403b78e246e88aa051c36ccf0aafebffe137d148a2bf9104562",
+        "c940e5a4404157cfb1628b108db051a8d439e1a421394ec4ebccb9ec92a8ac78",
+        "47cfc5497c53dc8e61c91d17fd626ffb1c49e2bca94eed052281b510b1117a24",
+        "f1c6165d33367351b0da8f6e4511010c68174a03b6581212c71c0e1d026c3c72",
+        "87260f7a2f12495118360f02c26a470f450dadf34a413d21042b43b9d93e1309",
+        // Non-square x².
+        "26948d35ca62e643e26a83177332e6b6afeb9d08e4268b650f1f5bbd8d81d371",
+        "4eac077a713c57b4f4397629a4145982c661f48044dd3f96427d40b147d9742f",
+        "de6a7b00deadc788eb6b6c8d20c0ae96c2f2019078fa604fee5b87d6e989ad7b",
+        "bcab477be20861e01e4a0e295284146a510150d9817763caf1a6f4b422d67042",
+        "2a292df7e32cababbd9de088d1d1abec9fc0440f637ed2fba145094dc14bea08",
+        "f4a9e534fc0d216c44b218fa0c42d99635a0127ee2e53c712f70609649fdff22",
+        "8268436f8c4126196cf64b3c7ddbda90746a378625f9813dd9b8457077256731",
+        "2810e5cbc2cc4d4eece54f61c6f69758e289aa7ab440b3cbeaa21995c2f4232b",
+        // Negative x⋅y value.
+        "3eb858e78f5a7254d8c9731174a94f76755fd3941c0ac93735c07ba14579630e",
+        "227142501b9d4355ccba290404bde41575b037693cef1f438c47f8fbf35d1165",
+        "5c37cc491da847cfeb9281d407efc41e15144c876e0170b499a96a22ed31e01e",
+        // s = -1, which causes y = 0.
+        "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+    ];
+    for hex in vectors {
+        let encoded = CompressedRistretto(hex32(hex));
+        assert!(bool::from(encoded.decompress().is_none()), "{hex}");
+    }
+}
+
+#[test]
+fn test_from_uniform_bytes() {
+    // From RFC 9496, Appendix A.3.
+    let vectors = [
+        (
+            "5d1be09e3d0c82fc538112490e35701979d99e06ca3e2b5b54bffe8b4dc772c1",
+            "4d98b696a1bbfb5ca32c436cc61c16563790306c79eaca7705668b47dffe5bb6",
+            "3066f82a1a747d45120d1740f14358531a8f04bbffe6a819f86dfe50f44a0a46",
+        ),
+        (
+            "f116b34b8f17ceb56e8732a60d913dd10cce47a6d53bee9204be8b44f6678b27",
+            "0102a56902e2488c46120e9276cfe54638286b9e4b3cdb470b542d46c2068d38",
+            "f26e5b6f7d362d2d2a94c5d0e7602cb4773c95a2e5c31a64f133189fa76ed61b",
+        ),
+        (
+            "8422e1bbdaab52938b81fd602effb6f89110e1e57208ad12d9ad767e2e25510c",
+            "27140775f9337088b982d83d7fcf0b2fa1edffe51952cbe7365e95c86eaf325c",
+            "006ccd2a9e6867e6a2c5cea83d3302cc9de128dd2a9a57dd8ee7b9d7ffe02826",
+        ),
+        (
+            "ac22415129b61427bf464e17baee8db65940c233b98afce8d17c57beeb7876c2",
+            "150d15af1cb1fb824bbd14955f2b57d08d388aab431a391cfc33d5bafb5dbbaf",
+            "f8f0c87cf237953c5890aec3998169005dae3eca1fbb04548c635953c817f92a",
+        ),
+        (
+            "165d697a1ef3d5cf3c38565beefcf88c0f282b8e7dbd28544c483432f1cec767",
+            "5debea8ebb4e5fe7d6f6e5db15f15587ac4d4d4a1de7191e0c1ca6664abcc413",
+            "ae81e7dedf20a497e10c304a765c1767a42d6e06029758d2d7e8ef7cc4c41179",
+        ),
+        (
+            "a836e6c9a9ca9f1e8d486273ad56a78c70cf18f0ce10abb1c7172ddd605d7fd2",
+            "979854f47ae1ccf204a33102095b4200e5befc0465accc263175485f0e17ea5c",
+            "e2705652ff9f5e44d3e841bf1c251cf7dddb77d140870d1ab2ed64f1a9ce8628",
+        ),
+        (
+            "2cdc11eaeb95daf01189417cdddbf95952993aa9cb9c640eb5058d09702c7462",
+            "2c9965a697a3b345ec24ee56335b556e677b30e6f90ac77d781064f866a3c982",
+            "80bd07262511cdde4863f8a7434cef696750681cb9510eea557088f76d9e5065",
+        ),
+    ];
+    for (lo, hi, out) in vectors {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&hex32(lo));
+        bytes[32..].copy_from_slice(&hex32(hi));
+        let p = RistrettoPoint::from_uniform_bytes(&bytes);
+        assert_eq!(p.compress(), CompressedRistretto(hex32(out)));
+        assert_eq!(p.compress().decompress().unwrap(), p);
+    }
+    // The top bit of each half is ignored.
+    let mut bytes = [0x5A; 64];
+    let p = RistrettoPoint::from_uniform_bytes(&bytes);
+    bytes[31] ^= 0x80;
+    bytes[63] ^= 0x80;
+    assert_eq!(RistrettoPoint::from_uniform_bytes(&bytes), p);
+}
+
+/// Scalars and elements for a multiscalar multiplication, along with the expected sum.
+///
+/// Every other element is represented by a point off the prime order subgroup, which
+/// shouldn't matter.
+fn msm_inputs(n: usize) -> (Vec<Scalar>, Vec<RistrettoPoint>, RistrettoPoint) {
+    let b = RISTRETTO_BASEPOINT_POINT;
+    let mut scalars = Vec::with_capacity(n);
+    let mut points = Vec::with_capacity(n);
+    let mut expected = Scalar::ZERO;
+    for i in 0..n as u64 {
+        let a = Scalar::from(i.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1).square();
+        let k = Scalar::from(3 * i + 2);
+        let p = b.vartime_mul(&k);
+        let p = if i % 2 == 0 {
+            p.representatives()[i as usize % 4]
+        } else {
+            p
+        };
+        expected += a * k;
+        scalars.push(a);
+        points.push(p);
+    }
+    (scalars, points, b.vartime_mul(&expected))
+}
+
+#[test]
+fn test_multiscalar_mul() {
+    // The last size is past the switch to Pippenger's method.
+    for n in [0, 1, 2, 5, 17, 200] {
+        let (scalars, points, expected) = msm_inputs(n);
+        assert_eq!(RistrettoPoint::multiscalar_mul(&scalars, &points), expected);
+        assert_eq!(
+            RistrettoPoint::vartime_multiscalar_mul(&scalars, &points),
+            expected
+        );
+        assert_eq!(
+            RistrettoPoint::vartime_multiscalar_mul(scalars.clone(), points.clone()),
+            expected
+        );
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_multiscalar_mul_rejects_mismatched_lengths() {
+    let (scalars, points, _) = msm_inputs(3);
+    RistrettoPoint::multiscalar_mul(&scalars, &points[..2]);
+}
+
+#[test]
+fn test_vartime_precomputed_multiscalar_mul() {
+    let n = 6;
+    let (scalars, points, expected) = msm_inputs(n);
+    let precomputation = VartimeRistrettoPrecomputation::new(&points);
+    assert_eq!(precomputation.vartime_multiscalar_mul(&scalars), expected);
+    for k in 0..=n {
+        let precomputation = VartimeRistrettoPrecomputation::new(&points[..k]);
+        assert_eq!(
+            precomputation.vartime_mixed_multiscalar_mul(
+                &scalars[..k],
+                &scalars[k..],
+                &points[k..],
+            ),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_vartime_double_scalar_mul_basepoint() {
+    let b = RISTRETTO_BASEPOINT_POINT;
+    for (a, p) in multiples() {
+        let c = a.square() + Scalar::ONE;
+        assert_eq!(
+            RistrettoPoint::vartime_double_scalar_mul_basepoint(&c, &p, &a),
+            p * c + b * a
+        );
+    }
+}
+
+// Every combination of owned and borrowed operands should work, which clippy objects to.
+#[allow(clippy::op_ref)]
+#[test]
+fn test_operators() {
+    let (scalars, points, expected) = msm_inputs(5);
+    let sum: RistrettoPoint = scalars.iter().zip(&points).map(|(a, p)| a * p).sum();
+    assert_eq!(sum, expected);
+    let owned: RistrettoPoint = scalars.iter().zip(points.clone()).map(|(a, p)| p * a).sum();
+    assert_eq!(owned, expected);
+    let multiples: Vec<RistrettoPoint> =
+        points.iter().zip(&scalars).map(|(p, a)| *a * *p).collect();
+    assert_eq!(multiples.iter().sum::<RistrettoPoint>(), expected);
+    assert_eq!(multiples.into_iter().sum::<RistrettoPoint>(), expected);
+
+    let (a, p) = (scalars[1], points[1]);
+    let expected = p.vartime_mul(&a);
+    for q in [
+        a * p,
+        a * &p,
+        &a * p,
+        &a * &p,
+        p * a,
+        p * &a,
+        &p * a,
+        &p * &a,
+    ] {
+        assert_eq!(q, expected);
+    }
+    let mut q = p;
+    q *= a;
+    assert_eq!(q, expected);
+    let mut q = p;
+    q *= &a;
+    assert_eq!(q, expected);
+    let mut q = p;
+    q += expected;
+    q -= &expected;
+    q -= p;
+    assert!(bool::from(q.is_identity()));
+    assert_eq!(
+        std::iter::empty::<RistrettoPoint>().sum::<RistrettoPoint>(),
+        RistrettoPoint::IDENTITY
+    );
+}
+
+#[cfg(feature = "digest")]
+#[test]
+fn test_lizard_encode() {
+    use sha2::Sha256;
+
+    // These match the lizard fork of curve25519-dalek.
+    let mut counting = [0u8; 16];
+    for (i, b) in counting.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    let vectors = [
+        (
+            [0u8; 16],
+            "f0b7e34484f74cf00f15024b738539738646bbbe1e9bc7509a676815227e774f",
+        ),
+        (
+            [1u8; 16],
+            "cc92e81f585afc5caac88660d8d17e9025a44489a363042123f6af0702156e65",
+        ),
+        (
+            counting,
+            "c830573f8a8e7778671f76cdc796dc0a235cf177f197d9fcba06e84e96247444",
+        ),
+    ];
+    for (data, out) in vectors {
+        let p = RistrettoPoint::lizard_encode::<Sha256>(&data);
+        assert_eq!(p.compress(), CompressedRistretto(hex32(out)));
+        assert_eq!(p.lizard_decode::<Sha256>().unwrap(), data);
+    }
+}
+
+#[cfg(feature = "digest")]
+#[test]
+fn test_lizard_round_trips() {
+    use sha2::Sha256;
+
+    for i in 0..64u8 {
+        let mut data = [0u8; 16];
+        for (j, b) in data.iter_mut().enumerate() {
+            *b = i.wrapping_mul(37).wrapping_add(j as u8).wrapping_mul(101);
+        }
+        let p = RistrettoPoint::lizard_encode::<Sha256>(&data);
+        assert_eq!(p.lizard_decode::<Sha256>().unwrap(), data);
+        // Decoding only depends on the element, not on the representative.
+        let q = p.compress().decompress().unwrap();
+        assert_eq!(q.lizard_decode::<Sha256>().unwrap(), data);
+        for r in p.representatives() {
+            assert_eq!(r.lizard_decode::<Sha256>().unwrap(), data);
+        }
+    }
+}
+
+#[cfg(feature = "digest")]
+#[test]
+fn test_lizard_rejects_other_elements() {
+    use sha2::Sha256;
+
+    assert!(bool::from(
+        RistrettoPoint::IDENTITY.lizard_decode::<Sha256>().is_none()
+    ));
+    for (_, p) in multiples() {
+        assert!(bool::from(p.lizard_decode::<Sha256>().is_none()));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let b = RISTRETTO_BASEPOINT_POINT;
+    let json = serde_json::to_string(&b).unwrap();
+    // Elements go through their compressed encoding, which is serialized as is.
+    assert_eq!(json, serde_json::to_string(&b.compress().0).unwrap());
+    assert_eq!(json, serde_json::to_string(&b.compress()).unwrap());
+    let decoded: RistrettoPoint = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, b);
+    let decoded: CompressedRistretto = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, RISTRETTO_BASEPOINT_COMPRESSED);
+
+    // s = 1 is negative, so it isn't the encoding of any element, but it's fine as a
+    // compressed encoding.
+    let mut one = [0u8; 32];
+    one[0] = 1;
+    let json = serde_json::to_string(&one).unwrap();
+    assert!(serde_json::from_str::<RistrettoPoint>(&json).is_err());
+    let compressed: CompressedRistretto = serde_json::from_str(&json).unwrap();
+    assert_eq!(compressed.0, one);
+    assert!(serde_json::from_str::<RistrettoPoint>("[1, 2, 3]").is_err());
+    assert!(serde_json::from_str::<CompressedRistretto>("[1, 2, 3]").is_err());
+}
+
+#[test]
+fn test_formatting() {
+    let hex = "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76";
+    let b = RISTRETTO_BASEPOINT_COMPRESSED;
+    assert_eq!(format!("{b}"), hex);
+    assert_eq!(format!("{b:#x}"), format!("0x{hex}"));
+    assert_eq!(format!("{b:?}"), format!("CompressedRistretto({hex})"));
+    assert_eq!(
+        format!("{:?}", RISTRETTO_BASEPOINT_POINT),
+        format!("RistrettoPoint({hex})")
+    );
+}
@@ -0,0 +1,309 @@
+//! Differential tests of scalar arithmetic, against num-bigint.
+#![cfg(not(target_arch = "wasm32"))]
+use ck_dodo::curve::scalar::Scalar;
+use num_bigint::{BigInt, BigUint};
+use proptest::prelude::*;
+
+fn l() -> BigUint {
+    (BigUint::from(1u8) << 252u32) + 27742317777372353535851937790883648493u128
+}
+
+fn to_big(x: &Scalar) -> BigUint {
+    BigUint::from_bytes_le(&x.to_bytes())
+}
+
+/// Encode an integer < 2²⁵⁶ as 32 little endian bytes.
+fn to_bytes(x: &BigUint) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let le = x.to_bytes_le();
+    out[..le.len()].copy_from_slice(&le);
+    out
+}
+
+/// A scalar, along with its value as an integer, including values right next to ℓ.
+fn scalar() -> impl Strategy<Value = (Scalar, BigUint)> {
+    prop_oneof![
+        any::<[u8; 64]>().prop_map(|bytes| BigUint::from_bytes_le(&bytes) % l()),
+        (1..64u64).prop_map(|k| l() - k),
+        (0..64u64).prop_map(BigUint::from),
+    ]
+    .prop_map(|x| (Scalar::from_canonical_bytes(&to_bytes(&x)).unwrap(), x))
+}
+
+#[test]
+fn modulus_is_l() {
+    let modulus: Vec<u8> = Scalar::MODULUS
+        .iter()
+        .flat_map(|x| x.to_le_bytes())
+        .collect();
+    assert_eq!(BigUint::from_bytes_le(&modulus), l());
+}
+
+#[test]
+fn from_bytes_mod_order_edge_cases() {
+    for byte in [0x00, 0x01, 0x7F, 0x80, 0xFF] {
+        let x = Scalar::from_bytes_mod_order(&[byte; 32]);
+        assert_eq!(to_big(&x), BigUint::from_bytes_le(&[byte; 32]) % l());
+        let x = Scalar::from_bytes_mod_order_wide(&[byte; 64]);
+        assert_eq!(to_big(&x), BigUint::from_bytes_le(&[byte; 64]) % l());
+    }
+}
+
+#[test]
+fn from_canonical_bytes_rejects_l_and_above() {
+    let accepted = [BigUint::from(0u8), BigUint::from(1u8), l() - 1u8];
+    for x in &accepted {
+        let decoded = Scalar::from_canonical_bytes(&to_bytes(x));
+        assert!(bool::from(decoded.is_some()), "{x} was rejected");
+        assert_eq!(to_big(&decoded.unwrap()), *x);
+    }
+    let rejected = [
+        l(),
+        l() + 1u8,
+        l() * 2u8,
+        (BigUint::from(1u8) << 255u32) - 1u8,
+        (BigUint::from(1u8) << 256u32) - 1u8,
+    ];
+    for x in &rejected {
+        let decoded = Scalar::from_canonical_bytes(&to_bytes(x));
+        assert!(bool::from(decoded.is_none()), "{x} was accepted");
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_rejects_non_canonical_scalars() {
+    let json = |x: &BigUint| serde_json::to_string(&to_bytes(x)).unwrap();
+    for x in [l(), l() + 1u8, (BigUint::from(1u8) << 256u32) - 1u8] {
+        assert!(
+            serde_json::from_str::<Scalar>(&json(&x)).is_err(),
+            "{x} was accepted"
+        );
+    }
+    let x: Scalar = serde_json::from_str(&json(&(l() - 1u8))).unwrap();
+    assert_eq!(to_big(&x), l() - 1u8);
+    // Too few bytes are rejected too.
+    assert!(serde_json::from_str::<Scalar>("[1, 2, 3]").is_err());
+}
+
+#[cfg(feature = "digest")]
+#[test]
+fn hash_to_scalar_separates_domains() {
+    use ck_dodo::curve::scalar::hash_to_scalar;
+    use sha2::Sha512;
+
+    let a = hash_to_scalar::<Sha512>(b"ab", b"c").to_bytes();
+    let b = hash_to_scalar::<Sha512>(b"a", b"bc").to_bytes();
+    let c = hash_to_scalar::<Sha512>(b"", b"abc").to_bytes();
+    assert_ne!(a, b);
+    assert_ne!(a, c);
+    assert_ne!(b, c);
+    assert_eq!(a, hash_to_scalar::<Sha512>(b"ab", b"c").to_bytes());
+}
+
+proptest! {
+    #[test]
+    fn from_canonical_bytes_accepts_exactly_reduced(bytes in any::<[u8; 32]>()) {
+        let decoded = Scalar::from_canonical_bytes(&bytes);
+        let canonical = BigUint::from_bytes_le(&bytes) < l();
+        prop_assert_eq!(bool::from(decoded.is_some()), canonical);
+        if canonical {
+            prop_assert_eq!(decoded.unwrap().to_bytes(), bytes);
+        }
+    }
+
+    #[test]
+    fn from_bytes_mod_order_reduces(bytes in any::<[u8; 32]>()) {
+        let x = Scalar::from_bytes_mod_order(&bytes);
+        prop_assert_eq!(to_big(&x), BigUint::from_bytes_le(&bytes) % l());
+    }
+
+    #[test]
+    fn from_bytes_mod_order_wide_reduces(bytes in any::<[u8; 64]>()) {
+        let x = Scalar::from_bytes_mod_order_wide(&bytes);
+        prop_assert_eq!(to_big(&x), BigUint::from_bytes_le(&bytes) % l());
+        prop_assert_eq!(x.to_bytes(), Scalar::from_bytes_wide(&bytes).to_bytes());
+    }
+
+    #[test]
+    fn from_bytes_mod_order_wide_near_multiples_of_l(k in any::<[u8; 32]>(), d in -64i64..64) {
+        // k⋅ℓ + d, which lands right next to a multiple of ℓ.
+        let x = BigUint::from_bytes_le(&k) * l();
+        let x = if d < 0 { x - (-d) as u64 } else { x + d as u64 };
+        let mut bytes = [0u8; 64];
+        let le = x.to_bytes_le();
+        bytes[..le.len()].copy_from_slice(&le);
+        let actual = Scalar::from_bytes_mod_order_wide(&bytes);
+        prop_assert_eq!(to_big(&actual), x % l());
+    }
+
+    #[test]
+    fn add_matches((x, xi) in scalar(), (y, yi) in scalar()) {
+        prop_assert_eq!(to_big(&(x + y)), (xi + yi) % l());
+    }
+
+    #[test]
+    fn sub_matches((x, xi) in scalar(), (y, yi) in scalar()) {
+        prop_assert_eq!(to_big(&(x - y)), (xi + l() - yi) % l());
+    }
+
+    #[test]
+    fn neg_matches((x, xi) in scalar()) {
+        prop_assert_eq!(to_big(&-x), (l() - xi) % l());
+    }
+
+    #[test]
+    fn mul_matches((x, xi) in scalar(), (y, yi) in scalar()) {
+        prop_assert_eq!(to_big(&(x * y)), (xi * yi) % l());
+    }
+
+    #[test]
+    fn invert_matches((x, xi) in scalar()) {
+        // Fermat's little theorem gives us an independent way to calculate the inverse.
+        let expected = xi.modpow(&(l() - 2u8), &l());
+        prop_assert_eq!(to_big(&x.invert()), expected.clone());
+        prop_assert_eq!(to_big(&x.vartime_invert()), expected);
+    }
+
+    #[test]
+    fn batch_invert_matches_invert(
+        xs in prop::collection::vec(scalar(), 0..16),
+        zeros in prop::collection::vec(any::<bool>(), 16),
+    ) {
+        let mut xs: Vec<Scalar> = xs.into_iter().map(|(x, _)| x).collect();
+        // Sprinkle in some zeros, which should be left alone.
+        for (x, zero) in xs.iter_mut().zip(zeros) {
+            if zero {
+                *x = Scalar::ZERO;
+            }
+        }
+        let mut inverted = xs.clone();
+        Scalar::batch_invert(&mut inverted);
+        for (x, x_inv) in xs.iter().zip(inverted.iter()) {
+            prop_assert_eq!(x_inv.to_bytes(), x.invert().to_bytes());
+        }
+    }
+
+    #[test]
+    fn non_adjacent_form_is_valid((x, xi) in scalar(), w in 2..=8usize) {
+        let naf = x.non_adjacent_form(w);
+        let mut sum = BigInt::from(0);
+        for (i, &d) in naf.iter().enumerate() {
+            sum += BigInt::from(d) << i;
+            if d != 0 {
+                prop_assert!(d % 2 != 0, "digit {} is even", d);
+                prop_assert!(i32::from(d).abs() < 1 << (w - 1), "digit {} is too large", d);
+                let window = &naf[i + 1..(i + w).min(256)];
+                prop_assert!(window.iter().all(|&d| d == 0), "digits are too close together");
+            }
+        }
+        prop_assert_eq!(sum, BigInt::from(xi));
+    }
+
+    #[test]
+    fn as_radix_16_is_valid((x, xi) in scalar()) {
+        let digits = x.as_radix_16();
+        let mut sum = BigInt::from(0);
+        for (i, &d) in digits.iter().enumerate() {
+            sum += BigInt::from(d) << (4 * i);
+            let max = if i == 63 { 8 } else { 7 };
+            prop_assert!((-8..=max).contains(&d), "digit {} is out of range", d);
+        }
+        prop_assert_eq!(sum, BigInt::from(xi));
+    }
+
+    #[test]
+    fn as_radix_2w_is_valid((x, xi) in scalar(), w in 4..=8usize) {
+        let digits = x.as_radix_2w(w);
+        let len = 256usize.div_ceil(w);
+        let mut sum = BigInt::from(0);
+        for (i, &d) in digits.iter().enumerate() {
+            sum += BigInt::from(d) << (w * i);
+            let half = 1i32 << (w - 1);
+            prop_assert!((-half..half).contains(&i32::from(d)), "digit {} is out of range", d);
+            prop_assert!(i < len || d == 0, "digit {} is past the end", i);
+        }
+        prop_assert_eq!(sum, BigInt::from(xi));
+    }
+
+    #[test]
+    fn bits_match((x, xi) in scalar()) {
+        let bits_le: Vec<bool> = x.bits_le().iter().map(|&b| bool::from(b)).collect();
+        let expected: Vec<bool> = (0..256).map(|i| xi.bit(i)).collect();
+        prop_assert_eq!(&bits_le, &expected);
+        let mut bits_be: Vec<bool> = x.iter_bits().map(bool::from).collect();
+        bits_be.reverse();
+        prop_assert_eq!(bits_be, expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trips((x, _) in scalar()) {
+        let json = serde_json::to_string(&x).unwrap();
+        // The encoding is just the canonical bytes.
+        prop_assert_eq!(&json, &serde_json::to_string(&x.to_bytes()).unwrap());
+        let decoded: Scalar = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(decoded.to_bytes(), x.to_bytes());
+    }
+
+    #[test]
+    fn from_integers_reduces(x in any::<u128>()) {
+        prop_assert_eq!(to_big(&Scalar::from(x as u64)), BigUint::from(x as u64));
+        prop_assert_eq!(to_big(&Scalar::from(x)), BigUint::from(x));
+        // Every u128 is < ℓ, so to check reduction, we need to go a bit further.
+        let big = Scalar::from(x) * Scalar::from(u128::MAX);
+        prop_assert_eq!(to_big(&big), BigUint::from(x) * u128::MAX % l());
+    }
+
+    #[test]
+    fn sum_and_product_match(xs in prop::collection::vec(scalar(), 0..8)) {
+        let sum: BigUint = xs.iter().map(|(_, xi)| xi).sum();
+        let product = xs.iter().fold(BigUint::from(1u8), |acc, (_, xi)| acc * xi);
+        let scalars: Vec<Scalar> = xs.iter().map(|(x, _)| *x).collect();
+        prop_assert_eq!(to_big(&scalars.iter().sum()), &sum % l());
+        prop_assert_eq!(to_big(&scalars.iter().copied().sum()), sum % l());
+        prop_assert_eq!(to_big(&scalars.iter().product()), &product % l());
+        prop_assert_eq!(to_big(&scalars.into_iter().product()), product % l());
+    }
+
+    #[test]
+    fn lagrange_interpolation_recovers_secret(
+        (secret, _) in scalar(),
+        coefficients in prop::collection::vec(scalar(), 1..5),
+    ) {
+        // Evaluate a polynomial with f(0) = secret at 1, 2, ..., t, and then interpolate f(0) back.
+        let t = coefficients.len() as u64 + 1;
+        let f = |x: Scalar| {
+            coefficients.iter().rev().fold(Scalar::ZERO, |acc, (c, _)| (acc + c) * x) + secret
+        };
+        let points: Vec<(Scalar, Scalar)> =
+            (1..=t).map(Scalar::from).map(|x| (x, f(x))).collect();
+        let interpolated: Scalar = points
+            .iter()
+            .map(|(xi, yi)| {
+                let lambda: Scalar = points
+                    .iter()
+                    .filter(|(xj, _)| xj.to_bytes() != xi.to_bytes())
+                    .map(|(xj, _)| xj * (xj - xi).invert())
+                    .product();
+                lambda * yi
+            })
+            .sum();
+        prop_assert_eq!(interpolated.to_bytes(), secret.to_bytes());
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn from_hash_reduces_the_digest(msg in any::<Vec<u8>>()) {
+        use sha2::{Digest, Sha512};
+
+        let digest = Sha512::digest(&msg);
+        let x = Scalar::from_hash(Sha512::new().chain_update(&msg));
+        prop_assert_eq!(to_big(&x), BigUint::from_bytes_le(&digest) % l());
+    }
+
+    #[test]
+    fn mul_add_matches((x, xi) in scalar(), (y, yi) in scalar(), (z, zi) in scalar()) {
+        prop_assert_eq!(to_big(&Scalar::mul_add(&x, &y, &z)), (xi * yi + zi) % l());
+    }
+}
@@ -0,0 +1,140 @@
+//! Known answer tests for scalars, on boundary values, and vectors from other implementations.
+//!
+//! Unlike the property based tests, these don't rely on another implementation at
+//! test time, so they pin down the reduction constants on every target, including wasm32.
+use ck_dodo::curve::scalar::Scalar;
+
+/// Parse a 32 byte little endian encoding, written in hex.
+fn hex32(s: &str) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    out
+}
+
+/// ℓ, the order of the group.
+const L: &str = "edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010";
+/// ℓ - 1, the largest canonical scalar.
+const L_MINUS_1: &str = "ecd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010";
+/// ℓ + 1.
+const L_PLUS_1: &str = "eed3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010";
+/// 2²⁵⁵ - 1.
+const MAX_255: &str = "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f";
+/// (2²⁵⁵ - 1) mod ℓ.
+const MAX_255_REDUCED: &str = "84344775474a7f9723b63a8be92ae76dffffffffffffffffffffffffffffff0f";
+/// (2²⁵⁶ - 1) mod ℓ.
+const MAX_256_REDUCED: &str = "1c95988d7431ecd670cf7d73f45befc6feffffffffffffffffffffffffffff0f";
+/// (2⁵¹² - 1) mod ℓ.
+const MAX_512_REDUCED: &str = "000f9c44e31106a447938568a71b0ed065bef517d273ecce3d9a307c1b419903";
+/// 2⁻¹ mod ℓ, i.e. (ℓ + 1) / 2.
+const HALF: &str = "f7e97a2e8d31092c6bce7b51ef7c6f0a00000000000000000000000000000008";
+
+// These come from the test suite of curve25519-dalek.
+
+/// A random scalar, X.
+const X: &str = "4e5ab4345d4708845913b4641bc27d5252a585101bcc4244d449f4a879d9f204";
+/// X⁻¹.
+const X_INV: &str = "1cdc17fce0e9a5bbd9247e56bb016347bbba31edd5a9bb96d50bcd7a3f962a0f";
+/// Another random scalar, Y.
+const Y: &str = "907633fe1c4b66a4a28d2dd7678386c353d0de5455d4fc9de8ef7ac31f35bb05";
+/// X⋅Y.
+const X_TIMES_Y: &str = "6c3374a1894f62210aaa2fe186a6f92ce0aa75c2779581c295fc08179a73940c";
+/// The 64 byte integer X + 2²⁵⁶⋅X, reduced mod ℓ.
+const X_WIDE_REDUCED: &str = "d89ab38bd279024745639ed817ad3f64cc005b32db9939f91c521fc564a5c008";
+
+fn scalar(s: &str) -> Scalar {
+    Scalar::from_canonical_bytes(&hex32(s)).unwrap()
+}
+
+#[test]
+fn canonical_decoding_boundaries() {
+    let zero = [0u8; 32];
+    let mut one = [0u8; 32];
+    one[0] = 1;
+    assert_eq!(
+        Scalar::from_canonical_bytes(&zero).unwrap().to_bytes(),
+        zero
+    );
+    assert_eq!(Scalar::from_canonical_bytes(&one).unwrap().to_bytes(), one);
+    assert_eq!(scalar(L_MINUS_1).to_bytes(), hex32(L_MINUS_1));
+    for s in [L, L_PLUS_1, MAX_255] {
+        assert!(bool::from(
+            Scalar::from_canonical_bytes(&hex32(s)).is_none()
+        ));
+    }
+    assert!(bool::from(
+        Scalar::from_canonical_bytes(&[0xFF; 32]).is_none()
+    ));
+}
+
+#[test]
+fn reduction_boundaries() {
+    let mut one = [0u8; 32];
+    one[0] = 1;
+    let reduce = |bytes: &[u8; 32]| Scalar::from_bytes_mod_order(bytes).to_bytes();
+    assert_eq!(reduce(&[0; 32]), [0; 32]);
+    assert_eq!(reduce(&one), one);
+    assert_eq!(reduce(&hex32(L_MINUS_1)), hex32(L_MINUS_1));
+    assert_eq!(reduce(&hex32(L)), [0; 32]);
+    assert_eq!(reduce(&hex32(L_PLUS_1)), one);
+    assert_eq!(reduce(&hex32(MAX_255)), hex32(MAX_255_REDUCED));
+    assert_eq!(reduce(&[0xFF; 32]), hex32(MAX_256_REDUCED));
+
+    // The generic reduction should agree with the specialized one.
+    let mut wide = [0u8; 64];
+    wide[..32].copy_from_slice(&hex32(L));
+    assert_eq!(Scalar::from_bytes_mod_order_wide(&wide).to_bytes(), [0; 32]);
+    assert_eq!(Scalar::from_bytes_wide(&wide).to_bytes(), [0; 32]);
+    let max_512 = hex32(MAX_512_REDUCED);
+    assert_eq!(
+        Scalar::from_bytes_mod_order_wide(&[0xFF; 64]).to_bytes(),
+        max_512
+    );
+    assert_eq!(Scalar::from_bytes_wide(&[0xFF; 64]).to_bytes(), max_512);
+}
+
+#[test]
+fn arithmetic_boundaries() {
+    let minus_one = scalar(L_MINUS_1);
+    assert_eq!((-Scalar::ONE).to_bytes(), hex32(L_MINUS_1));
+    assert_eq!((minus_one + Scalar::ONE).to_bytes(), [0; 32]);
+    assert_eq!((Scalar::ZERO - Scalar::ONE).to_bytes(), hex32(L_MINUS_1));
+    assert_eq!((minus_one * minus_one).to_bytes(), Scalar::ONE.to_bytes());
+    assert_eq!(
+        Scalar::mul_add(&minus_one, &minus_one, &minus_one).to_bytes(),
+        [0; 32]
+    );
+}
+
+#[test]
+fn inversion_boundaries() {
+    assert_eq!(Scalar::ZERO.invert().to_bytes(), [0; 32]);
+    assert_eq!(Scalar::ZERO.vartime_invert().to_bytes(), [0; 32]);
+    assert_eq!(Scalar::ONE.invert().to_bytes(), Scalar::ONE.to_bytes());
+    let minus_one = scalar(L_MINUS_1);
+    assert_eq!(minus_one.invert().to_bytes(), hex32(L_MINUS_1));
+    assert_eq!(minus_one.vartime_invert().to_bytes(), hex32(L_MINUS_1));
+    assert_eq!(Scalar::from(2u64).invert().to_bytes(), hex32(HALF));
+    assert_eq!(Scalar::from(2u64).vartime_invert().to_bytes(), hex32(HALF));
+}
+
+#[test]
+fn dalek_vectors() {
+    assert_eq!(scalar(X).invert().to_bytes(), hex32(X_INV));
+    assert_eq!(scalar(X).vartime_invert().to_bytes(), hex32(X_INV));
+    assert_eq!((scalar(X) * scalar(Y)).to_bytes(), hex32(X_TIMES_Y));
+    let mut inverses = [scalar(X), Scalar::ZERO, scalar(X_INV)];
+    Scalar::batch_invert(&mut inverses);
+    assert_eq!(inverses[0].to_bytes(), hex32(X_INV));
+    assert_eq!(inverses[1].to_bytes(), [0; 32]);
+    assert_eq!(inverses[2].to_bytes(), hex32(X));
+
+    let mut wide = [0u8; 64];
+    wide[..32].copy_from_slice(&hex32(X));
+    wide[32..].copy_from_slice(&hex32(X));
+    assert_eq!(
+        Scalar::from_bytes_mod_order_wide(&wide).to_bytes(),
+        hex32(X_WIDE_REDUCED)
+    );
+}
@@ -0,0 +1,749 @@
+//! Tests of Ed25519 signatures, against the vectors from RFC 8032.
+#![cfg(feature = "ed25519")]
+use ck_dodo::{
+    curve::{
+        edwards::{EdwardsPoint, ED25519_BASEPOINT_TABLE, EIGHT_TORSION},
+        scalar::Scalar,
+    },
+    sign::{verify_batch, ExpandedSecretKey, Signature, SignatureError, SigningKey, VerifyingKey},
+};
+use sha2::{Digest, Sha512};
+
+/// Parse a string of bytes, written in hex.
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn hex32(s: &str) -> [u8; 32] {
+    hex(s).try_into().unwrap()
+}
+
+fn hex64(s: &str) -> [u8; 64] {
+    hex(s).try_into().unwrap()
+}
+
+/// From RFC 8032, Section 7.1: the seed, the verifying key, the message, and the signature.
+const VECTORS: [(&str, &str, &str, &str); 4] = [
+    (
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "",
+        "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    ),
+    (
+        "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        "72",
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+    ),
+    (
+        "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+        "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        "af82",
+        "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+    ),
+    (
+        "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42",
+        "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf",
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+        "dc2a4459e7369633a52b1bf277839a00201009a3efbf3ecb69bea2186c26b58909351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704",
+    ),
+];
+
+#[test]
+fn test_rfc8032_vectors() {
+    for (seed, public, msg, sig) in VECTORS {
+        let key = SigningKey::from_bytes(&hex32(seed));
+        let verifying_key = key.verifying_key();
+        assert_eq!(verifying_key.to_bytes(), hex32(public));
+        assert_eq!(VerifyingKey::from_bytes(&hex32(public)), Ok(verifying_key));
+
+        let msg = hex(msg);
+        let signature = key.sign(&msg);
+        assert_eq!(signature.to_bytes(), hex64(sig));
+        assert_eq!(verifying_key.verify(&msg, &signature), Ok(()));
+    }
+}
+
+#[test]
+fn test_rejects_invalid_signatures() {
+    let (seed, _, msg, _) = VECTORS[2];
+    let key = SigningKey::from_bytes(&hex32(seed));
+    let verifying_key = key.verifying_key();
+    let msg = hex(msg);
+    let signature = key.sign(&msg);
+
+    let mut tampered = msg.clone();
+    tampered[0] ^= 1;
+    assert_eq!(
+        verifying_key.verify(&tampered, &signature),
+        Err(SignatureError::InvalidSignature)
+    );
+
+    let mut bytes = signature.to_bytes();
+    bytes[0] ^= 1;
+    assert_eq!(
+        verifying_key.verify(&msg, &Signature::from_bytes(&bytes)),
+        Err(SignatureError::InvalidSignature)
+    );
+
+    let other = SigningKey::from_bytes(&[7; 32]).verifying_key();
+    assert_eq!(
+        other.verify(&msg, &signature),
+        Err(SignatureError::InvalidSignature)
+    );
+}
+
+#[test]
+fn test_rejects_non_canonical_scalar() {
+    let (seed, _, msg, _) = VECTORS[0];
+    let key = SigningKey::from_bytes(&hex32(seed));
+    let msg = hex(msg);
+    let mut bytes = key.sign(&msg).to_bytes();
+    // Adding ℓ to s leaves s⋅B unchanged, so only the range check rejects this.
+    let ell = hex32("edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010");
+    let mut carry = 0u16;
+    for (s, l) in bytes[32..].iter_mut().zip(ell) {
+        carry += *s as u16 + l as u16;
+        *s = carry as u8;
+        carry >>= 8;
+    }
+    assert_eq!(carry, 0);
+    assert!(bool::from(
+        Scalar::from_canonical_bytes(&bytes[32..].try_into().unwrap()).is_none()
+    ));
+    assert_eq!(
+        key.verifying_key()
+            .verify(&msg, &Signature::from_bytes(&bytes)),
+        Err(SignatureError::NonCanonicalScalar)
+    );
+}
+
+#[test]
+fn test_rejects_invalid_keys() {
+    // y = 2 isn't the y coordinate of any point.
+    let mut bytes = [0u8; 32];
+    bytes[0] = 2;
+    assert_eq!(
+        VerifyingKey::from_bytes(&bytes),
+        Err(SignatureError::InvalidKey)
+    );
+    // y = p isn't canonical.
+    let p = hex32("edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f");
+    assert_eq!(
+        VerifyingKey::from_bytes(&p),
+        Err(SignatureError::InvalidKey)
+    );
+}
+
+#[test]
+fn test_encodings() {
+    let (seed, public, msg, sig) = VECTORS[1];
+    let key = SigningKey::from_bytes(&hex32(seed));
+    assert_eq!(key.to_bytes(), hex32(seed));
+    assert_eq!(key.as_bytes(), &hex32(seed));
+
+    let verifying_key = key.verifying_key();
+    assert_eq!(verifying_key.as_bytes(), &hex32(public));
+    assert_eq!(format!("{verifying_key}"), public);
+    assert_eq!(format!("{verifying_key:x}"), public);
+    assert_eq!(
+        format!("{verifying_key:?}"),
+        format!("VerifyingKey({public})")
+    );
+    // The seed never gets printed.
+    assert!(!format!("{key:?}").contains(seed));
+
+    let signature = key.sign(&hex(msg));
+    assert_eq!(Signature::from_bytes(&signature.to_bytes()), signature);
+    assert_eq!(signature.r_bytes(), &hex32(&sig[..64]));
+    assert_eq!(signature.s_bytes(), &hex32(&sig[64..]));
+    assert_eq!(format!("{signature}"), sig);
+    assert_eq!(format!("{signature:?}"), format!("Signature({sig})"));
+}
+
+#[test]
+fn test_seed_round_trip() {
+    for (seed, public, _, _) in VECTORS {
+        let key = SigningKey::from_seed(hex32(seed));
+        assert_eq!(key.to_seed(), hex32(seed));
+        assert_eq!(key.verifying_key().to_bytes(), hex32(public));
+        let again = SigningKey::from_seed(key.to_seed());
+        assert_eq!(again.to_bytes(), key.to_bytes());
+        assert_eq!(again.verifying_key(), key.verifying_key());
+    }
+}
+
+#[test]
+fn test_expanded_secret_key() {
+    for (seed, public, msg, sig) in VECTORS {
+        let expanded = ExpandedSecretKey::from_seed(&hex32(seed));
+        let verifying_key = expanded.verifying_key();
+        assert_eq!(verifying_key.to_bytes(), hex32(public));
+        let signature = expanded.sign(&hex(msg));
+        assert_eq!(signature.to_bytes(), hex64(sig));
+
+        // A signing key reuses the same expansion.
+        let key = SigningKey::from_seed(hex32(seed));
+        assert_eq!(key.expanded_secret_key().verifying_key(), verifying_key);
+        let signature = key.expanded_secret_key().sign(&hex(msg));
+        assert_eq!(signature.to_bytes(), hex64(sig));
+        assert_eq!(
+            format!("{:?}", key.expanded_secret_key()),
+            "ExpandedSecretKey { .. }"
+        );
+    }
+}
+
+/// From RFC 8032, Section 7.2: the seed, the verifying key, the message, the context,
+/// and the signature.
+const CTX_VECTORS: [(&str, &str, &str, &str, &str); 4] = [
+    (
+        "0305334e381af78f141cb666f6199f57bc3495335a256a95bd2a55bf546663f6",
+        "dfc9425e4f968f7f0c29f0259cf5f9aed6851c2bb4ad8bfb860cfee0ab248292",
+        "f726936d19c800494e3fdaff20b276a8",
+        "666f6f",
+        "55a4cc2f70a54e04288c5f4cd1e45a7bb520b36292911876cada7323198dd87a8b36950b95130022907a7fb7c4e9b2d5f6cca685a587b4b21f4b888e4e7edb0d",
+    ),
+    (
+        "0305334e381af78f141cb666f6199f57bc3495335a256a95bd2a55bf546663f6",
+        "dfc9425e4f968f7f0c29f0259cf5f9aed6851c2bb4ad8bfb860cfee0ab248292",
+        "f726936d19c800494e3fdaff20b276a8",
+        "626172",
+        "fc60d5872fc46b3aa69f8b5b4351d5808f92bcc044606db097abab6dbcb1aee3216c48e8b3b66431b5b186d1d28f8ee15a5ca2df6668346291c2043d4eb3e90d",
+    ),
+    (
+        "0305334e381af78f141cb666f6199f57bc3495335a256a95bd2a55bf546663f6",
+        "dfc9425e4f968f7f0c29f0259cf5f9aed6851c2bb4ad8bfb860cfee0ab248292",
+        "508e9e6882b979fea900f62adceaca35",
+        "666f6f",
+        "8b70c1cc8310e1de20ac53ce28ae6e7207f33c3295e03bb5c0732a1d20dc64908922a8b052cf99b7c4fe107a5abb5b2c4085ae75890d02df26269d8945f84b0b",
+    ),
+    (
+        "ab9c2853ce297ddab85c993b3ae14bcad39b2c682beabc27d6d4eb20711d6560",
+        "0f1d1274943b91415889152e893d80e93275a1fc0b65fd71b4b0dda10ad7d772",
+        "f726936d19c800494e3fdaff20b276a8",
+        "666f6f",
+        "21655b5f1aa965996b3f97b3c849eafba922a0a62992f73b3d1b73106a84ad85e9b86a7b6005ea868337ff2d20a7f5fbd4cd10b0be49a68da2b2e0dc0ad8960f",
+    ),
+];
+
+#[test]
+fn test_rfc8032_ctx_vectors() {
+    for (seed, public, msg, context, sig) in CTX_VECTORS {
+        let key = SigningKey::from_seed(hex32(seed));
+        let verifying_key = key.verifying_key();
+        assert_eq!(verifying_key.to_bytes(), hex32(public));
+
+        let (msg, context) = (hex(msg), hex(context));
+        let signature = key.sign_with_context(&msg, &context).unwrap();
+        assert_eq!(signature.to_bytes(), hex64(sig));
+        assert_eq!(
+            verifying_key.verify_with_context(&msg, &context, &signature),
+            Ok(())
+        );
+        // The context is part of what gets signed.
+        assert_eq!(
+            verifying_key.verify(&msg, &signature),
+            Err(SignatureError::InvalidSignature)
+        );
+        assert_eq!(
+            verifying_key.verify_with_context(&msg, b"baz", &signature),
+            Err(SignatureError::InvalidSignature)
+        );
+    }
+}
+
+#[test]
+fn test_context_length() {
+    let key = SigningKey::from_seed([1; 32]);
+    let verifying_key = key.verifying_key();
+    let msg = b"message";
+    assert_eq!(
+        key.sign_with_context(msg, b""),
+        Err(SignatureError::InvalidContext)
+    );
+    assert_eq!(
+        key.sign_with_context(msg, &[0; 256]),
+        Err(SignatureError::InvalidContext)
+    );
+    let signature = key.sign_with_context(msg, &[0xAB; 255]).unwrap();
+    assert_eq!(
+        verifying_key.verify_with_context(msg, &[0xAB; 255], &signature),
+        Ok(())
+    );
+    assert_eq!(
+        verifying_key.verify_with_context(msg, &[0xAB; 256], &signature),
+        Err(SignatureError::InvalidContext)
+    );
+    assert_eq!(
+        verifying_key.verify_with_context(msg, b"", &key.sign(msg)),
+        Err(SignatureError::InvalidContext)
+    );
+}
+
+/// Derive the secret scalar a from a seed, like RFC 8032 does.
+fn secret_scalar(seed: [u8; 32]) -> Scalar {
+    let hash = Sha512::digest(seed);
+    let mut a = [0u8; 32];
+    a.copy_from_slice(&hash[..32]);
+    a[0] &= 0xF8;
+    a[31] &= 0x7F;
+    a[31] |= 0x40;
+    Scalar::from_bytes_mod_order(&a)
+}
+
+/// Make a signature with arbitrary encodings of R and A, and s = k⋅a + r.
+fn sign_raw(
+    a: &Scalar,
+    a_bytes: &[u8; 32],
+    r: &Scalar,
+    r_bytes: &[u8; 32],
+    msg: &[u8],
+) -> Signature {
+    let k = Scalar::from_hash(
+        Sha512::new()
+            .chain_update(r_bytes)
+            .chain_update(a_bytes)
+            .chain_update(msg),
+    );
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(r_bytes);
+    bytes[32..].copy_from_slice(&Scalar::mul_add(&k, a, r).to_bytes());
+    Signature::from_bytes(&bytes)
+}
+
+/// Make a signature on a message, with the torsion point T⋅i added to R.
+///
+/// This satisfies the cofactored equation 8⋅s⋅B = 8⋅R + 8⋅k⋅A, but not the cofactorless
+/// one, unless i = 0.
+fn sign_with_torsion(seed: [u8; 32], msg: &[u8], i: usize) -> (VerifyingKey, Signature) {
+    let key = SigningKey::from_seed(seed).verifying_key();
+    let r = Scalar::from_hash(Sha512::new().chain_update(msg).chain_update(seed));
+    let big_r = (&r * &ED25519_BASEPOINT_TABLE + EIGHT_TORSION[i]).compress();
+    let signature = sign_raw(
+        &secret_scalar(seed),
+        key.as_bytes(),
+        &r,
+        big_r.as_bytes(),
+        msg,
+    );
+    (key, signature)
+}
+
+/// Make a batch of n valid signatures, on different messages, under different keys.
+fn batch(n: usize) -> (Vec<Vec<u8>>, Vec<Signature>, Vec<VerifyingKey>) {
+    let keys: Vec<SigningKey> = (0..n)
+        .map(|i| SigningKey::from_seed(Sha512::digest(i.to_le_bytes())[..32].try_into().unwrap()))
+        .collect();
+    let messages: Vec<Vec<u8>> = (0..n).map(|i| vec![i as u8; i % 7]).collect();
+    let signatures = keys
+        .iter()
+        .zip(&messages)
+        .map(|(key, msg)| key.sign(msg))
+        .collect();
+    let keys = keys.iter().map(|key| key.verifying_key()).collect();
+    (messages, signatures, keys)
+}
+
+#[test]
+fn test_verify_batch() {
+    for n in [0, 1, 2, 3, 16, 64] {
+        let (messages, signatures, keys) = batch(n);
+        let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+        assert_eq!(verify_batch(&messages, &signatures, &keys), Ok(()));
+    }
+}
+
+/// The indices of the invalid signatures reported by batch verification.
+fn invalid_indices(
+    messages: &[&[u8]],
+    signatures: &[Signature],
+    keys: &[VerifyingKey],
+) -> Vec<usize> {
+    verify_batch(messages, signatures, keys)
+        .unwrap_err()
+        .invalid_indices()
+        .to_vec()
+}
+
+#[test]
+fn test_verify_batch_rejects_invalid_signatures() {
+    let (messages, signatures, keys) = batch(32);
+    let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+
+    // Every position gets checked.
+    for i in [0, 13, 31] {
+        let mut tampered = signatures.clone();
+        tampered[i] = signatures[(i + 1) % 32];
+        assert_eq!(invalid_indices(&messages, &tampered, &keys), [i]);
+    }
+
+    let mut tampered = messages.clone();
+    tampered[5] = b"something else";
+    assert_eq!(invalid_indices(&tampered, &signatures, &keys), [5]);
+
+    // Two invalid signatures can't cancel out.
+    let mut swapped = signatures.clone();
+    swapped.swap(3, 4);
+    assert_eq!(invalid_indices(&messages, &swapped, &keys), [3, 4]);
+
+    // Nor can two whose errors would cancel out under fixed weights a and b, by
+    // changing s₃ by b⋅δ, and s₄ by -a⋅δ.
+    let delta = Scalar::from(0x1234_5678u64);
+    for (a, b) in [(1u64, 1u64), (1, 2), (3, 5)] {
+        let mut tampered = signatures.clone();
+        for (i, shift) in [
+            (3, Scalar::from(b) * delta),
+            (4, -(Scalar::from(a) * delta)),
+        ] {
+            let mut bytes = signatures[i].to_bytes();
+            let s = Scalar::from_canonical_bytes(bytes[32..].try_into().unwrap()).unwrap();
+            bytes[32..].copy_from_slice(&(s + shift).to_bytes());
+            tampered[i] = Signature::from_bytes(&bytes);
+        }
+        assert_eq!(invalid_indices(&messages, &tampered, &keys), [3, 4]);
+    }
+
+    // Signatures which can't be decoded are invalid too.
+    let mut tampered = signatures.clone();
+    let mut bytes = signatures[7].to_bytes();
+    bytes[63] |= 0xF0;
+    tampered[7] = Signature::from_bytes(&bytes);
+    let mut bytes = signatures[2].to_bytes();
+    bytes[..32].copy_from_slice(&hex32(
+        "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+    ));
+    tampered[2] = Signature::from_bytes(&bytes);
+    tampered[20] = signatures[21];
+    assert_eq!(invalid_indices(&messages, &tampered, &keys), [2, 7, 20]);
+}
+
+#[test]
+fn test_verify_batch_finds_every_invalid_signature() {
+    let (messages, signatures, keys) = batch(64);
+    let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+    for invalid in [vec![], vec![63], vec![0, 1, 2], vec![10, 31, 32, 50]] {
+        let mut tampered = signatures.clone();
+        for &i in &invalid {
+            let mut bytes = tampered[i].to_bytes();
+            bytes[40] ^= 1;
+            tampered[i] = Signature::from_bytes(&bytes);
+        }
+        let result = verify_batch(&messages, &tampered, &keys);
+        if invalid.is_empty() {
+            assert_eq!(result, Ok(()));
+        } else {
+            let err = result.unwrap_err();
+            assert_eq!(err.invalid_indices(), invalid);
+            assert_eq!(
+                err.to_string(),
+                format!("invalid signatures in batch, at indices {invalid:?}")
+            );
+        }
+    }
+    // Everything being invalid still gets reported.
+    let mut shifted = signatures.clone();
+    shifted.rotate_left(1);
+    assert_eq!(
+        invalid_indices(&messages, &shifted, &keys),
+        (0..64).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_verify_batch_is_cofactored() {
+    let msg: &[u8] = b"torsion";
+    let (key, signature) = sign_with_torsion([3; 32], msg, 1);
+    assert_eq!(
+        key.verify(msg, &signature),
+        Err(SignatureError::InvalidSignature)
+    );
+    assert_eq!(verify_batch(&[msg], &[signature], &[key]), Ok(()));
+    // Without any torsion, both agree.
+    let (key, signature) = sign_with_torsion([3; 32], msg, 0);
+    assert_eq!(key.verify(msg, &signature), Ok(()));
+    assert_eq!(verify_batch(&[msg], &[signature], &[key]), Ok(()));
+
+    // Signatures checked one by one, after the batch fails, are cofactored too.
+    let (messages, mut signatures, mut keys) = batch(8);
+    let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+    (keys[2], signatures[2]) = sign_with_torsion([3; 32], messages[2], 5);
+    let mut bytes = signatures[6].to_bytes();
+    bytes[40] ^= 1;
+    signatures[6] = Signature::from_bytes(&bytes);
+    assert_eq!(invalid_indices(&messages, &signatures, &keys), [6]);
+}
+
+#[test]
+#[should_panic]
+fn test_verify_batch_rejects_mismatched_lengths() {
+    let (messages, signatures, keys) = batch(4);
+    let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+    let _ = verify_batch(&messages[..3], &signatures, &keys);
+}
+
+#[test]
+fn test_verify_strict() {
+    for (seed, public, msg, sig) in VECTORS {
+        let key = VerifyingKey::from_bytes(&hex32(public)).unwrap();
+        assert_eq!(
+            key.verify_strict(&hex(msg), &Signature::from_bytes(&hex64(sig))),
+            Ok(())
+        );
+        let (key, signature) = sign_with_torsion(hex32(seed), &hex(msg), 0);
+        assert_eq!(key.verify_strict(&hex(msg), &signature), Ok(()));
+    }
+
+    let msg: &[u8] = b"strict";
+    let (key, signature) = sign_with_torsion([5; 32], msg, 0);
+    let mut bytes = signature.to_bytes();
+    bytes[63] |= 0xF0;
+    assert_eq!(
+        key.verify_strict(msg, &Signature::from_bytes(&bytes)),
+        Err(SignatureError::NonCanonicalScalar)
+    );
+    // A signature with torsion in R doesn't pass either.
+    let (key, signature) = sign_with_torsion([5; 32], msg, 3);
+    assert_eq!(
+        key.verify_strict(msg, &signature),
+        Err(SignatureError::InvalidSignature)
+    );
+}
+
+#[test]
+fn test_verify_strict_rejects_small_order_points() {
+    let msg: &[u8] = b"small order";
+    let identity = EdwardsPoint::IDENTITY.compress();
+
+    // With the identity as a key, s⋅B = R is a valid signature on every message.
+    let key = VerifyingKey::from_bytes(identity.as_bytes()).unwrap();
+    let r = Scalar::from(1234u64);
+    let big_r = (&r * &ED25519_BASEPOINT_TABLE).compress();
+    for msg in [msg, b"another message"] {
+        let signature = sign_raw(
+            &Scalar::ZERO,
+            identity.as_bytes(),
+            &r,
+            big_r.as_bytes(),
+            msg,
+        );
+        assert_eq!(key.verify(msg, &signature), Ok(()));
+        assert_eq!(
+            key.verify_strict(msg, &signature),
+            Err(SignatureError::SmallOrderPoint)
+        );
+    }
+
+    // With R the identity, s = k⋅a is valid, and needs no nonce.
+    let seed = [6; 32];
+    let key = SigningKey::from_seed(seed).verifying_key();
+    let signature = sign_raw(
+        &secret_scalar(seed),
+        key.as_bytes(),
+        &Scalar::ZERO,
+        identity.as_bytes(),
+        msg,
+    );
+    assert_eq!(key.verify(msg, &signature), Ok(()));
+    assert_eq!(
+        key.verify_strict(msg, &signature),
+        Err(SignatureError::SmallOrderPoint)
+    );
+
+    // Every key of small order gets rejected.
+    let signature = SigningKey::from_seed(seed).sign(msg);
+    for point in EIGHT_TORSION {
+        let key = VerifyingKey::from_bytes(point.compress().as_bytes()).unwrap();
+        assert_eq!(
+            key.verify_strict(msg, &signature),
+            Err(SignatureError::SmallOrderPoint)
+        );
+    }
+}
+
+#[test]
+fn test_verify_strict_rejects_non_canonical_keys() {
+    // y = P + 9, and a few others below 2²⁵⁵, encode points of large order, which
+    // ZIP-215 decoding accepts, but aren't canonical encodings.
+    let msg: &[u8] = b"non-canonical";
+    let signature = SigningKey::from_seed([7; 32]).sign(msg);
+    for y in [9u8, 10, 14, 15, 16, 18] {
+        for sign in [0, 0x80] {
+            let mut bytes =
+                hex32("edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f");
+            bytes[0] += y;
+            bytes[31] |= sign;
+            assert!(VerifyingKey::from_bytes(&bytes).is_err());
+            let key = VerifyingKey::from_bytes_zip215(&bytes).unwrap();
+            assert!(!bool::from(key.to_edwards().is_small_order()));
+            assert_eq!(
+                key.verify_strict(msg, &signature),
+                Err(SignatureError::InvalidKey)
+            );
+            // The canonical encoding of the same point gets past the key checks.
+            let key = VerifyingKey::from_bytes(key.to_edwards().compress().as_bytes()).unwrap();
+            assert_eq!(
+                key.verify_strict(msg, &signature),
+                Err(SignatureError::InvalidSignature)
+            );
+        }
+    }
+}
+
+/// The 14 encodings of points of small order, including the non-canonical ones.
+fn small_order_encodings() -> Vec<[u8; 32]> {
+    let mut out: Vec<[u8; 32]> = EIGHT_TORSION
+        .iter()
+        .map(|p| p.compress().to_bytes())
+        .collect();
+    // x = 0 with the sign bit set, for y = 1, and y = -1.
+    for y in [
+        "0100000000000000000000000000000000000000000000000000000000000000",
+        "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+    ] {
+        let mut bytes = hex32(y);
+        bytes[31] |= 0x80;
+        out.push(bytes);
+    }
+    // y = P, and y = P + 1, which are y = 0, and y = 1, with either sign bit.
+    for y in [
+        "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+    ] {
+        let mut bytes = hex32(y);
+        out.push(bytes);
+        bytes[31] |= 0x80;
+        out.push(bytes);
+    }
+    out
+}
+
+#[test]
+fn test_verify_zip215() {
+    for (_, public, msg, sig) in VECTORS {
+        let key = VerifyingKey::from_bytes_zip215(&hex32(public)).unwrap();
+        assert_eq!(
+            key.verify_zip215(&hex(msg), &Signature::from_bytes(&hex64(sig))),
+            Ok(())
+        );
+    }
+    // Torsion in R is accepted, since the equation is multiplied by the cofactor.
+    let msg: &[u8] = b"zip215";
+    for i in 0..8 {
+        let (key, signature) = sign_with_torsion([9; 32], msg, i);
+        assert_eq!(key.verify_zip215(msg, &signature), Ok(()));
+        assert_eq!(
+            key.verify_zip215(b"other", &signature),
+            Err(SignatureError::InvalidSignature)
+        );
+    }
+
+    let (key, signature) = sign_with_torsion([9; 32], msg, 0);
+    let mut bytes = signature.to_bytes();
+    bytes[63] |= 0xF0;
+    assert_eq!(
+        key.verify_zip215(msg, &Signature::from_bytes(&bytes)),
+        Err(SignatureError::NonCanonicalScalar)
+    );
+    // R still needs to be a point.
+    bytes = signature.to_bytes();
+    bytes[..32].copy_from_slice(&[0; 32]);
+    bytes[0] = 2;
+    assert_eq!(
+        key.verify_zip215(msg, &Signature::from_bytes(&bytes)),
+        Err(SignatureError::InvalidSignature)
+    );
+}
+
+#[test]
+fn test_verify_zip215_small_order_encodings() {
+    // From ZIP-215: with s = 0, every combination of small order encodings, canonical
+    // or not, for A and R, makes a valid signature.
+    let encodings = small_order_encodings();
+    assert_eq!(encodings.len(), 14);
+    let msg: &[u8] = b"Zcash";
+    for a in &encodings {
+        let key = VerifyingKey::from_bytes_zip215(a).unwrap();
+        assert_eq!(key.to_bytes(), *a);
+        for r in &encodings {
+            let mut bytes = [0u8; 64];
+            bytes[..32].copy_from_slice(r);
+            let signature = Signature::from_bytes(&bytes);
+            assert_eq!(key.verify_zip215(msg, &signature), Ok(()));
+            // Strict verification checks the encoding of A, then decodes R strictly,
+            // before rejecting small orders.
+            let expected = if VerifyingKey::from_bytes(a).is_err() {
+                SignatureError::InvalidKey
+            } else if VerifyingKey::from_bytes(r).is_ok() {
+                SignatureError::SmallOrderPoint
+            } else {
+                SignatureError::InvalidSignature
+            };
+            assert_eq!(key.verify_strict(msg, &signature), Err(expected));
+        }
+    }
+    // Only the canonical encodings pass strict decoding.
+    let canonical = encodings
+        .iter()
+        .filter(|a| VerifyingKey::from_bytes(a).is_ok())
+        .count();
+    assert_eq!(canonical, 8);
+}
+
+/// A deterministic generator, SplitMix64, so that failures are reproducible.
+#[cfg(feature = "rand_core")]
+struct TestRng(u64);
+
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+// Not actually secure, but enough to test generation.
+#[cfg(feature = "rand_core")]
+impl rand_core::CryptoRng for TestRng {}
+
+#[test]
+#[cfg(feature = "rand_core")]
+fn test_generate() {
+    let mut rng = TestRng(1);
+    let key = SigningKey::generate(&mut rng);
+    let other = SigningKey::generate(&mut rng);
+    assert_ne!(key.to_seed(), other.to_seed());
+    assert_ne!(key.verifying_key(), other.verifying_key());
+
+    // Generating is the same as picking a seed at random.
+    let mut seed = [0u8; 32];
+    rand_core::RngCore::fill_bytes(&mut TestRng(1), &mut seed);
+    assert_eq!(key.to_seed(), seed);
+    assert_eq!(
+        SigningKey::from_seed(seed).verifying_key(),
+        key.verifying_key()
+    );
+
+    let msg = b"generated";
+    assert_eq!(key.verifying_key().verify(msg, &key.sign(msg)), Ok(()));
+}
@@ -0,0 +1,105 @@
+//! Exhaustive tests of the generic prime field arithmetic, using tiny moduli.
+use ck_dodo::testing::{
+    BarrettFp251, BarrettFp64, Fp251, Fp64, Montgomery32Fp251, Montgomery32Fp64, MontgomeryFp251,
+    MontgomeryFp64,
+};
+use subtle::ConstantTimeEq;
+
+/// Check every operation of a field with a single limb, on all pairs of the given values.
+macro_rules! check_field {
+    ($F:ty, $values:expr) => {{
+        let m = u128::from(<$F>::MODULUS[0]);
+        let value = |x: &$F| u128::from(u64::from_le_bytes(x.to_bytes()));
+        let values: Vec<u64> = $values;
+        for &a in &values {
+            let x = <$F>::from(a);
+            let a = u128::from(a) % m;
+            assert_eq!(value(&x), a);
+            assert_eq!(value(&-x), (m - a) % m);
+            let inverse = x.invert();
+            assert_eq!(value(&inverse), value(&x.vartime_invert()));
+            if a == 0 {
+                assert!(bool::from(inverse.is_zero()));
+            } else {
+                assert_eq!(value(&(inverse * x)), 1);
+            }
+            // Canonical encodings round trip, and anything else gets rejected.
+            let decoded = <$F>::from_canonical_bytes(&(a as u64).to_le_bytes()).unwrap();
+            assert!(bool::from(decoded.ct_eq(&x)));
+            let unreduced = (a + m).try_into().map(|b: u64| b.to_le_bytes());
+            if let Ok(bytes) = unreduced {
+                assert!(bool::from(<$F>::from_canonical_bytes(&bytes).is_none()));
+            }
+            for &b in &values {
+                let y = <$F>::from(b);
+                let b = u128::from(b) % m;
+                assert_eq!(value(&(x + y)), (a + b) % m);
+                assert_eq!(value(&(x - y)), (a + m - b) % m);
+                assert_eq!(value(&(x * y)), (a * b) % m);
+                assert_eq!(
+                    value(&<$F>::mul_add(&x, &y, &-x)),
+                    (a * b + (m - a) % m) % m
+                );
+                let mut wide = [0u8; 16];
+                wide[..8].copy_from_slice(&(a as u64).to_le_bytes());
+                wide[8..].copy_from_slice(&(b as u64).to_le_bytes());
+                let expected = (u128::from_le_bytes(wide) % m) as u64;
+                assert_eq!(value(&<$F>::from_bytes_wide(&wide)), u128::from(expected));
+            }
+        }
+    }};
+}
+
+/// All of the integers up to, and a bit past, 251.
+fn all_251() -> Vec<u64> {
+    (0..300).collect()
+}
+
+/// The integers around 0, and the modulus, as well as the ends of the 64 bit range.
+fn edges_64() -> Vec<u64> {
+    let m = Fp64::MODULUS[0];
+    (0..64)
+        .chain(m - 64..=u64::MAX)
+        .chain((1..64).map(|i| 1 << i))
+        .collect()
+}
+
+#[test]
+fn fp251_is_exhaustively_correct() {
+    check_field!(Fp251, all_251());
+}
+
+#[test]
+fn montgomery_fp251_is_exhaustively_correct() {
+    check_field!(MontgomeryFp251, all_251());
+}
+
+#[test]
+fn montgomery32_fp251_is_exhaustively_correct() {
+    check_field!(Montgomery32Fp251, all_251());
+}
+
+#[test]
+fn barrett_fp251_is_exhaustively_correct() {
+    check_field!(BarrettFp251, all_251());
+}
+
+#[test]
+fn fp64_is_correct_around_the_edges() {
+    check_field!(Fp64, edges_64());
+}
+
+#[test]
+fn montgomery_fp64_is_correct_around_the_edges() {
+    check_field!(MontgomeryFp64, edges_64());
+}
+
+#[test]
+fn montgomery32_fp64_is_correct_around_the_edges() {
+    check_field!(Montgomery32Fp64, edges_64());
+}
+
+#[test]
+fn barrett_fp64_is_correct_around_the_edges() {
+    check_field!(BarrettFp64, edges_64());
+}